@@ -0,0 +1,45 @@
+#!/usr/bin/env bash
+# Criterion baseline of `strategy_bench`, committed under `benches/baseline`
+#
+#   benches/baseline.sh save    run the benchmarks and store the results as the baseline
+#   benches/baseline.sh check   run the benchmarks against the baseline, fail if the mean
+#                               time of any of them grew by more than THRESHOLD (0.25 = 25%)
+#
+# Timings depend on the machine: save the baseline on the runner class CI uses.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+baseline=benches/baseline
+results=target/criterion
+threshold=${THRESHOLD:-0.25}
+
+case "${1:-}" in
+save)
+    cargo bench --bench strategy_bench -- --save-baseline main
+    rm -rf "$baseline"
+    (cd "$results" && find . -path '*/main/*' -type f) | while read -r file; do
+        mkdir -p "$baseline/$(dirname "$file")"
+        cp "$results/$file" "$baseline/$file"
+    done
+    ;;
+check)
+    mkdir -p "$results"
+    cp -R "$baseline/." "$results/"
+    cargo bench --bench strategy_bench -- --baseline main
+    failed=0
+    while read -r change; do
+        bench=${change#"$results"/}
+        bench=${bench%/change/estimates.json}
+        mean=$(jq '.mean.point_estimate' "$change")
+        if awk -v m="$mean" -v t="$threshold" 'BEGIN { exit !(m > t) }'; then
+            awk -v b="$bench" -v m="$mean" 'BEGIN { printf "regressed: %s %+.1f%%\n", b, m * 100 }'
+            failed=1
+        fi
+    done < <(find "$results" -path '*/change/estimates.json')
+    exit "$failed"
+    ;;
+*)
+    echo "usage: $0 save|check" >&2
+    exit 2
+    ;;
+esac
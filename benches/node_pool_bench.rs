@@ -0,0 +1,53 @@
+//! Node list churn with and without a `NodePool`
+//!
+//! Every iteration swaps the balancer between two disjoint 64-node lists, so all nodes
+//! are replaced on each update.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::sync::Arc;
+use volo_loadbalance::{
+    node::{Endpoint, Node, NodePool},
+    strategy::{BaseBalancer, RoundRobin},
+};
+
+const NODES: u64 = 64;
+
+fn endpoints(offset: u64) -> Vec<Endpoint> {
+    (offset..offset + NODES)
+        .map(|id| Endpoint {
+            id,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn bench_churn(c: &mut Criterion) {
+    let lists = [endpoints(0), endpoints(NODES)];
+
+    let mut group = c.benchmark_group("churn");
+    group.bench_function("update_nodes", |b| {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let mut turn = 0;
+        b.iter(|| {
+            let nodes = lists[turn % 2]
+                .iter()
+                .map(|e| Arc::new(Node::builder().endpoint(e.clone()).weight(100).build()))
+                .collect();
+            balancer.update_nodes(nodes);
+            turn += 1;
+        })
+    });
+    group.bench_function("update_nodes_pooled", |b| {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let pool = NodePool::new(NODES as usize * 2);
+        let mut turn = 0;
+        b.iter(|| {
+            balancer.update_nodes_pooled(&pool, lists[turn % 2].clone());
+            turn += 1;
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_churn);
+criterion_main!(benches);
@@ -0,0 +1,104 @@
+//! Pick latency of the built-in strategies
+//!
+//! Run with `cargo bench`. The committed baseline in `benches/baseline` is refreshed with
+//! `benches/baseline.sh save`; CI runs `benches/baseline.sh check`, which fails if a
+//! benchmark got slower than the baseline by more than `THRESHOLD` (25% by default).
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use volo_loadbalance::{
+    node::Node,
+    strategy::{
+        BalanceStrategy, CircuitBreaker, ConsistentHash, LeastConnection, LeastConnectionWeighted,
+        PeakEwma, Picker, PowerOfTwoChoices, RequestMetadata, ResponseTimeWeighted, RoundRobin,
+        WeightedRandom, WeightedRoundRobin,
+    },
+};
+
+const POOL_SIZE: u64 = 10;
+
+fn create_nodes() -> Arc<Vec<Arc<Node>>> {
+    Arc::new(
+        (0..POOL_SIZE)
+            .map(|i| {
+                Arc::new(
+                    Node::builder()
+                        .id(i)
+                        .weight(10 + i as u32 * 10)
+                        .initial_rtt_ns(1_000_000 + i * 100_000)
+                        .build(),
+                )
+            })
+            .collect(),
+    )
+}
+
+fn strategies() -> Vec<(&'static str, Box<dyn BalanceStrategy>)> {
+    vec![
+        ("round_robin", Box::new(RoundRobin)),
+        ("weighted_round_robin", Box::new(WeightedRoundRobin)),
+        ("power_of_two_choices", Box::new(PowerOfTwoChoices)),
+        ("weighted_random", Box::new(WeightedRandom)),
+        ("least_connection", Box::new(LeastConnection)),
+        (
+            "least_connection_weighted",
+            Box::new(LeastConnectionWeighted),
+        ),
+        ("response_time_weighted", Box::new(ResponseTimeWeighted)),
+        ("consistent_hash", Box::new(ConsistentHash::default())),
+        ("circuit_breaker", Box::new(CircuitBreaker::new(RoundRobin))),
+        ("peak_ewma", Box::new(PeakEwma::default())),
+    ]
+}
+
+// Spread `iters` picks over `threads` threads sharing one picker, return wall time
+fn concurrent_picks(picker: &Arc<dyn Picker>, threads: u64, iters: u64) -> Duration {
+    let per_thread = iters.div_ceil(threads);
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for t in 0..threads {
+            let picker = picker.clone();
+            scope.spawn(move || {
+                let req = RequestMetadata {
+                    hash_key: Some(t),
+                    ..Default::default()
+                };
+                for _ in 0..per_thread {
+                    black_box(picker.pick(&req).unwrap());
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn bench_pick(c: &mut Criterion) {
+    let nodes = create_nodes();
+
+    let mut group = c.benchmark_group("pick");
+    for (name, strategy) in strategies() {
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+
+        group.bench_with_input(BenchmarkId::new(name, "1_thread"), &picker, |b, p| {
+            let req = RequestMetadata {
+                hash_key: Some(42),
+                ..Default::default()
+            };
+            b.iter(|| black_box(p.pick(&req).unwrap()))
+        });
+
+        for threads in [4u64, 16] {
+            group.bench_with_input(
+                BenchmarkId::new(name, format!("{threads}_threads")),
+                &picker,
+                |b, p| b.iter_custom(|iters| concurrent_picks(p, threads, iters)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pick);
+criterion_main!(benches);
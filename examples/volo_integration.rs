@@ -1,3 +1,4 @@
+#[cfg(feature = "volo-adapter")]
 use std::sync::Arc;
 #[cfg(feature = "volo-adapter")]
 use volo::discovery::{Discover, StaticDiscover};
@@ -1,129 +1,159 @@
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use ahash::AHasher;
+use async_broadcast::RecvError;
+use tokio::task::JoinHandle;
 use volo::discovery::{Change, Discover, Instance};
 use volo::net::Address;
 
 use volo::loadbalance::error::LoadBalanceError;
-use volo::loadbalance::LoadBalance;
+use volo::loadbalance::{LoadBalance, RequestHash};
 
+use crate::config::BalanceConfig;
 use crate::node::Node as InternalNode;
-use crate::strategy::{BalanceStrategy, RequestMetadata};
+use crate::strategy::{BalanceStrategy, BaseBalancer, RequestMetadata};
 
 type DiscoverKey = <volo::discovery::StaticDiscover as Discover>::Key;
 
+type NodeCache = HashMap<String, HashMap<u64, Arc<InternalNode>>>;
+
 struct PickerCacheEntry {
     picker: Arc<dyn crate::strategy::Picker>,
     signature: u64,
+    created_at: Instant,
+}
+
+/// Picker cache limits of the Volo adapter
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    /// Maximum number of cached pickers, the oldest entry is evicted beyond this
+    pub max_entries: usize,
+    /// Cached pickers older than this are rebuilt on the next request
+    pub ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            ttl: Duration::from_secs(300),
+        }
+    }
 }
 
 /// Volo LoadBalancer Adapter
-pub struct VoloLoadBalancer<S: BalanceStrategy> {
+///
+/// `K` is the key type of the `Discover` it is used with; the default fits
+/// `StaticDiscover`. Balancers created with `new_dynamic` follow the discover's `watch`
+/// stream in a background task, stopped when the balancer is dropped.
+pub struct VoloLoadBalancer<S: BalanceStrategy, K = DiscoverKey> {
     strategy: S,
-    picker_cache: Arc<parking_lot::RwLock<HashMap<String, PickerCacheEntry>>>,
-    node_cache: Arc<parking_lot::RwLock<HashMap<String, HashMap<u64, Arc<InternalNode>>>>>,
-    key_index: Arc<parking_lot::RwLock<HashMap<DiscoverKey, HashSet<String>>>>,
+    // `strategy.config_hash()`, part of every cache key
+    strategy_hash: u64,
+    cache_config: CacheConfig,
+    caches: Arc<AdapterCaches<K>>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+// Cache state shared with the `new_dynamic` watch task
+struct AdapterCaches<K> {
+    picker_cache: parking_lot::RwLock<HashMap<String, PickerCacheEntry>>,
+    node_cache: parking_lot::RwLock<NodeCache>,
+    key_index: parking_lot::RwLock<HashMap<K, HashSet<String>>>,
+    // Weight of instances registered with weight 0, `BalanceConfig::default_weight`
+    default_weight: u32,
 }
 
 impl<S: BalanceStrategy> VoloLoadBalancer<S> {
+    /// Balancer with the default `BalanceConfig`, so weight-0 instances get weight 100.
     pub fn new(strategy: S) -> Self {
+        Self::with_config(strategy, BalanceConfig::default())
+    }
+
+    /// Balancer that gives instances registered with weight 0 (e.g. by registries
+    /// without weights) `config.default_weight`.
+    pub fn with_config(strategy: S, config: BalanceConfig) -> Self {
+        Self::new_with_configs(strategy, CacheConfig::default(), config)
+    }
+}
+
+impl<S: BalanceStrategy, K: Hash + Eq + Send + Sync + 'static> VoloLoadBalancer<S, K> {
+    pub fn new_with_cache_config(strategy: S, cache_config: CacheConfig) -> Self {
+        Self::new_with_configs(strategy, cache_config, BalanceConfig::default())
+    }
+
+    pub fn new_with_configs(strategy: S, cache_config: CacheConfig, config: BalanceConfig) -> Self {
         Self {
+            strategy_hash: strategy.config_hash(),
             strategy,
-            picker_cache: Arc::new(parking_lot::RwLock::new(HashMap::new())),
-            node_cache: Arc::new(parking_lot::RwLock::new(HashMap::new())),
-            key_index: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            cache_config,
+            caches: Arc::new(AdapterCaches {
+                picker_cache: parking_lot::RwLock::new(HashMap::new()),
+                node_cache: parking_lot::RwLock::new(HashMap::new()),
+                key_index: parking_lot::RwLock::new(HashMap::new()),
+                default_weight: config.default_weight,
+            }),
+            watcher: None,
         }
     }
 
-    fn convert_instances_to_nodes(
-        &self,
-        cache_key: &str,
-        instances: &[Arc<Instance>],
-    ) -> Vec<Arc<InternalNode>> {
-        self.sync_instances(cache_key, instances)
+    /// Balancer for a watchable `discover` that applies every `Change` it broadcasts,
+    /// like `rebalance` does, from a background task.
+    ///
+    /// Without a `watch` stream this is the same as `new`. Must be called within a Tokio
+    /// runtime.
+    pub fn new_dynamic<D: Discover<Key = K>>(strategy: S, discover: &D) -> Self
+    where
+        K: Clone,
+    {
+        let mut lb = Self::new_with_cache_config(strategy, CacheConfig::default());
+        let Some(mut changes) = discover.watch(None) else {
+            return lb;
+        };
+        let caches = lb.caches.clone();
+        lb.watcher = Some(tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(change) => caches.rebalance(change),
+                    // Every change carries the full instance list, the next one catches up
+                    Err(RecvError::Overflowed(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }));
+        lb
     }
 
-    fn sync_instances(
+    /// Apply a discovery change: cached pickers of its key are dropped and rebuilt from
+    /// `changes.all` on the next request. Same as `LoadBalance::rebalance`.
+    pub fn rebalance(&self, changes: Change<K>) {
+        self.caches.rebalance(changes);
+    }
+
+    /// Number of pickers currently cached.
+    pub fn cached_pickers(&self) -> usize {
+        self.caches.picker_cache.read().len()
+    }
+
+    fn convert_instances_to_nodes(
         &self,
         cache_key: &str,
         instances: &[Arc<Instance>],
     ) -> Vec<Arc<InternalNode>> {
-        let cache_key_owned = cache_key.to_owned();
-        let mut state_guard = self.node_cache.write();
-        let mut seen = HashSet::with_capacity(instances.len());
-        let mut nodes = Vec::with_capacity(instances.len());
-
-        let should_remove = {
-            let nodes_map = state_guard
-                .entry(cache_key_owned.clone())
-                .or_insert_with(HashMap::new);
-
-            for instance in instances {
-                let node_id = Self::compute_instance_id(instance);
-                let endpoint = crate::node::Endpoint {
-                    id: node_id,
-                    address: instance.address.clone(),
-                };
-                let weight = instance.weight;
-
-                let node = match nodes_map.get(&node_id) {
-                    Some(existing)
-                        if existing.weight == weight
-                            && existing.endpoint.address == endpoint.address =>
-                    {
-                        existing.clone()
-                    }
-                    Some(existing) => {
-                        let rebuilt = Arc::new(existing.clone_with_metadata(endpoint, weight));
-                        nodes_map.insert(node_id, rebuilt.clone());
-                        rebuilt
-                    }
-                    None => {
-                        let node = Arc::new(InternalNode::new(endpoint, weight));
-                        nodes_map.insert(node_id, node.clone());
-                        node
-                    }
-                };
-
-                nodes.push(node);
-                seen.insert(node_id);
-            }
-
-            nodes_map.retain(|id, _| seen.contains(id));
-            nodes_map.is_empty()
-        };
-
-        if should_remove {
-            state_guard.remove(&cache_key_owned);
-        }
-
+        let nodes = self.caches.sync_instances(cache_key, instances);
+        // The strategy is shared by every cache key, so prune only nodes none of them has
+        self.strategy.prune(&self.caches.live_ids());
         nodes
     }
 
-    fn compute_instance_id(instance: &Instance) -> u64 {
-        let mut hasher = AHasher::default();
-        instance.address.hash(&mut hasher);
-
-        if !instance.tags.is_empty() {
-            let mut tags: Vec<_> = instance.tags.iter().collect();
-            tags.sort_by(|a, b| a.0.cmp(b.0).then_with(|| a.1.cmp(b.1)));
-            for (k, v) in tags {
-                k.hash(&mut hasher);
-                v.hash(&mut hasher);
-            }
-        }
-
-        hasher.finish()
-    }
-
-    fn get_cache_key(
-        &self,
-        endpoint: &volo::context::Endpoint,
-        discover_key: &DiscoverKey,
-    ) -> String {
+    /// Key the picker for `endpoint` is cached under: the service name followed by a
+    /// hash of the endpoint, the discover key and the strategy's `config_hash`.
+    pub fn cache_key(&self, endpoint: &volo::context::Endpoint, discover_key: &K) -> String {
         let mut hasher = AHasher::default();
+        hasher.write_u64(self.strategy_hash);
         endpoint.service_name.hash(&mut hasher);
         if let Some(addr) = &endpoint.address {
             addr.hash(&mut hasher);
@@ -155,15 +185,105 @@ impl<S: BalanceStrategy> VoloLoadBalancer<S> {
         format!("{}:{:016x}", endpoint.service_name, hasher.finish())
     }
 
-    fn update_key_index(&self, discover_key: DiscoverKey, cache_key: String) {
-        let mut index = self.key_index.write();
-        index
-            .entry(discover_key)
-            .or_insert_with(HashSet::new)
-            .insert(cache_key);
+    fn update_key_index(&self, discover_key: K, cache_key: String) {
+        let mut index = self.caches.key_index.write();
+        index.entry(discover_key).or_default().insert(cache_key);
+    }
+
+    // Make room for `cache_key`: drop expired entries, then the oldest ones over capacity.
+    // Evicted keys also release their node state; `cache_key` itself keeps its nodes so
+    // a rebuilt picker carries over runtime stats.
+    fn evict_entries(
+        &self,
+        cache: &mut HashMap<String, PickerCacheEntry>,
+        cache_key: &str,
+        now: Instant,
+    ) {
+        let ttl = self.cache_config.ttl;
+        let mut evicted: Vec<String> = cache
+            .iter()
+            .filter(|(key, entry)| {
+                key.as_str() != cache_key && now.duration_since(entry.created_at) >= ttl
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &evicted {
+            cache.remove(key);
+        }
+
+        let max_entries = self.cache_config.max_entries.max(1);
+        while !cache.contains_key(cache_key) && cache.len() >= max_entries {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.created_at)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => {
+                    cache.remove(&key);
+                    evicted.push(key);
+                }
+                None => break,
+            }
+        }
+
+        if evicted.is_empty() {
+            return;
+        }
+
+        {
+            let mut node_cache = self.caches.node_cache.write();
+            for key in &evicted {
+                node_cache.remove(key);
+            }
+        }
+
+        let mut index = self.caches.key_index.write();
+        index.retain(|_, set| {
+            for key in &evicted {
+                set.remove(key);
+            }
+            !set.is_empty()
+        });
     }
+}
 
-    fn handle_rebalance(&self, changes: Change<DiscoverKey>) {
+impl<S: BalanceStrategy, K> Drop for VoloLoadBalancer<S, K> {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.take() {
+            watcher.abort();
+        }
+    }
+}
+
+impl<K: Hash + Eq> AdapterCaches<K> {
+    fn sync_instances(
+        &self,
+        cache_key: &str,
+        instances: &[Arc<Instance>],
+    ) -> Vec<Arc<InternalNode>> {
+        let mut state_guard = self.node_cache.write();
+        let nodes_map = state_guard.entry(cache_key.to_owned()).or_default();
+        let nodes = sync_node_map(nodes_map, instances, self.default_weight);
+        if nodes_map.is_empty() {
+            state_guard.remove(cache_key);
+        }
+        nodes
+    }
+
+    // Sorted ids of the nodes cached under any key
+    fn live_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self
+            .node_cache
+            .read()
+            .values()
+            .flat_map(|nodes| nodes.keys().copied())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    fn rebalance(&self, changes: Change<K>) {
         let cache_keys = {
             let index = self.key_index.read();
             index
@@ -199,45 +319,46 @@ impl<S: BalanceStrategy> VoloLoadBalancer<S> {
     }
 }
 
-impl<S: BalanceStrategy + 'static> LoadBalance<volo::discovery::StaticDiscover>
-    for VoloLoadBalancer<S>
-{
+impl<S: BalanceStrategy + 'static, D: Discover> LoadBalance<D> for VoloLoadBalancer<S, D::Key> {
     type InstanceIter = VoloInstanceIter;
 
     async fn get_picker(
         &self,
         endpoint: &volo::context::Endpoint,
-        discover: &volo::discovery::StaticDiscover,
+        discover: &D,
     ) -> Result<Self::InstanceIter, LoadBalanceError> {
         let discover_key = discover.key(endpoint);
 
         // Get instances from service discovery first to avoid stale cache
-        let instances = discover
-            .discover(endpoint)
-            .await
-            .map_err(|e| LoadBalanceError::Discover(Box::new(e)))?;
+        let instances = discover.discover(endpoint).await.map_err(Into::into)?;
 
+        let hash_key = endpoint.get::<RequestHash>().map(|h| h.0);
         let signature = instances_signature(&instances);
-        let cache_key = self.get_cache_key(endpoint, &discover_key);
+        let cache_key = self.cache_key(endpoint, &discover_key);
 
         // Check cache with signature guard
         {
-            let cache = self.picker_cache.read();
+            let cache = self.caches.picker_cache.read();
             if let Some(entry) = cache.get(&cache_key) {
-                if entry.signature == signature {
+                if entry.signature == signature
+                    && entry.created_at.elapsed() < self.cache_config.ttl
+                {
                     return Ok(VoloInstanceIter {
                         picker: entry.picker.clone(),
+                        hash_key,
+                        service_name: endpoint.service_name(),
                     });
                 }
             }
         }
 
         if instances.is_empty() {
-            // When no available instances are found, return a custom error
-            return Err(LoadBalanceError::from(Box::<
-                dyn std::error::Error + Send + Sync,
-            >::from(
-                "No available instances found"
+            // volo's error has no variant for it; boxed as a typed error callers can
+            // downcast, unlike a failed discovery
+            return Err(LoadBalanceError::Discover(Box::new(
+                crate::error::LoadBalanceError::EmptyInstances {
+                    service: endpoint.service_name_ref().to_owned(),
+                },
             )));
         }
 
@@ -246,47 +367,163 @@ impl<S: BalanceStrategy + 'static> LoadBalance<volo::discovery::StaticDiscover>
         let nodes_arc = Arc::new(nodes);
 
         // Create picker
-        let picker = self.strategy.build_picker(nodes_arc);
+        let picker = self
+            .strategy
+            .build_picker(nodes_arc)
+            .map_err(|e| LoadBalanceError::Discover(Box::new(e)))?;
 
         // Update cache
         {
-            let mut cache = self.picker_cache.write();
+            let now = Instant::now();
+            let mut cache = self.caches.picker_cache.write();
+            self.evict_entries(&mut cache, &cache_key, now);
             cache.insert(
                 cache_key.clone(),
                 PickerCacheEntry {
                     picker: picker.clone(),
                     signature,
+                    created_at: now,
                 },
             );
         }
 
         self.update_key_index(discover_key, cache_key);
 
-        Ok(VoloInstanceIter { picker })
+        Ok(VoloInstanceIter {
+            picker,
+            hash_key,
+            service_name: endpoint.service_name(),
+        })
     }
 
-    fn rebalance(&self, changes: Change<<volo::discovery::StaticDiscover as Discover>::Key>) {
-        self.handle_rebalance(changes);
+    fn rebalance(&self, changes: Change<D::Key>) {
+        self.caches.rebalance(changes);
     }
 }
 
 /// Volo Instance Iterator
+///
+/// Ends at the first failed pick, or at a node whose address has no volo equivalent
+/// (a `Named` address that is not a Unix socket path).
 pub struct VoloInstanceIter {
     picker: Arc<dyn crate::strategy::Picker>,
+    hash_key: Option<u64>,
+    // Forwarded as `RequestMetadata::service_name`
+    service_name: faststr::FastStr,
+}
+
+impl VoloInstanceIter {
+    /// Set the hash key forwarded to the picker, required by `ConsistentHash`.
+    ///
+    /// `get_picker` already fills it from a `volo::loadbalance::RequestHash` endpoint tag.
+    pub fn with_hash_key(mut self, key: u64) -> Self {
+        self.hash_key = Some(key);
+        self
+    }
 }
 
 impl Iterator for VoloInstanceIter {
     type Item = Address;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: self.hash_key,
+            service_name: Some(self.service_name.clone()),
+            ..Default::default()
+        };
         match self.picker.pick(&req) {
-            Ok(node) => Some(node.endpoint.address.clone()),
+            Ok(node) => Address::try_from(&node.endpoint.address).ok(),
             Err(_) => None,
         }
     }
 }
 
+/// Balancer kept up to date by a `Discover::watch` stream
+///
+/// `spawn` loads the current instances of an endpoint and starts a task that applies every
+/// later `Change` for the endpoint's discover key through
+/// `BaseBalancer::update_nodes_from_instances`. Nodes are matched by instance id across
+/// changes, so unchanged nodes keep their runtime stats and nodes with a new weight or
+/// zone carry them over; instances with weight 0 get `BalanceConfig::default_weight`.
+/// The task stops on
+/// `shutdown`, when the balancer is dropped, or when the watch channel closes.
+pub struct AutoRefreshBalancer<S: BalanceStrategy> {
+    balancer: Arc<BaseBalancer<S>>,
+    task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl<S: BalanceStrategy + 'static> AutoRefreshBalancer<S> {
+    pub fn new(strategy: S) -> Self {
+        Self::with_config(strategy, BalanceConfig::default())
+    }
+
+    /// Instances registered with weight 0 get `config.default_weight`.
+    pub fn with_config(strategy: S, config: BalanceConfig) -> Self {
+        Self {
+            balancer: Arc::new(BaseBalancer::with_config(strategy, config)),
+            task: parking_lot::Mutex::new(None),
+        }
+    }
+
+    /// The balancer holding the current nodes, e.g. for `picker()`.
+    pub fn balancer(&self) -> &BaseBalancer<S> {
+        &self.balancer
+    }
+
+    /// Load the instances of `endpoint` and follow their changes in a background task.
+    ///
+    /// Replaces the task of an earlier call. If `discover` does not support `watch`, the
+    /// nodes are loaded once and no task is started. Must be called within a Tokio runtime.
+    pub async fn spawn<D: Discover>(
+        &self,
+        discover: &D,
+        endpoint: &volo::context::Endpoint,
+    ) -> Result<(), LoadBalanceError> {
+        let key = discover.key(endpoint);
+        let instances = discover.discover(endpoint).await.map_err(Into::into)?;
+
+        self.balancer.update_nodes_from_instances(&instances);
+
+        let Some(mut changes) = discover.watch(Some(std::slice::from_ref(&key))) else {
+            self.shutdown();
+            return Ok(());
+        };
+        let balancer = self.balancer.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(change) if change.key == key => {
+                        balancer.update_nodes_from_instances(&change.all);
+                    }
+                    Ok(_) => {}
+                    // Every change carries the full instance list, the next one catches up
+                    Err(RecvError::Overflowed(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        if let Some(old) = self.task.lock().replace(task) {
+            old.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop following changes; the current nodes stay in place.
+    pub fn shutdown(&self) {
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+    }
+}
+
+impl<S: BalanceStrategy> Drop for AutoRefreshBalancer<S> {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.get_mut().take() {
+            task.abort();
+        }
+    }
+}
+
 // Convenience constructors for various strategies
 pub fn round_robin() -> VoloLoadBalancer<crate::strategy::RoundRobin> {
     VoloLoadBalancer::new(crate::strategy::RoundRobin)
@@ -308,6 +545,10 @@ pub fn least_connection() -> VoloLoadBalancer<crate::strategy::LeastConnection>
     VoloLoadBalancer::new(crate::strategy::LeastConnection)
 }
 
+pub fn least_connection_weighted() -> VoloLoadBalancer<crate::strategy::LeastConnectionWeighted> {
+    VoloLoadBalancer::new(crate::strategy::LeastConnectionWeighted)
+}
+
 pub fn response_time_weighted() -> VoloLoadBalancer<crate::strategy::ResponseTimeWeighted> {
     VoloLoadBalancer::new(crate::strategy::ResponseTimeWeighted)
 }
@@ -316,6 +557,80 @@ pub fn consistent_hash() -> VoloLoadBalancer<crate::strategy::ConsistentHash> {
     VoloLoadBalancer::new(crate::strategy::ConsistentHash::default())
 }
 
+// Node per instance, reusing the node of `nodes_map` with the same id. Ids missing from
+// `instances` are dropped from the map. Instances with weight 0 get `default_weight`.
+pub(crate) fn sync_node_map(
+    nodes_map: &mut HashMap<u64, Arc<InternalNode>>,
+    instances: &[Arc<Instance>],
+    default_weight: u32,
+) -> Vec<Arc<InternalNode>> {
+    let mut seen = HashSet::with_capacity(instances.len());
+    let mut nodes = Vec::with_capacity(instances.len());
+
+    for instance in instances {
+        let node_id = compute_instance_id(instance);
+        let endpoint = crate::node::Endpoint {
+            id: node_id,
+            address: instance.address.clone().into(),
+            zone: instance
+                .tags
+                .get("zone")
+                .map(|zone| faststr::FastStr::new(zone.as_ref())),
+        };
+        let weight = match instance.weight {
+            0 => default_weight,
+            weight => weight,
+        };
+
+        let node = match nodes_map.get(&node_id) {
+            Some(existing)
+                if existing.weight() == weight
+                    && existing.endpoint.address == endpoint.address
+                    && existing.endpoint.zone == endpoint.zone =>
+            {
+                existing.clone()
+            }
+            Some(existing) => {
+                let rebuilt = Arc::new(existing.clone_with_metadata(endpoint, weight));
+                nodes_map.insert(node_id, rebuilt.clone());
+                rebuilt
+            }
+            None => {
+                let node = Arc::new(
+                    crate::node::NodeBuilder::default()
+                        .endpoint(endpoint)
+                        .weight(weight)
+                        .build(),
+                );
+                nodes_map.insert(node_id, node.clone());
+                node
+            }
+        };
+
+        nodes.push(node);
+        seen.insert(node_id);
+    }
+
+    nodes_map.retain(|id, _| seen.contains(id));
+    nodes
+}
+
+fn compute_instance_id(instance: &Instance) -> u64 {
+    let mut hasher = AHasher::default();
+    instance.address.hash(&mut hasher);
+
+    if !instance.tags.is_empty() {
+        let mut tags: Vec<_> = instance.tags.iter().collect();
+        tags.sort_by(|a, b| a.0.cmp(b.0).then_with(|| a.1.cmp(b.1)));
+        for (k, v) in tags {
+            k.hash(&mut hasher);
+            v.hash(&mut hasher);
+        }
+    }
+
+    hasher.finish()
+}
+
 fn instances_signature(instances: &[Arc<Instance>]) -> u64 {
     let mut h = AHasher::default();
     for inst in instances {
@@ -0,0 +1,56 @@
+//! Pick throughput harness
+//!
+//! Builds a picker once and times a tight loop of picks, so strategies can be compared
+//! on the same nodes in the caller's own environment. Each request carries its index as
+//! `hash_key`, which lets the hashing strategies run unchanged.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::LoadBalanceError;
+use crate::node::Node;
+use crate::strategy::{BalanceStrategy, RequestMetadata};
+
+/// Outcome of [`run_pick_bench`]
+#[derive(Clone, Debug)]
+pub struct BenchResult {
+    /// Number of picks made
+    pub requests: usize,
+    /// Wall time spent picking, excluding the picker build
+    pub elapsed: Duration,
+    /// Picks completed per second
+    pub picks_per_sec: f64,
+    /// Picks per node id
+    pub distribution: HashMap<u64, usize>,
+}
+
+/// Build `strategy` over `nodes` and pick `requests` times.
+///
+/// Stops at the first failed pick and returns its error.
+pub fn run_pick_bench<S: BalanceStrategy + ?Sized>(
+    strategy: &S,
+    nodes: Arc<Vec<Arc<Node>>>,
+    requests: usize,
+) -> Result<BenchResult, LoadBalanceError> {
+    let picker = strategy.build_picker(nodes)?;
+    let mut req = RequestMetadata::default();
+    let mut distribution = HashMap::new();
+
+    let start = Instant::now();
+    for i in 0..requests {
+        req.hash_key = Some(i as u64);
+        let node = picker.pick(&req)?;
+        *distribution.entry(node.endpoint.id).or_insert(0) += 1;
+    }
+    let elapsed = start.elapsed();
+
+    // Guard against a zero reading on coarse clocks
+    let secs = elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+    Ok(BenchResult {
+        requests,
+        elapsed,
+        picks_per_sec: requests as f64 / secs,
+        distribution,
+    })
+}
@@ -6,12 +6,16 @@ pub struct NodeMeta {
 #[derive(Clone, Debug)]
 pub struct BalanceConfig {
     pub default_weight: u32,
+    /// In-flight requests a node may hold before picks skip it, enforced by wrapping the
+    /// strategy with `CapacityLimited::from_config`. `None` means no cap.
+    pub max_in_flight_per_node: Option<usize>,
 }
 
 impl Default for BalanceConfig {
     fn default() -> Self {
         Self {
             default_weight: 100,
+            max_in_flight_per_node: None,
         }
     }
 }
@@ -1,9 +1,56 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+
 use thiserror::Error;
 
+/// Errors returned by strategies and pickers
+///
+/// New variants may be added in minor releases, so matches outside this crate need a
+/// wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum LoadBalanceError {
     #[error("no available nodes")]
     NoAvailableNodes,
     #[error("hash key missing")]
     MissingHashKey,
+    #[error("weight {weight} of node {node_id} overflows the supported range")]
+    WeightOverflow { node_id: u64, weight: u32 },
+    #[error("{weights} weights given for {nodes} nodes")]
+    WeightCountMismatch { nodes: usize, weights: usize },
+    #[error("{requested} distinct nodes requested but only {available} available")]
+    InsufficientNodes { requested: usize, available: usize },
+    #[error("virtual_factor must be at least 1")]
+    ZeroVirtualFactor,
+    #[error("unknown tenant: {0}")]
+    UnknownTenant(String),
+    #[error("every candidate node is at its rate limit or in-flight cap")]
+    Overloaded,
+    #[error("no node has a positive weight")]
+    InvalidWeights,
+    #[error("pick did not complete before its deadline")]
+    Timeout,
+    /// Discovery succeeded but returned no instance, unlike a failed discovery
+    #[error("discovery returned no instances for service {service}")]
+    EmptyInstances { service: String },
+    #[error("node {0} not found")]
+    NodeNotFound(u64),
+    #[error("node {0} already exists")]
+    DuplicateNode(u64),
+    /// Strategy-defined failure, displayed as the inner error
+    #[error(transparent)]
+    Custom(Box<dyn core::error::Error + Send + Sync>),
+}
+
+// Static assertion: the error stays usable as a boxed `dyn Error` across threads
+const _: fn() = || {
+    fn assert_impl_all<T: core::error::Error + Send + Sync + 'static>() {}
+    assert_impl_all::<LoadBalanceError>();
+};
+
+impl LoadBalanceError {
+    /// `Custom` error carrying just a message.
+    pub fn custom(msg: impl Into<String>) -> Self {
+        Self::Custom(msg.into().into())
+    }
 }
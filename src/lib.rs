@@ -1,13 +1,38 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod adapter;
+#[cfg(feature = "bench")]
+pub mod bench;
 pub mod config;
 pub mod error;
+#[cfg(feature = "std")]
 pub mod node;
+pub mod portable;
+#[cfg(feature = "std")]
 pub mod strategy;
 
+#[cfg(feature = "std")]
 pub use strategy::{
-    BalanceStrategy, BaseBalancer, ConsistentHash, LeastConnection, Picker, PowerOfTwoChoices,
-    RequestMetadata, ResponseTimeWeighted, RoundRobin, WeightedRandom, WeightedRoundRobin,
+    BalanceStrategy, BaseBalancer, CachingBalancer, CapacityLimited, CircuitBreaker,
+    CircuitBreakerConfig, CircuitState, ConcurrencyLimited, ConcurrencyLimitedPicker,
+    ConsistentHash, DeadlineAware, DynamicWeightStrategy, FaultTolerant, FractionalWRR,
+    GradientDescent, HealthFilter, HotStandby, LeastConnection, LeastConnectionWeighted, LeastLoad,
+    MultiTenantBalancer, NodeChange, NodeDiff, NodePredicate, PeakEwma, Picker, PowerOfKChoices,
+    PowerOfTwoChoices, RandomSubset, RateAwarePicker, RequestMetadata, ResponseTimeWeighted,
+    RetryExhaustion, RetryPolicy, RetryableBalancer, RoundRobin, RoundRobinWithWeightedJump,
+    SeededPowerOfTwoChoices, Shadow, ShadowPickResult, SlowAwareWeightedRandom,
+    StaticWeightSnapshot, Sticky, StickyFailover, StrategyCompose, Tiered, TimedPickHandle,
+    TimeoutAwarePicker, TopologySpread, VirtualNodePolicy, WeightDecay, WeightedRandom,
+    WeightedRandomConfig, WeightedReservoir, WeightedRoundRobin, ZoneAware,
 };
 
+#[cfg(feature = "metrics")]
+pub use strategy::MeteredStrategy;
+#[cfg(feature = "tracing")]
+pub use strategy::TracedStrategy;
+
 #[cfg(feature = "volo-adapter")]
 pub use adapter::*;
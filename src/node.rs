@@ -1,49 +1,1181 @@
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Clone, Debug)]
+use faststr::FastStr;
+
+use crate::config::{BalanceConfig, NodeMeta};
+
+/// Address of an [`Endpoint`], the same type with or without the `volo-adapter` feature
+///
+/// Strings that parse as a `SocketAddr` become `Socket`, anything else (e.g. `host:port`
+/// to be resolved later) is kept as `Named`. Cloning never copies the name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EndpointAddress {
+    Socket(SocketAddr),
+    Named(Arc<str>),
+}
+
+// Prefix of `Named` addresses converted from volo Unix domain socket addresses
+#[cfg(feature = "volo-adapter")]
+const UNIX_PREFIX: &str = "unix:";
+
+impl EndpointAddress {
+    pub fn as_socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Socket(addr) => Some(*addr),
+            Self::Named(_) => None,
+        }
+    }
+}
+
+impl Default for EndpointAddress {
+    fn default() -> Self {
+        Self::Socket(SocketAddr::from(([0, 0, 0, 0], 0)))
+    }
+}
+
+impl fmt::Display for EndpointAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Socket(addr) => addr.fmt(f),
+            Self::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+impl From<SocketAddr> for EndpointAddress {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Socket(addr)
+    }
+}
+
+impl From<String> for EndpointAddress {
+    fn from(addr: String) -> Self {
+        match addr.parse() {
+            Ok(socket) => Self::Socket(socket),
+            Err(_) => Self::Named(addr.into()),
+        }
+    }
+}
+
+impl From<&str> for EndpointAddress {
+    fn from(addr: &str) -> Self {
+        match addr.parse() {
+            Ok(socket) => Self::Socket(socket),
+            Err(_) => Self::Named(addr.into()),
+        }
+    }
+}
+
+impl FromStr for EndpointAddress {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Infallible> {
+        Ok(s.into())
+    }
+}
+
+/// Resolves `Named` addresses like `std`'s `(&str)` impl, i.e. `host:port` via DNS.
+impl ToSocketAddrs for EndpointAddress {
+    type Iter = std::vec::IntoIter<SocketAddr>;
+
+    fn to_socket_addrs(&self) -> io::Result<Self::Iter> {
+        match self {
+            Self::Socket(addr) => Ok(vec![*addr].into_iter()),
+            Self::Named(name) => Ok(name[..].to_socket_addrs()?.collect::<Vec<_>>().into_iter()),
+        }
+    }
+}
+
+/// Unix domain socket addresses become `Named("unix:<path>")`, unnamed ones `"unix:"`.
+#[cfg(feature = "volo-adapter")]
+impl From<volo::net::Address> for EndpointAddress {
+    fn from(addr: volo::net::Address) -> Self {
+        match addr {
+            volo::net::Address::Ip(addr) => Self::Socket(addr),
+            #[cfg(unix)]
+            volo::net::Address::Unix(addr) => {
+                let path = addr.as_pathname().map(|p| p.display().to_string());
+                Self::Named(format!("{UNIX_PREFIX}{}", path.unwrap_or_default()).into())
+            }
+        }
+    }
+}
+
+/// Reverse of the `From` conversion; fails for other `Named` addresses and unnamed
+/// Unix sockets.
+#[cfg(feature = "volo-adapter")]
+impl TryFrom<&EndpointAddress> for volo::net::Address {
+    type Error = io::Error;
+
+    fn try_from(addr: &EndpointAddress) -> io::Result<Self> {
+        match addr {
+            EndpointAddress::Socket(addr) => Ok(Self::Ip(*addr)),
+            EndpointAddress::Named(name) => match name.strip_prefix(UNIX_PREFIX) {
+                #[cfg(unix)]
+                Some(path) if !path.is_empty() => {
+                    std::os::unix::net::SocketAddr::from_pathname(path).map(Self::Unix)
+                }
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("not a socket address: {name}"),
+                )),
+            },
+        }
+    }
+}
+
+/// Network identity of a [`Node`]
+///
+/// Equality and hashing look at `id` only, so endpoints (and nodes) can be deduplicated
+/// in a `HashSet` or used as `HashMap` keys even if the address or zone changed.
+#[derive(Clone, Debug, Default)]
 pub struct Endpoint {
     pub id: u64,
-    #[cfg(feature = "volo-adapter")]
-    pub address: volo::net::Address,
-    #[cfg(not(feature = "volo-adapter"))]
-    pub address: String,
+    pub address: EndpointAddress,
+    // Locality (e.g. availability zone) used by `ZoneAware`, `None` if unknown
+    pub zone: Option<FastStr>,
+}
+
+impl PartialEq for Endpoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Endpoint {}
+
+impl Hash for Endpoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// `Endpoint({id}@{address})`
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Endpoint({}@{})", self.id, self.address)
+    }
 }
 
 #[derive(Debug)]
 pub struct Node {
     pub endpoint: Endpoint,
-    pub weight: u32,
+    // Updated in place by `BaseBalancer::update_weights`, read through `Node::weight`
+    pub weight: AtomicU32,
+    // Weight used by `FractionalWRR`, see `Node::fractional_weight`
+    pub fractional_weight: Option<f64>,
     pub in_flight: AtomicUsize,
+    // Requests waiting in front of the node (e.g. in a proxy) as last reported, 0 if the
+    // backend has no queue; read by `LeastLoad`
+    pub queue_depth: AtomicU32,
     pub success: AtomicU64,
     pub fail: AtomicU64,
     pub last_rtt_ns: AtomicU64,
+    // Unix time in nanoseconds of the last `record_rtt`, 0 if the node never reported one
+    pub last_rtt_updated_at: AtomicU64,
+    // Maximum concurrent requests the node accepts, 0 means unlimited
+    pub max_in_flight: u32,
+    // Hard cap on `in_flight` enforced by `try_acquire`, `None` means unlimited. Unlike
+    // `max_in_flight`, which pickers only read, it is checked and claimed atomically
+    pub max_concurrency: Option<usize>,
+    pub priority: u32,
+    pub tags: HashMap<String, String>,
+    // Requests per second admitted by `try_acquire_token`, `None` means unlimited
+    pub rate_limit: Option<u32>,
+    // Token bucket for `rate_limit`: refill time in the upper bits, tokens in the lower
+    // `TOKEN_BITS`, 0 while untouched (a full bucket)
+    rate_bucket: AtomicU64,
+    // Administrative switch, disabled nodes stay in the list but are skipped by every picker
+    pub enabled: AtomicBool,
+    // Recent error rate, kept only if enabled with `NodeBuilder::error_window`
+    pub error_window: Option<WindowedErrorRate>,
+    // Every RTT passed to `record_rtt`, see `Node::rtt_percentile`
+    #[cfg(feature = "rtt-histogram")]
+    pub rtt_histogram: RttHistogram,
 }
 
-impl Node {
-    pub fn new(endpoint: Endpoint, weight: u32) -> Self {
+/// One-line summary for logs:
+/// `Node(id={id}, addr={address}, weight={w}, in_flight={f}, success={s}, fail={e}, rtt={r}ns)`
+///
+/// Counters are loaded one by one with `Relaxed`, so they are not a consistent snapshot.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Node(id={}, addr={}, weight={}, in_flight={}, success={}, fail={}, rtt={}ns)",
+            self.endpoint.id,
+            self.endpoint.address,
+            self.weight(),
+            self.in_flight.load(Ordering::Relaxed),
+            self.success.load(Ordering::Relaxed),
+            self.fail.load(Ordering::Relaxed),
+            self.last_rtt_ns.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// `in_flight` slot claimed by `Node::try_acquire`, released on drop
+#[must_use = "the slot is released as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct InFlightGuard {
+    node: Arc<Node>,
+}
+
+impl InFlightGuard {
+    pub fn node(&self) -> &Arc<Node> {
+        &self.node
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        // Saturating, `reset_stats` may have zeroed the counter meanwhile
+        let _ = self
+            .node
+            .in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                Some(n.saturating_sub(1))
+            });
+    }
+}
+
+/// Plain copy of a node's runtime counters, see `Node::stats_snapshot`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeStatsSnapshot {
+    pub in_flight: usize,
+    pub success: u64,
+    pub fail: u64,
+    pub last_rtt_ns: u64,
+}
+
+/// Error rate over a sliding window of time buckets
+///
+/// Unlike the cumulative `success` / `fail` counters, old results age out: `error_rate`
+/// covers the last `buckets - 1` completed buckets, the live bucket is left out until it
+/// is complete. Buckets are reused round robin and cleared lock-free on first use in a
+/// new period; results recorded concurrently with that clear may be lost.
+#[derive(Debug)]
+pub struct WindowedErrorRate {
+    buckets: Box<[WindowBucket]>,
+    bucket_duration_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct WindowBucket {
+    // Period the counts belong to, `now_ms / bucket_duration_ms + 1`, 0 while unused
+    period: AtomicU64,
+    success: AtomicU64,
+    fail: AtomicU64,
+}
+
+impl Default for WindowedErrorRate {
+    /// 60 buckets of one second.
+    fn default() -> Self {
+        Self::new(60, Duration::from_secs(1))
+    }
+}
+
+impl Clone for WindowedErrorRate {
+    fn clone(&self) -> Self {
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|b| WindowBucket {
+                period: AtomicU64::new(b.period.load(Ordering::Acquire)),
+                success: AtomicU64::new(b.success.load(Ordering::Relaxed)),
+                fail: AtomicU64::new(b.fail.load(Ordering::Relaxed)),
+            })
+            .collect();
         Self {
-            endpoint,
-            weight,
-            in_flight: AtomicUsize::new(0),
-            success: AtomicU64::new(0),
-            fail: AtomicU64::new(0),
-            last_rtt_ns: AtomicU64::new(0),
+            buckets,
+            bucket_duration_ms: self.bucket_duration_ms,
+        }
+    }
+}
+
+impl WindowedErrorRate {
+    /// Window of `buckets` (at least 2) buckets of `bucket_duration` (at least 1ms) each.
+    pub fn new(buckets: usize, bucket_duration: Duration) -> Self {
+        Self {
+            buckets: (0..buckets.max(2))
+                .map(|_| WindowBucket::default())
+                .collect(),
+            bucket_duration_ms: (bucket_duration.as_millis() as u64).max(1),
+        }
+    }
+
+    pub fn record_success(&self) {
+        self.record_at(true, bucket_clock_ms());
+    }
+
+    pub fn record_failure(&self) {
+        self.record_at(false, bucket_clock_ms());
+    }
+
+    /// Record a result at `now_ms`, milliseconds on a monotonic clock. Results older than
+    /// a bucket that was already reused are dropped.
+    pub fn record_at(&self, success: bool, now_ms: u64) {
+        let period = now_ms / self.bucket_duration_ms + 1;
+        let bucket = &self.buckets[(period % self.buckets.len() as u64) as usize];
+        let seen = bucket.period.load(Ordering::Acquire);
+        if seen > period {
+            return;
+        }
+        if seen < period
+            && bucket
+                .period
+                .compare_exchange(seen, period, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            bucket.success.store(0, Ordering::Release);
+            bucket.fail.store(0, Ordering::Release);
+        }
+        let counter = if success {
+            &bucket.success
+        } else {
+            &bucket.fail
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Failed fraction of the results in the completed buckets of the window, 0.0 without
+    /// results.
+    pub fn error_rate(&self) -> f64 {
+        self.error_rate_at(bucket_clock_ms())
+    }
+
+    /// `error_rate` as of `now_ms`, on the clock passed to `record_at`.
+    pub fn error_rate_at(&self, now_ms: u64) -> f64 {
+        let live = now_ms / self.bucket_duration_ms + 1;
+        let window = self.buckets.len() as u64;
+        let (mut success, mut fail) = (0u64, 0u64);
+        for bucket in self.buckets.iter() {
+            let period = bucket.period.load(Ordering::Acquire);
+            // Completed buckets of the last `window - 1` periods
+            if period != 0 && period < live && live - period < window {
+                success += bucket.success.load(Ordering::Relaxed);
+                fail += bucket.fail.load(Ordering::Relaxed);
+            }
+        }
+        let total = success.saturating_add(fail);
+        if total == 0 {
+            return 0.0;
+        }
+        fail as f64 / total as f64
+    }
+
+    /// Drop every recorded result.
+    pub fn clear(&self) {
+        for bucket in self.buckets.iter() {
+            bucket.period.store(0, Ordering::Release);
+            bucket.success.store(0, Ordering::Release);
+            bucket.fail.store(0, Ordering::Release);
+        }
+    }
+}
+
+// Sub-buckets per power of two of `RttHistogram`, as a bit count
+#[cfg(feature = "rtt-histogram")]
+const RTT_SUB_BITS: u32 = 3;
+#[cfg(feature = "rtt-histogram")]
+const RTT_SUB_BUCKETS: usize = 1 << RTT_SUB_BITS;
+// Exact buckets for values below `RTT_SUB_BUCKETS`, then `RTT_SUB_BUCKETS` per power of two
+#[cfg(feature = "rtt-histogram")]
+const RTT_BUCKETS: usize = (64 - RTT_SUB_BITS as usize + 1) * RTT_SUB_BUCKETS;
+
+/// Log-linear histogram of RTTs in nanoseconds
+///
+/// HDR-style: every power of two is split into 8 equal buckets, so a percentile is at
+/// most 12.5% above the recorded value, over the whole `u64` range in 4 KiB. Recording
+/// is a single relaxed `fetch_add`.
+#[cfg(feature = "rtt-histogram")]
+#[derive(Debug)]
+pub struct RttHistogram {
+    counts: Box<[AtomicU64]>,
+}
+
+#[cfg(feature = "rtt-histogram")]
+impl Default for RttHistogram {
+    fn default() -> Self {
+        Self {
+            counts: (0..RTT_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
         }
     }
+}
+
+#[cfg(feature = "rtt-histogram")]
+impl Clone for RttHistogram {
+    fn clone(&self) -> Self {
+        Self {
+            counts: self
+                .counts
+                .iter()
+                .map(|c| AtomicU64::new(c.load(Ordering::Relaxed)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "rtt-histogram")]
+impl RttHistogram {
+    pub fn record(&self, rtt_ns: u64) {
+        self.counts[Self::bucket(rtt_ns)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Recorded RTTs.
+    pub fn len(&self) -> u64 {
+        self.counts.iter().map(|c| c.load(Ordering::Relaxed)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// RTT at quantile `q` in `0.0..=1.0` (nearest rank), as the upper edge of its bucket.
+    /// `None` without recorded RTTs.
+    pub fn percentile(&self, q: f64) -> Option<u64> {
+        let counts: Vec<u64> = self
+            .counts
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .collect();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let q = if q.is_nan() { 0.0 } else { q.clamp(0.0, 1.0) };
+        let rank = ((q * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &count) in counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Self::upper_edge(i));
+            }
+        }
+        Some(Self::upper_edge(RTT_BUCKETS - 1))
+    }
+
+    pub fn clear(&self) {
+        for count in self.counts.iter() {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn bucket(value: u64) -> usize {
+        if value < RTT_SUB_BUCKETS as u64 {
+            return value as usize;
+        }
+        // Position of the highest set bit, at least `RTT_SUB_BITS`
+        let exp = 63 - value.leading_zeros();
+        let sub = (value >> (exp - RTT_SUB_BITS)) as usize & (RTT_SUB_BUCKETS - 1);
+        (exp - RTT_SUB_BITS + 1) as usize * RTT_SUB_BUCKETS + sub
+    }
+
+    // Largest value that lands in bucket `i`
+    fn upper_edge(i: usize) -> u64 {
+        if i < RTT_SUB_BUCKETS {
+            return i as u64;
+        }
+        let shift = (i / RTT_SUB_BUCKETS - 1) as u32;
+        let lower = ((RTT_SUB_BUCKETS + i % RTT_SUB_BUCKETS) as u64) << shift;
+        lower + ((1u64 << shift) - 1)
+    }
+}
+
+const TOKEN_BITS: u32 = 24;
+const TOKEN_MASK: u64 = (1 << TOKEN_BITS) - 1;
+
+// Milliseconds on a process-wide monotonic clock, starting at 1 so a stored bucket is never 0
+fn bucket_clock_ms() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_millis() as u64 + 1
+}
+
+// Nanoseconds since the unix epoch, 0 for clocks set before it
+pub(crate) fn unix_now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos().min(u64::MAX as u128) as u64)
+}
+
+// Same identity as the endpoint, ignoring weight and runtime counters
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.endpoint == other.endpoint
+    }
+}
+
+impl Eq for Node {}
+
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.endpoint.hash(state);
+    }
+}
+
+impl Node {
+    pub fn builder() -> NodeBuilder {
+        NodeBuilder::default()
+    }
+
+    #[deprecated(note = "use `NodeBuilder` instead")]
+    pub fn new(endpoint: Endpoint, weight: u32) -> Self {
+        NodeBuilder::default()
+            .endpoint(endpoint)
+            .weight(weight)
+            .build()
+    }
 
     pub fn clone_with_metadata(&self, endpoint: Endpoint, weight: u32) -> Self {
-        let node = Self::new(endpoint, weight);
+        let mut builder = NodeBuilder::default()
+            .endpoint(endpoint)
+            .weight(weight)
+            .max_in_flight(self.max_in_flight)
+            .priority(self.priority)
+            .tags(self.tags.clone());
+        builder.rate_limit = self.rate_limit;
+        builder.fractional_weight = self.fractional_weight;
+        builder.max_concurrency = self.max_concurrency;
+        let mut node = builder.build();
+        node.error_window = self.error_window.clone();
+        #[cfg(feature = "rtt-histogram")]
+        {
+            node.rtt_histogram = self.rtt_histogram.clone();
+        }
         let in_flight = self.in_flight.load(Ordering::Relaxed);
+        let queue_depth = self.queue_depth.load(Ordering::Relaxed);
         let success = self.success.load(Ordering::Relaxed);
         let fail = self.fail.load(Ordering::Relaxed);
         let last_rtt = self.last_rtt_ns.load(Ordering::Relaxed);
+        let last_rtt_updated_at = self.last_rtt_updated_at.load(Ordering::Relaxed);
 
         let cloned = node;
         cloned.in_flight.store(in_flight, Ordering::Relaxed);
+        cloned.queue_depth.store(queue_depth, Ordering::Relaxed);
         cloned.success.store(success, Ordering::Relaxed);
         cloned.fail.store(fail, Ordering::Relaxed);
         cloned.last_rtt_ns.store(last_rtt, Ordering::Relaxed);
+        cloned
+            .last_rtt_updated_at
+            .store(last_rtt_updated_at, Ordering::Relaxed);
+        cloned.set_enabled(self.is_enabled());
 
         cloned
     }
+
+    /// Current weight.
+    pub fn weight(&self) -> u32 {
+        self.weight.load(Ordering::Acquire)
+    }
+
+    /// Change the weight in place. Pickers read weights when they are built, so the new
+    /// weight takes effect with the next picker.
+    pub fn set_weight(&self, weight: u32) {
+        self.weight.store(weight, Ordering::Release);
+    }
+
+    /// Store the queue depth last reported for the node, see `LeastLoad`.
+    pub fn set_queue_depth(&self, depth: u32) {
+        self.queue_depth.store(depth, Ordering::Release);
+    }
+
+    /// Whether pickers may return this node.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    /// Enable or disable the node without removing it from discovery. Unlike draining, a
+    /// disabled node is skipped immediately, by pickers that are already built as well.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Release);
+    }
+
+    /// Fractional weight, falling back to the integer weight if none is set.
+    pub fn fractional_weight(&self) -> f64 {
+        self.fractional_weight
+            .unwrap_or_else(|| self.weight() as f64)
+    }
+
+    /// Completed requests, `success + fail`.
+    ///
+    /// Both counters are loaded with `Acquire` one after the other, not as one atomic
+    /// snapshot: under concurrent updates the sum may mix counts from slightly different
+    /// moments, off by at most the requests completing in between.
+    pub fn total_requests(&self) -> u64 {
+        let (success, fail) = self.counters();
+        success.saturating_add(fail)
+    }
+
+    /// Fraction of completed requests that succeeded, in `0.0..=1.0`.
+    ///
+    /// A node without completed requests returns 1.0, so new nodes are not penalized.
+    /// Computed from a single load of each counter, with the ordering of `total_requests`.
+    pub fn success_rate(&self) -> f64 {
+        let (success, fail) = self.counters();
+        let total = success.saturating_add(fail);
+        if total == 0 {
+            return 1.0;
+        }
+        success as f64 / total as f64
+    }
+
+    /// Current counters.
+    ///
+    /// Each counter is loaded with `Acquire` on its own, like `total_requests`: the
+    /// snapshot is consistent per counter, not across counters.
+    pub fn stats_snapshot(&self) -> NodeStatsSnapshot {
+        let (success, fail) = self.counters();
+        NodeStatsSnapshot {
+            in_flight: self.in_flight.load(Ordering::Acquire),
+            success,
+            fail,
+            last_rtt_ns: self.last_rtt_ns.load(Ordering::Acquire),
+        }
+    }
+
+    /// Store `rtt_ns` as the last RTT and stamp `last_rtt_updated_at` with the current time.
+    /// With the `rtt-histogram` feature, also add it to `rtt_histogram`.
+    pub fn record_rtt(&self, rtt_ns: u64) {
+        self.last_rtt_ns.store(rtt_ns, Ordering::Relaxed);
+        self.last_rtt_updated_at
+            .store(unix_now_ns(), Ordering::Relaxed);
+        #[cfg(feature = "rtt-histogram")]
+        self.rtt_histogram.record(rtt_ns);
+    }
+
+    /// RTT in nanoseconds at quantile `q`, e.g. 0.99, over every RTT recorded with
+    /// `record_rtt`. `None` before the first one. See `RttHistogram::percentile`.
+    #[cfg(feature = "rtt-histogram")]
+    pub fn rtt_percentile(&self, q: f64) -> Option<u64> {
+        self.rtt_histogram.percentile(q)
+    }
+
+    /// Time since the last `record_rtt` as of `now_unix_ns`, `None` if there was none.
+    pub fn rtt_staleness(&self, now_unix_ns: u64) -> Option<Duration> {
+        match self.last_rtt_updated_at.load(Ordering::Relaxed) {
+            0 => None,
+            at => Some(Duration::from_nanos(now_unix_ns.saturating_sub(at))),
+        }
+    }
+
+    /// Count a completed request in `success` or `fail`, and in `error_window` if enabled.
+    pub fn record_result(&self, success: bool) {
+        let counter = if success { &self.success } else { &self.fail };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(window) = &self.error_window {
+            if success {
+                window.record_success();
+            } else {
+                window.record_failure();
+            }
+        }
+    }
+
+    /// Error rate over `error_window`, `None` if the node keeps no window.
+    pub fn windowed_error_rate(&self) -> Option<f64> {
+        self.error_window
+            .as_ref()
+            .map(WindowedErrorRate::error_rate)
+    }
+
+    /// Whether the last reported RTT exceeds `threshold_ns`. Nodes without a reported RTT
+    /// are never slow.
+    pub fn is_slow(&self, threshold_ns: u64) -> bool {
+        self.last_rtt_ns.load(Ordering::Relaxed) > threshold_ns
+    }
+
+    /// Fraction of completed requests that failed, `fail / (success + fail)`.
+    ///
+    /// Returns 0.0 for a node without completed requests. Same ordering as `total_requests`.
+    pub fn error_rate(&self) -> f64 {
+        let (success, fail) = self.counters();
+        let total = success.saturating_add(fail);
+        if total == 0 {
+            return 0.0;
+        }
+        fail as f64 / total as f64
+    }
+
+    /// `in_flight / max_in_flight`, comparable across nodes of different capacity: 0.0 when
+    /// idle, 1.0 at the cap and above 1.0 while over it, as the cap is not enforced.
+    ///
+    /// Returns 0.0 for nodes without a cap (`max_in_flight == 0`), i.e. unknown.
+    pub fn utilization(&self) -> f64 {
+        if self.max_in_flight == 0 {
+            return 0.0;
+        }
+        self.in_flight.load(Ordering::Acquire) as f64 / self.max_in_flight as f64
+    }
+
+    /// Health in `0.0..=1.0`, higher is better: `success_rate` scaled by an RTT factor.
+    ///
+    /// The RTT factor is `1 / (1 + last_rtt / 100ms)`, so a 100ms RTT halves the score and
+    /// an unknown RTT (0) leaves the success rate as is. A node without any requests and
+    /// RTT scores 1.0; a node with only failures scores 0.0.
+    pub fn health_score(&self) -> f64 {
+        const RTT_REFERENCE_NS: f64 = 100_000_000.0;
+        let rtt = self.last_rtt_ns.load(Ordering::Acquire) as f64;
+        self.success_rate() / (1.0 + rtt / RTT_REFERENCE_NS)
+    }
+
+    /// Zero `in_flight`, `queue_depth`, `success`, `fail`, `last_rtt_ns` (with its
+    /// timestamp) and the `error_window` in place, e.g. after a config reload, keeping the
+    /// node shared with existing pickers.
+    ///
+    /// Each counter is stored with `Release` on its own; requests completing concurrently
+    /// may land before or after the reset. Requests still in flight during the reset must
+    /// decrement `in_flight` saturating (e.g. with `fetch_update`), or it wraps around.
+    pub fn reset_stats(&self) {
+        self.in_flight.store(0, Ordering::Release);
+        self.queue_depth.store(0, Ordering::Release);
+        self.success.store(0, Ordering::Release);
+        self.fail.store(0, Ordering::Release);
+        self.last_rtt_ns.store(0, Ordering::Release);
+        self.last_rtt_updated_at.store(0, Ordering::Release);
+        if let Some(window) = &self.error_window {
+            window.clear();
+        }
+        #[cfg(feature = "rtt-histogram")]
+        self.rtt_histogram.clear();
+    }
+
+    /// Claim one `in_flight` slot if the node is below `max_concurrency`.
+    ///
+    /// Check and increment are a single atomic update, so concurrent callers never push
+    /// `in_flight` past the cap. The slot is released when the guard is dropped.
+    pub fn try_acquire(self: &Arc<Self>) -> Option<InFlightGuard> {
+        let limit = self.max_concurrency.unwrap_or(usize::MAX);
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < limit).then_some(n + 1)
+            })
+            .ok()?;
+        Some(InFlightGuard { node: self.clone() })
+    }
+
+    /// Take a token from the node's `rate_limit` bucket, `false` if it is empty.
+    ///
+    /// The bucket holds up to one second of tokens (capped at 2^24 - 1) and refills
+    /// continuously at `rate_limit` per second. Always `true` without a rate limit.
+    pub fn try_acquire_token(&self) -> bool {
+        match self.rate_limit {
+            Some(rate) => self.acquire_token_at(rate, bucket_clock_ms()),
+            None => true,
+        }
+    }
+
+    fn acquire_token_at(&self, rate: u32, now: u64) -> bool {
+        if rate == 0 {
+            return false;
+        }
+        let rate = rate as u64;
+        let capacity = rate.min(TOKEN_MASK);
+
+        let mut state = self.rate_bucket.load(Ordering::Acquire);
+        loop {
+            let (mut last, mut tokens) = if state == 0 {
+                (now, capacity)
+            } else {
+                (state >> TOKEN_BITS, state & TOKEN_MASK)
+            };
+
+            // A second refills the whole bucket, capping the product below overflow
+            let refill = now.saturating_sub(last).min(1000) * rate / 1000;
+            if refill > 0 {
+                tokens = (tokens + refill).min(capacity);
+                // Keep the time not yet turned into a token for the next refill
+                last = if tokens == capacity {
+                    now
+                } else {
+                    last + refill * 1000 / rate
+                };
+            }
+            if tokens == 0 {
+                return false;
+            }
+
+            let next = (last << TOKEN_BITS) | (tokens - 1);
+            match self.rate_bucket.compare_exchange_weak(
+                state,
+                next,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return true,
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    fn counters(&self) -> (u64, u64) {
+        (
+            self.success.load(Ordering::Acquire),
+            self.fail.load(Ordering::Acquire),
+        )
+    }
+
+    // Back to the state of `NodeBuilder::default().build()`, keeping the tag map allocation
+    fn reset(&mut self) {
+        self.endpoint = Endpoint::default();
+        *self.weight.get_mut() = 0;
+        self.fractional_weight = None;
+        *self.in_flight.get_mut() = 0;
+        *self.queue_depth.get_mut() = 0;
+        *self.success.get_mut() = 0;
+        *self.fail.get_mut() = 0;
+        *self.last_rtt_ns.get_mut() = 0;
+        *self.last_rtt_updated_at.get_mut() = 0;
+        self.max_in_flight = 0;
+        self.max_concurrency = None;
+        self.priority = 0;
+        self.tags.clear();
+        self.rate_limit = None;
+        *self.rate_bucket.get_mut() = 0;
+        *self.enabled.get_mut() = true;
+        self.error_window = None;
+        #[cfg(feature = "rtt-histogram")]
+        self.rtt_histogram.clear();
+    }
+}
+
+/// Zone of `nodes_by_zone` for nodes without the zone tag
+pub const UNTAGGED_ZONE: &str = "_untagged_";
+
+/// Group `nodes` by the value of their `zone_tag` tag, keeping their order within a zone.
+///
+/// Nodes without the tag go into `UNTAGGED_ZONE`. Empty input gives an empty map.
+pub fn nodes_by_zone(nodes: &[Arc<Node>], zone_tag: &str) -> HashMap<String, Vec<Arc<Node>>> {
+    let mut zones: HashMap<String, Vec<Arc<Node>>> = HashMap::new();
+    for node in nodes {
+        let zone = node
+            .tags
+            .get(zone_tag)
+            .map_or(UNTAGGED_ZONE, String::as_str);
+        match zones.get_mut(zone) {
+            Some(members) => members.push(node.clone()),
+            None => {
+                zones.insert(zone.to_owned(), vec![node.clone()]);
+            }
+        }
+    }
+    zones
+}
+
+/// The `zone_tag` value shared by the most nodes, the smallest value on ties.
+///
+/// Untagged nodes do not form a zone here, so this is `None` if no node has the tag.
+pub fn dominant_zone(nodes: &[Arc<Node>], zone_tag: &str) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for zone in nodes.iter().filter_map(|n| n.tags.get(zone_tag)) {
+        *counts.entry(zone).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|(za, ca), (zb, cb)| ca.cmp(cb).then_with(|| zb.cmp(za)))
+        .map(|(zone, _)| zone.to_owned())
+}
+
+/// Node for `endpoint` with the configured weight
+impl From<(&Endpoint, NodeMeta)> for Node {
+    fn from((endpoint, meta): (&Endpoint, NodeMeta)) -> Self {
+        NodeBuilder::default()
+            .endpoint(endpoint.clone())
+            .weight(meta.weight)
+            .build()
+    }
+}
+
+/// Current weight of the node as stored, 0 included; see `NodeMeta::from_node`.
+impl From<&Node> for NodeMeta {
+    fn from(node: &Node) -> Self {
+        NodeMeta {
+            weight: node.weight(),
+        }
+    }
+}
+
+impl NodeMeta {
+    /// Current weight of the node; a weight of 0 becomes `config.default_weight`.
+    pub fn from_node(node: &Node, config: &BalanceConfig) -> Self {
+        let weight = match node.weight() {
+            0 => config.default_weight,
+            w => w,
+        };
+        NodeMeta { weight }
+    }
+}
+
+/// Fluent builder for [`Node`]
+///
+/// Every field is optional: the endpoint defaults to id 0 with an unspecified
+/// address, the weight to 0 and the runtime counters start at 0. `id`, `address` and
+/// `zone` override the corresponding fields of `endpoint` regardless of order.
+#[derive(Clone, Debug, Default)]
+pub struct NodeBuilder {
+    endpoint: Option<Endpoint>,
+    id: Option<u64>,
+    address: Option<EndpointAddress>,
+    zone: Option<FastStr>,
+    weight: u32,
+    fractional_weight: Option<f64>,
+    max_in_flight: u32,
+    max_concurrency: Option<usize>,
+    initial_rtt_ns: u64,
+    priority: u32,
+    tags: HashMap<String, String>,
+    rate_limit: Option<u32>,
+    error_window: Option<WindowedErrorRate>,
+}
+
+impl NodeBuilder {
+    pub fn endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the address; accepts anything convertible into an [`EndpointAddress`], e.g. a
+    /// `SocketAddr`, a `&str` / `String`, or a `volo::net::Address` with `volo-adapter`.
+    pub fn address(mut self, address: impl Into<EndpointAddress>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    pub fn zone(mut self, zone: impl Into<FastStr>) -> Self {
+        self.zone = Some(zone.into());
+        self
+    }
+
+    pub fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Weight for `FractionalWRR`, e.g. a normalized CPU share like 0.5.
+    pub fn fractional_weight(mut self, weight: f64) -> Self {
+        self.fractional_weight = Some(weight);
+        self
+    }
+
+    /// Maximum concurrent requests, 0 means unlimited.
+    pub fn max_in_flight(mut self, max_in_flight: u32) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+
+    /// Hard concurrency cap enforced by `Node::try_acquire`.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Seed `last_rtt_ns` so RTT-based strategies have a value before the first response.
+    pub fn initial_rtt_ns(mut self, rtt_ns: u64) -> Self {
+        self.initial_rtt_ns = rtt_ns;
+        self
+    }
+
+    pub fn tags(mut self, tags: HashMap<String, String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Add a single tag, keeping the ones already set.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Requests per second admitted by `Node::try_acquire_token`, unlimited if unset.
+    pub fn rate_limit(mut self, rps: u32) -> Self {
+        self.rate_limit = Some(rps);
+        self
+    }
+
+    /// Track the error rate over `buckets` buckets of `bucket_duration`, see
+    /// `WindowedErrorRate`. Off by default.
+    pub fn error_window(mut self, buckets: usize, bucket_duration: Duration) -> Self {
+        self.error_window = Some(WindowedErrorRate::new(buckets, bucket_duration));
+        self
+    }
+
+    pub fn build(self) -> Node {
+        let mut endpoint = self.endpoint.unwrap_or_default();
+        if let Some(id) = self.id {
+            endpoint.id = id;
+        }
+        if let Some(address) = self.address {
+            endpoint.address = address;
+        }
+        if let Some(zone) = self.zone {
+            endpoint.zone = Some(zone);
+        }
+
+        Node {
+            endpoint,
+            weight: AtomicU32::new(self.weight),
+            fractional_weight: self.fractional_weight,
+            in_flight: AtomicUsize::new(0),
+            queue_depth: AtomicU32::new(0),
+            success: AtomicU64::new(0),
+            fail: AtomicU64::new(0),
+            last_rtt_ns: AtomicU64::new(self.initial_rtt_ns),
+            last_rtt_updated_at: AtomicU64::new(0),
+            max_in_flight: self.max_in_flight,
+            max_concurrency: self.max_concurrency,
+            priority: self.priority,
+            tags: self.tags,
+            rate_limit: self.rate_limit,
+            rate_bucket: AtomicU64::new(0),
+            enabled: AtomicBool::new(true),
+            error_window: self.error_window,
+            #[cfg(feature = "rtt-histogram")]
+            rtt_histogram: RttHistogram::default(),
+        }
+    }
+}
+
+/// Free-list of recycled [`Node`] allocations for churn-heavy node updates
+///
+/// Released nodes stay in the pool until every other reference (e.g. an old picker) is
+/// dropped; only uniquely owned nodes are handed out again, so a recycled node can never
+/// be observed by a live picker. At most `capacity` nodes are retained, extra ones are freed.
+#[derive(Clone, Debug)]
+pub struct NodePool {
+    capacity: usize,
+    free: Arc<parking_lot::Mutex<Vec<Arc<Node>>>>,
+}
+
+impl NodePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            free: Arc::new(parking_lot::Mutex::new(Vec::with_capacity(capacity))),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of retained nodes, including ones still referenced elsewhere.
+    pub fn len(&self) -> usize {
+        self.free.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.lock().is_empty()
+    }
+
+    /// Take a reset node from the pool, allocating a new one if none is reusable.
+    pub fn acquire(&self) -> PooledNode {
+        let node = self.lock().acquire();
+        PooledNode {
+            node: Some(node),
+            pool: self.clone(),
+        }
+    }
+
+    /// Hand a node back; it is reused once no other reference to it remains.
+    pub fn release(&self, node: Arc<Node>) {
+        self.lock().release(node);
+    }
+
+    // Hold the free-list lock across a batch of acquire/release calls
+    pub(crate) fn lock(&self) -> NodePoolGuard<'_> {
+        NodePoolGuard {
+            capacity: self.capacity,
+            free: self.free.lock(),
+        }
+    }
+}
+
+pub(crate) struct NodePoolGuard<'a> {
+    capacity: usize,
+    free: parking_lot::MutexGuard<'a, Vec<Arc<Node>>>,
+}
+
+impl NodePoolGuard<'_> {
+    pub(crate) fn acquire(&mut self) -> Arc<Node> {
+        let reusable = self
+            .free
+            .iter()
+            .rposition(|n| Arc::strong_count(n) == 1 && Arc::weak_count(n) == 0);
+        match reusable.map(|i| self.free.swap_remove(i)) {
+            Some(mut node) => {
+                if let Some(n) = Arc::get_mut(&mut node) {
+                    n.reset();
+                }
+                node
+            }
+            None => Arc::new(NodeBuilder::default().build()),
+        }
+    }
+
+    pub(crate) fn release(&mut self, node: Arc<Node>) {
+        if self.free.len() < self.capacity {
+            self.free.push(node);
+        }
+    }
+}
+
+/// A uniquely owned [`Node`] taken from a [`NodePool`]
+///
+/// Returned to the pool on drop unless detached with [`PooledNode::into_arc`].
+#[derive(Debug)]
+pub struct PooledNode {
+    node: Option<Arc<Node>>,
+    pool: NodePool,
+}
+
+impl PooledNode {
+    /// Detach the node from the pool, e.g. to hand it to a balancer.
+    pub fn into_arc(mut self) -> Arc<Node> {
+        self.node.take().expect("pooled node already taken")
+    }
+
+    /// Return the node to the pool explicitly, same as dropping it.
+    pub fn release(self) {}
+}
+
+impl std::ops::Deref for PooledNode {
+    type Target = Node;
+
+    fn deref(&self) -> &Node {
+        self.node.as_ref().expect("pooled node already taken")
+    }
+}
+
+impl std::ops::DerefMut for PooledNode {
+    fn deref_mut(&mut self) -> &mut Node {
+        // Acquired nodes are uniquely owned until `into_arc`
+        Arc::get_mut(self.node.as_mut().expect("pooled node already taken"))
+            .expect("pooled node is uniquely owned")
+    }
+}
+
+impl Drop for PooledNode {
+    fn drop(&mut self) {
+        if let Some(node) = self.node.take() {
+            self.pool.release(node);
+        }
+    }
 }
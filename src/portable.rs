@@ -0,0 +1,127 @@
+//! `no_std` + `alloc` strategies
+//!
+//! Always available, also without the `std` feature. Nodes are identified by their `u64`
+//! id only and pickers return the picked id, so callers map it back to their own address
+//! type. None of the strategies needs a random number generator or a lock.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::LoadBalanceError;
+
+/// Picker over a fixed list of node ids
+pub trait PortablePicker: Send + Sync {
+    /// Pick a node id; `hash_key` is required by the hashing strategies.
+    fn pick(&self, hash_key: Option<u64>) -> Result<u64, LoadBalanceError>;
+}
+
+/// Round Robin Load Balancing Strategy over node ids
+///
+/// Features:
+/// - Strict rotation driven by a single atomic cursor, lock-free
+/// - Ignores the hash key
+pub struct RoundRobin {
+    ids: Vec<u64>,
+    cursor: AtomicUsize,
+}
+
+impl RoundRobin {
+    pub fn new(ids: Vec<u64>) -> Self {
+        Self {
+            ids,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl PortablePicker for RoundRobin {
+    fn pick(&self, _hash_key: Option<u64>) -> Result<u64, LoadBalanceError> {
+        let len = self.ids.len();
+        if len == 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // The cursor wraps at `usize::MAX`, which only skews a single rotation
+        let i = self.cursor.fetch_add(1, Ordering::Relaxed) % len;
+        Ok(self.ids[i])
+    }
+}
+
+/// Consistent Hash Load Balancing Strategy over node ids
+///
+/// Features:
+/// - Hash ring with `virtual_factor` virtual nodes per id (at least 1)
+/// - Ring positions only depend on the ids, so every platform builds the same ring
+/// - Requires a hash key
+pub struct ConsistentHash {
+    // (ring position, node id), sorted by position
+    ring: Vec<(u64, u64)>,
+}
+
+impl ConsistentHash {
+    pub fn new(ids: &[u64], virtual_factor: usize) -> Self {
+        let vf = virtual_factor.max(1);
+        let mut ring = Vec::with_capacity(ids.len() * vf);
+        for &id in ids {
+            for j in 0..vf as u64 {
+                ring.push((mix(id ^ mix(j)), id));
+            }
+        }
+        ring.sort_unstable();
+        Self { ring }
+    }
+}
+
+impl PortablePicker for ConsistentHash {
+    fn pick(&self, hash_key: Option<u64>) -> Result<u64, LoadBalanceError> {
+        if self.ring.is_empty() {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+        let key = hash_key.ok_or(LoadBalanceError::MissingHashKey)?;
+
+        // First virtual node clockwise from the key, wrapping around the ring
+        let pos = mix(key);
+        let idx = self.ring.partition_point(|&(p, _)| p < pos);
+        Ok(self.ring[idx % self.ring.len()].1)
+    }
+}
+
+/// Rendezvous (highest random weight) Load Balancing Strategy over node ids
+///
+/// Features:
+/// - Picks the id with the highest `hash(key, id)` score, O(n) per pick
+/// - Removing a node only remaps the keys that were on it, without virtual nodes
+/// - Requires a hash key
+pub struct Rendezvous {
+    ids: Vec<u64>,
+}
+
+impl Rendezvous {
+    pub fn new(ids: Vec<u64>) -> Self {
+        Self { ids }
+    }
+}
+
+impl PortablePicker for Rendezvous {
+    fn pick(&self, hash_key: Option<u64>) -> Result<u64, LoadBalanceError> {
+        if self.ids.is_empty() {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+        let key = hash_key.ok_or(LoadBalanceError::MissingHashKey)?;
+
+        let key = mix(key);
+        self.ids
+            .iter()
+            .copied()
+            .max_by_key(|&id| (mix(key ^ mix(id)), id))
+            .ok_or(LoadBalanceError::NoAvailableNodes)
+    }
+}
+
+// splitmix64 finalizer: cheap, well-distributed and identical on every platform
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
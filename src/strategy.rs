@@ -1,485 +1,5940 @@
-use std::hash::{Hash, Hasher};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use ahash::AHasher;
+use ahash::RandomState;
+use faststr::FastStr;
 use parking_lot::RwLock;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use smallvec::SmallVec;
+use tokio::sync::broadcast;
 
+use crate::config::BalanceConfig;
 use crate::error::LoadBalanceError;
-use crate::node::Node;
+use crate::node::{unix_now_ns, Endpoint, InFlightGuard, Node, NodePool, NodeStatsSnapshot};
+
+pub mod sim;
 
 #[derive(Clone, Debug, Default)]
 pub struct RequestMetadata {
     pub hash_key: Option<u64>,
+    // `endpoint.id`s every picker must skip, e.g. the node that just failed a retry
+    pub exclude_ids: SmallVec<[u64; 4]>,
+    // Nodes already returned by `Picker::pick_retry`, skipped like `exclude_ids`
+    pub tried_ids: SmallVec<[u64; 4]>,
+    // What `Picker::pick_retry` does once every node has been tried
+    pub retry_exhaustion: RetryExhaustion,
+    // When the caller gives up on the request, used by `DeadlineAware`
+    pub deadline: Option<Instant>,
+    // Service the request is for, so one balancer shared by several services can route
+    // them differently; filled by the volo adapter
+    pub service_name: Option<FastStr>,
+    // Free-form request attributes for strategies, e.g. `LATENCY_SENSITIVE_TAG`
+    pub tags: HashMap<String, String>,
+}
+
+/// Behavior of `Picker::pick_retry` when the attempts exceed the available nodes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RetryExhaustion {
+    /// Forget the tried nodes and start another round over all of them
+    #[default]
+    Wrap,
+    /// Fail with `NoAvailableNodes`
+    Error,
+}
+
+impl RequestMetadata {
+    /// Tag marking a request as latency sensitive, whatever its value; read by
+    /// `ResponseTimeWeighted`.
+    pub const LATENCY_SENSITIVE_TAG: &'static str = "latency_sensitive";
+
+    pub fn with_service_name(mut self, service_name: impl Into<FastStr>) -> Self {
+        self.service_name = Some(service_name.into());
+        self
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn is_latency_sensitive(&self) -> bool {
+        self.tags.contains_key(Self::LATENCY_SENSITIVE_TAG)
+    }
+
+    /// Exclude a node from this request's picks.
+    pub fn exclude(mut self, node_id: u64) -> Self {
+        if !self.exclude_ids.contains(&node_id) {
+            self.exclude_ids.push(node_id);
+        }
+        self
+    }
+
+    /// Whether `node` is excluded or already tried for this request, or disabled.
+    pub fn is_excluded(&self, node: &Node) -> bool {
+        let id = node.endpoint.id;
+        !node.is_enabled() || self.exclude_ids.contains(&id) || self.tried_ids.contains(&id)
+    }
+
+    fn has_exclusions(&self) -> bool {
+        !self.exclude_ids.is_empty() || !self.tried_ids.is_empty()
+    }
 }
 
 pub trait Picker: Send + Sync {
     fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError>;
+
+    /// Position of the picked node in `nodes`, for callers keeping per-node state in a
+    /// parallel array.
+    ///
+    /// The default calls `pick` and looks the node up by pointer; it fails with a `Custom`
+    /// error if the picker does not expose its nodes. Index-based strategies override it.
+    fn pick_index(&self, req: &RequestMetadata) -> Result<usize, LoadBalanceError> {
+        let node = self.pick(req)?;
+        self.nodes()
+            .iter()
+            .position(|n| Arc::ptr_eq(n, &node))
+            .ok_or_else(|| LoadBalanceError::custom("picked node is not in the picker's node list"))
+    }
+
+    /// Nodes the picker was built from, in their original order.
+    ///
+    /// Empty by default; every picker of this crate returns its list.
+    fn nodes(&self) -> &[Arc<Node>] {
+        &[]
+    }
+
+    /// Pick up to `n` distinct nodes (by `endpoint.id`) for fan-out / hedged requests.
+    ///
+    /// If fewer than `n` distinct nodes are available, returns what could be found.
+    /// The default implementation calls `pick` repeatedly with a bounded number of attempts.
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let mut picked: Vec<Arc<Node>> = Vec::with_capacity(n);
+        if n == 0 {
+            return Ok(picked);
+        }
+
+        // Bound attempts so deterministic strategies (e.g. consistent hash) terminate
+        let max_attempts = n.saturating_mul(4);
+        for attempt in 0..max_attempts {
+            let node = match self.pick(req) {
+                Ok(node) => node,
+                Err(e) if attempt == 0 => return Err(e),
+                Err(_) => break,
+            };
+            if !picked.iter().any(|p| p.endpoint.id == node.endpoint.id) {
+                picked.push(node);
+                if picked.len() == n {
+                    break;
+                }
+            }
+        }
+
+        Ok(picked)
+    }
+
+    /// Pick exactly `n` distinct nodes (by `endpoint.id`), e.g. the replicas of a write.
+    ///
+    /// Unlike `pick_n` this never returns fewer nodes: if fewer than `n` nodes are left
+    /// after `req`'s exclusions it fails with `InsufficientNodes`. The default
+    /// implementation picks repeatedly, excluding every node already picked; repeats
+    /// from a `pick` that ignores exclusions are dropped within a bounded number of
+    /// attempts.
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let mut req = req.clone();
+        let mut picked: Vec<Arc<Node>> = Vec::with_capacity(n);
+        // Same bound as `pick_n`
+        let max_attempts = n.saturating_mul(4);
+        for _ in 0..max_attempts {
+            if picked.len() == n {
+                break;
+            }
+            match self.pick(&req) {
+                Ok(node) if picked.iter().any(|p| p.endpoint.id == node.endpoint.id) => {}
+                Ok(node) => {
+                    req.exclude_ids.push(node.endpoint.id);
+                    picked.push(node);
+                }
+                Err(LoadBalanceError::NoAvailableNodes) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        check_batch(picked, n)
+    }
+
+    /// Pick a node for retry `attempt` of a request, avoiding the nodes earlier attempts got.
+    ///
+    /// Attempt 0 starts a new request and clears `req.tried_ids`; every returned node is
+    /// recorded there and skipped, together with `req.exclude_ids`, by later attempts. So
+    /// the first K attempts hit K distinct nodes as long as K nodes are available. Once
+    /// every node has been tried, `req.retry_exhaustion` either wraps around to a new
+    /// round (`Wrap`) or fails with `NoAvailableNodes` (`Error`).
+    fn pick_retry(
+        &self,
+        req: &mut RequestMetadata,
+        attempt: usize,
+    ) -> Result<Arc<Node>, LoadBalanceError> {
+        if attempt == 0 {
+            req.tried_ids.clear();
+        }
+
+        let node = match self.pick(req) {
+            Err(LoadBalanceError::NoAvailableNodes)
+                if !req.tried_ids.is_empty() && req.retry_exhaustion == RetryExhaustion::Wrap =>
+            {
+                req.tried_ids.clear();
+                self.pick(req)?
+            }
+            res => res?,
+        };
+        req.tried_ids.push(node.endpoint.id);
+        Ok(node)
+    }
+
+    /// Pick a node together with its counters as read right after selection, e.g. for
+    /// logging the load a request was routed on.
+    ///
+    /// The default reads the counters once `pick` returns; strategies that load them
+    /// while selecting may return those values instead.
+    fn pick_with_stats(
+        &self,
+        req: &RequestMetadata,
+    ) -> Result<(Arc<Node>, NodeStatsSnapshot), LoadBalanceError> {
+        let node = self.pick(req)?;
+        let stats = node.stats_snapshot();
+        Ok((node, stats))
+    }
+
+    /// Pick a node, failing with `Timeout` if the pick completes after `deadline`.
+    ///
+    /// Strategies that consult external state override this to give up early. The
+    /// default picks synchronously and discards a result that arrives too late.
+    fn pick_with_deadline(
+        &self,
+        req: &RequestMetadata,
+        deadline: Instant,
+    ) -> Result<Arc<Node>, LoadBalanceError> {
+        let node = self.pick(req)?;
+        if Instant::now() > deadline {
+            return Err(LoadBalanceError::Timeout);
+        }
+        Ok(node)
+    }
+
+    /// Report the round-trip time of a request sent to `node`.
+    ///
+    /// The default implementation stores it with `Node::record_rtt` and passes it on to
+    /// `update_estimates`. Wrappers over several inner pickers call this on at most one
+    /// of them, so each response is recorded on the node once.
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        node.record_rtt(rtt.as_nanos().min(u64::MAX as u128) as u64);
+        self.update_estimates(node, rtt);
+    }
+
+    /// Update the picker's own per-node estimates from a response, leaving `node` alone.
+    ///
+    /// No-op by default; adaptive strategies (e.g. `PeakEwma`) override it and wrappers
+    /// forward it.
+    fn update_estimates(&self, _node: &Node, _rtt: Duration) {}
 }
 
 pub trait BalanceStrategy: Send + Sync {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker>;
+    /// Short name for logs and metric labels, e.g. `"round_robin"`; wrappers include the
+    /// wrapped strategy, e.g. `"circuit_breaker(round_robin)"`.
+    ///
+    /// Defaults to the type name.
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(std::any::type_name::<Self>())
+    }
+
+    /// Hash of the configuration that shapes the pickers, e.g. to keep cached pickers of
+    /// differently configured strategies apart.
+    ///
+    /// Defaults to a hash of `name`; strategies with parameters mix them in, wrappers
+    /// also mix in the `config_hash` of the strategies they wrap.
+    fn config_hash(&self) -> u64 {
+        config_hasher(&self.name()).finish()
+    }
+
+    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>)
+        -> Result<Arc<dyn Picker>, LoadBalanceError>;
+
+    /// Forget per-node state kept across picker builds for nodes not in `live_ids`
+    /// (sorted ascending).
+    ///
+    /// Pickers may be built over a subset of the nodes, e.g. one zone of `ZoneAware`, so
+    /// stateful strategies never drop state in `build_picker`; `BaseBalancer` calls this
+    /// after every node list change instead. Wrappers forward it to their inner
+    /// strategies; the default does nothing.
+    fn prune(&self, _live_ids: &[u64]) {}
+
+    /// Give the strategy per-node state of its own, empty, instead of the state it shares
+    /// with the strategy it was cloned from.
+    ///
+    /// Clones of stateful strategies (e.g. `PeakEwma`) share their estimates, which lets
+    /// callers keep a handle to inspect them; `MultiTenantBalancer` detaches the clone of
+    /// every tenant instead. Wrappers forward it to their inner strategies; the default
+    /// does nothing.
+    fn detach_state(&mut self) {}
+
+    /// Build a picker that uses `weights[i]` in place of `nodes[i].weight`.
+    ///
+    /// `weights` has exactly one entry per node. Weighted strategies override this and
+    /// implement `build_picker` on top of it; the default ignores the weights.
+    ///
+    /// Weights are read when the picker is built, so `Node::set_weight` takes effect with
+    /// the next build. Only `WeightedReservoir` and `LeastConnectionWeighted` read
+    /// `Node::weight` on every pick; `WeightedRandom` re-checks it only to notice that
+    /// every weight dropped to 0.
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        _weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        self.build_picker(nodes)
+    }
+}
+
+/// Weight override Strategy Wrapper
+///
+/// Features:
+/// - Builds the inner strategy with a fixed list of weights instead of `Node::weight`,
+///   e.g. weights computed from CPU or load feedback, without touching the nodes
+/// - `weights[i]` applies to the i-th node of the list the picker is built from; a
+///   length mismatch fails with `WeightCountMismatch`
+/// - Created with `with_weights` on `WeightedRandom`, `WeightedRoundRobin` and `ConsistentHash`
+#[derive(Clone)]
+pub struct StaticWeightSnapshot<S: BalanceStrategy> {
+    inner: S,
+    weights: Arc<[u32]>,
+}
+
+impl<S: BalanceStrategy> StaticWeightSnapshot<S> {
+    pub fn new(inner: S, weights: Vec<u32>) -> Self {
+        Self {
+            inner,
+            weights: weights.into(),
+        }
+    }
+
+    pub fn weights(&self) -> &[u32] {
+        &self.weights
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for StaticWeightSnapshot<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("static_weights({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.weights.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        if self.weights.len() != nodes.len() {
+            return Err(LoadBalanceError::WeightCountMismatch {
+                nodes: nodes.len(),
+                weights: self.weights.len(),
+            });
+        }
+        self.inner.build_picker_with_weights(nodes, &self.weights)
+    }
+}
+
+/// Weight provider of `DynamicWeightStrategy`
+pub type WeightProvider = Arc<dyn Fn(&Node) -> f64 + Send + Sync>;
+
+/// Externally computed weights Strategy Wrapper
+///
+/// Features:
+/// - Calls `weight_provider` for every node on each `build_picker`, e.g. to turn CPU
+///   usage or sidecar queue depth into weights
+/// - Weights are snapshot at build time, picks never call the provider
+/// - Provided weights are rounded and clamped to `0..=u32::MAX`, NaN counts as 0
+/// - The picker still hands out the original nodes, so their counters stay shared
+/// - Only strategies that read weights at build time (`WeightedRandom`,
+///   `WeightedRoundRobin`, `ConsistentHash`) are affected
+#[derive(Clone)]
+pub struct DynamicWeightStrategy<S: BalanceStrategy> {
+    inner: S,
+    weight_provider: WeightProvider,
+}
+
+impl<S: BalanceStrategy> DynamicWeightStrategy<S> {
+    pub fn new<F>(inner: S, weight_provider: F) -> Self
+    where
+        F: Fn(&Node) -> f64 + Send + Sync + 'static,
+    {
+        Self {
+            inner,
+            weight_provider: Arc::new(weight_provider),
+        }
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for DynamicWeightStrategy<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("dynamic_weights({})", self.inner.name()).into()
+    }
+
+    /// Covers the inner strategy only: the weight provider cannot be compared.
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights: Vec<u32> = nodes
+            .iter()
+            .map(|n| {
+                // `as` saturates and maps NaN to 0
+                (self.weight_provider)(n).round() as u32
+            })
+            .collect();
+        self.inner.build_picker_with_weights(nodes, &weights)
+    }
+}
+
+/// Stale RTT weight decay Strategy Wrapper
+///
+/// Features:
+/// - A node is stale once its last `Node::record_rtt` is more than `stale_threshold` ago;
+///   nodes that never reported an RTT are not stale
+/// - Stale nodes' weights are multiplied by `decay_per_second` for every second past
+///   `stale_threshold`, see `decayed_weight`
+/// - Decayed weights are snapshot at build time and passed to `build_picker_with_weights`,
+///   so only weighted inner strategies see the reduced weights
+/// - Nodes stale for `max_staleness` or longer count as weight 0 and are skipped by every
+///   inner strategy, unless every allowed node is that stale
+pub struct WeightDecay<S: BalanceStrategy> {
+    inner: S,
+    stale_threshold: Duration,
+    decay_per_second: f64,
+    max_staleness: Duration,
+}
+
+impl<S: BalanceStrategy> WeightDecay<S> {
+    pub const DEFAULT_DECAY_PER_SECOND: f64 = 0.9;
+
+    pub fn new(inner: S, stale_threshold: Duration, max_staleness: Duration) -> Self {
+        Self {
+            inner,
+            stale_threshold,
+            decay_per_second: Self::DEFAULT_DECAY_PER_SECOND,
+            max_staleness,
+        }
+    }
+
+    /// Weight multiplier per stale second, clamped to `0.0..=1.0`.
+    pub fn with_decay_per_second(mut self, decay_per_second: f64) -> Self {
+        self.decay_per_second = if decay_per_second.is_nan() {
+            0.0
+        } else {
+            decay_per_second.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// `weight` after `staleness` without an RTT report.
+    ///
+    /// Unchanged up to `stale_threshold`, 0 from `max_staleness` on, and
+    /// `weight * decay_per_second ^ seconds_past_threshold` (rounded) in between.
+    pub fn decayed_weight(&self, weight: u32, staleness: Duration) -> u32 {
+        if staleness >= self.max_staleness {
+            return 0;
+        }
+        if staleness <= self.stale_threshold {
+            return weight;
+        }
+        let stale_secs = (staleness - self.stale_threshold).as_secs_f64();
+        (weight as f64 * self.decay_per_second.powf(stale_secs)).round() as u32
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for WeightDecay<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("weight_decay({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.stale_threshold.hash(&mut h);
+        self.decay_per_second.to_bits().hash(&mut h);
+        self.max_staleness.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights = node_weights(&nodes);
+        self.build_picker_with_weights(nodes, &weights)
+    }
+
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let now = unix_now_ns();
+        let weights: Vec<u32> = nodes
+            .iter()
+            .zip(weights)
+            .map(|(n, &w)| match n.rtt_staleness(now) {
+                Some(staleness) => self.decayed_weight(w, staleness),
+                None => w,
+            })
+            .collect();
+        Ok(Arc::new(WeightDecayPicker {
+            inner: self
+                .inner
+                .build_picker_with_weights(nodes.clone(), &weights)?,
+            nodes,
+            max_staleness: self.max_staleness,
+        }))
+    }
+}
+
+struct WeightDecayPicker {
+    inner: Arc<dyn Picker>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    max_staleness: Duration,
+}
+
+impl Picker for WeightDecayPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        // Staleness keeps growing after the build, so expired nodes are checked per pick
+        let now = unix_now_ns();
+        let mut filtered: Option<RequestMetadata> = None;
+        let mut fresh = false;
+        for node in self.nodes.iter().filter(|n| !req.is_excluded(n)) {
+            match node.rtt_staleness(now) {
+                Some(staleness) if staleness >= self.max_staleness => {
+                    filtered
+                        .get_or_insert_with(|| req.clone())
+                        .exclude_ids
+                        .push(node.endpoint.id);
+                }
+                _ => fresh = true,
+            }
+        }
+        match filtered {
+            Some(filtered) if fresh => self.inner.pick(&filtered),
+            _ => self.inner.pick(req),
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+// Whether load can be normalized by capacity: every node sets `max_in_flight`
+fn all_capped(nodes: &[Arc<Node>]) -> bool {
+    !nodes.is_empty() && nodes.iter().all(|n| n.max_in_flight > 0)
+}
+
+// Weights as stored on the nodes, for strategies built on `build_picker_with_weights`
+fn node_weights(nodes: &[Arc<Node>]) -> Vec<u32> {
+    nodes.iter().map(|n| n.weight()).collect()
 }
 
 #[derive(Clone)]
 pub struct BaseBalancer<S: BalanceStrategy> {
     strategy: S,
     nodes: Arc<RwLock<Vec<Arc<Node>>>>,
+    config: Option<BalanceConfig>,
+    // Bumped on every node or weight change, shared by clones
+    generation: Arc<AtomicU64>,
+    // Node list changes for `subscribe_changes`, shared by clones
+    changes: broadcast::Sender<NodeChange>,
+    // Picker published by `prewarm` with the generation it was built for, shared by clones
+    published: Arc<RwLock<Option<PublishedPicker>>>,
 }
 
+// Picker and the generation of the node list it was built from
+type PublishedPicker = (u64, Arc<dyn Picker>);
+
 impl<S: BalanceStrategy> BaseBalancer<S> {
+    /// Events buffered per subscriber before the slowest one lags.
+    pub const DEFAULT_CHANGE_BUFFER: usize = 64;
+
     pub fn new(strategy: S) -> Self {
         Self {
             strategy,
             nodes: Arc::new(RwLock::new(Vec::new())),
+            config: None,
+            generation: Arc::new(AtomicU64::new(0)),
+            changes: broadcast::channel(Self::DEFAULT_CHANGE_BUFFER).0,
+            published: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Create a balancer that applies `config` to incoming nodes.
+    ///
+    /// Nodes with `weight == 0` get `config.default_weight` set in place, so strategies
+    /// never see zero weights.
+    pub fn with_config(strategy: S, config: BalanceConfig) -> Self {
+        Self {
+            strategy,
+            nodes: Arc::new(RwLock::new(Vec::new())),
+            config: Some(config),
+            generation: Arc::new(AtomicU64::new(0)),
+            changes: broadcast::channel(Self::DEFAULT_CHANGE_BUFFER).0,
+            published: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// Buffer `capacity` (at least 1) change events per subscriber. Call before
+    /// `subscribe_changes`: receivers of the previous channel stop getting events.
+    pub fn with_change_buffer(mut self, capacity: usize) -> Self {
+        self.changes = broadcast::channel(capacity.max(1)).0;
+        self
+    }
+
+    /// Receive `NodeChange`s from every `update_nodes*` call that adds or removes nodes.
+    ///
+    /// Writers never wait for receivers: one that falls more than the change buffer
+    /// behind gets `RecvError::Lagged` and then continues with the oldest event kept.
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<NodeChange> {
+        self.changes.subscribe()
+    }
+
+    // Weight of new nodes without one: the configured `default_weight`, else the default
+    fn default_weight(&self) -> u32 {
+        self.config
+            .as_ref()
+            .map_or(BalanceConfig::default().default_weight, |c| {
+                c.default_weight
+            })
+    }
+
+    // Let the strategy forget state of nodes that are no longer in `nodes`
+    fn prune_strategy(&self, nodes: &[Arc<Node>]) {
+        let mut live: Vec<u64> = nodes.iter().map(|n| n.endpoint.id).collect();
+        live.sort_unstable();
+        self.strategy.prune(&live);
+    }
+
+    // Send the id difference between `old` and `new`, skipping empty events. Called with
+    // the node list locked, so events arrive in update order.
+    fn notify_changes(&self, old: &[Arc<Node>], new: &[Arc<Node>]) {
+        if self.changes.receiver_count() == 0 {
+            return;
+        }
+        let old_ids: ahash::AHashSet<u64> = old.iter().map(|n| n.endpoint.id).collect();
+        let new_ids: ahash::AHashSet<u64> = new.iter().map(|n| n.endpoint.id).collect();
+        let changed = |nodes: &[Arc<Node>], other: &ahash::AHashSet<u64>| -> Vec<Arc<Node>> {
+            nodes
+                .iter()
+                .filter(|n| !other.contains(&n.endpoint.id))
+                .cloned()
+                .collect()
+        };
+
+        // A send only fails without receivers
+        let added = changed(new, &old_ids);
+        if !added.is_empty() {
+            let _ = self.changes.send(NodeChange::Added(added));
+        }
+        let removed = changed(old, &new_ids);
+        if !removed.is_empty() {
+            let _ = self.changes.send(NodeChange::Removed(removed));
+        }
+    }
+
+    // Set `config.default_weight` on `node` if it has weight 0
+    fn apply_default_weight(&self, node: &Node) {
+        if let Some(config) = &self.config {
+            if node.weight() == 0 {
+                node.set_weight(config.default_weight);
+            }
         }
     }
+
     pub fn update_nodes(&self, nodes: Vec<Arc<Node>>) {
-        *self.nodes.write() = nodes;
+        nodes.iter().for_each(|n| self.apply_default_weight(n));
+        let mut guard = self.nodes.write();
+        let old = std::mem::replace(&mut *guard, nodes);
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.notify_changes(&old, &guard);
+        self.prune_strategy(&guard);
+    }
+
+    /// Replace the node list with the nodes of an iterator, see `update_nodes`.
+    pub fn update_nodes_iter(&self, nodes: impl IntoIterator<Item = Arc<Node>>) {
+        self.update_nodes(nodes.into_iter().collect());
     }
-    pub fn picker(&self) -> Arc<dyn Picker> {
+
+    /// Replace the node list with one node per Volo discovery instance.
+    ///
+    /// Node ids and endpoints are derived as in `VoloLoadBalancer`. A current node with the
+    /// same id is kept (stats included), or rebuilt with its stats carried over if the
+    /// weight, address or zone changed. Instances with weight 0 get the configured
+    /// `default_weight`, see `with_config`.
+    #[cfg(feature = "volo-adapter")]
+    pub fn update_nodes_from_instances(&self, instances: &[Arc<volo::discovery::Instance>]) {
+        let default_weight = self.default_weight();
+        let mut guard = self.nodes.write();
+        let mut current: HashMap<u64, Arc<Node>> =
+            guard.iter().map(|n| (n.endpoint.id, n.clone())).collect();
+        let nodes = crate::adapter::sync_node_map(&mut current, instances, default_weight);
+        let old = std::mem::replace(&mut *guard, nodes);
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.notify_changes(&old, &guard);
+        self.prune_strategy(&guard);
+    }
+
+    /// Swap the node with id `old_id` for `new_node` in place, e.g. after an address change.
+    ///
+    /// The rest of the list is untouched and the swap happens under the write lock, so
+    /// pickers never see the list without either node. `new_node` itself is stored, with
+    /// the default weight set if it has weight 0 (see `with_config`): build it with
+    /// `Node::clone_with_metadata` from the old node to keep its stats.
+    /// Fails with `NodeNotFound` if no node has `old_id`, and with `DuplicateNode` if
+    /// another node already has the id of `new_node`.
+    pub fn replace_node(&self, old_id: u64, new_node: Arc<Node>) -> Result<(), LoadBalanceError> {
+        let mut guard = self.nodes.write();
+        let index = guard
+            .iter()
+            .position(|n| n.endpoint.id == old_id)
+            .ok_or(LoadBalanceError::NodeNotFound(old_id))?;
+        let new_id = new_node.endpoint.id;
+        if new_id != old_id && guard.iter().any(|n| n.endpoint.id == new_id) {
+            return Err(LoadBalanceError::DuplicateNode(new_id));
+        }
+        self.apply_default_weight(&new_node);
+        let old = std::mem::replace(&mut guard[index], new_node.clone());
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.notify_changes(&[old], &[new_node]);
+        self.prune_strategy(&guard);
+        Ok(())
+    }
+
+    /// Update node weights in place by `endpoint.id`, keeping nodes and their runtime stats.
+    ///
+    /// Ids not in the balancer are ignored, and weight 0 gets the configured
+    /// `default_weight` as in `update_nodes`. Existing pickers keep the weights they were
+    /// built with (see `BalanceStrategy::build_picker_with_weights`); the next `picker()`
+    /// uses the new ones.
+    pub fn update_weights(&self, weights: HashMap<u64, u32>) {
+        // Hold the write lock so a concurrent `update_nodes` cannot interleave
+        let nodes = self.nodes.write();
+        for node in nodes.iter() {
+            if let Some(&weight) = weights.get(&node.endpoint.id) {
+                let weight = match &self.config {
+                    Some(config) if weight == 0 => config.default_weight,
+                    _ => weight,
+                };
+                node.set_weight(weight);
+            }
+        }
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Counter bumped by every `update_nodes*`, `replace_node` and `update_weights` call, e.g. to tell
+    /// whether a cached picker is stale.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Replace the node list with one node per endpoint, recycling allocations from `pool`.
+    ///
+    /// Nodes whose id and address are unchanged are kept as is (stats included), removed
+    /// nodes are released to `pool`. New nodes get the configured `default_weight`.
+    pub fn update_nodes_pooled(&self, pool: &NodePool, endpoints: Vec<Endpoint>) {
+        let weight = self.default_weight();
+
+        let mut guard = self.nodes.write();
+        // Only kept for subscribers, extra references keep removed nodes from being reused
+        let subscribed = self.changes.receiver_count() > 0;
+        let previous = if subscribed {
+            guard.clone()
+        } else {
+            Vec::new()
+        };
+        let mut old: ahash::AHashMap<u64, Arc<Node>> =
+            guard.drain(..).map(|n| (n.endpoint.id, n)).collect();
+
+        let mut free = pool.lock();
+        let nodes = endpoints
+            .into_iter()
+            .map(|endpoint| match old.remove(&endpoint.id) {
+                Some(n) if n.endpoint.address == endpoint.address => n,
+                stale => {
+                    if let Some(n) = stale {
+                        free.release(n);
+                    }
+                    let mut node = free.acquire();
+                    let n = Arc::get_mut(&mut node).expect("acquired node is uniquely owned");
+                    n.endpoint = endpoint;
+                    *n.weight.get_mut() = weight;
+                    node
+                }
+            })
+            .collect();
+        *guard = nodes;
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        if subscribed {
+            self.notify_changes(&previous, &guard);
+        }
+        self.prune_strategy(&guard);
+        drop(guard);
+
+        for (_, n) in old {
+            free.release(n);
+        }
+    }
+    pub fn picker(&self) -> Result<Arc<dyn Picker>, LoadBalanceError> {
         // Use cloning to get the node list, avoiding holding the read lock for a long time
         let nodes = Arc::new(self.nodes.read().clone());
         self.strategy.build_picker(nodes)
     }
-}
 
-// Round Robin
-pub struct RoundRobin;
+    /// Pick from the picker of the current nodes, or `None` without building one if there
+    /// are no nodes.
+    ///
+    /// Lets hot paths tell an empty pool apart from a pick error such as `MissingHashKey`
+    /// or every node being excluded. The picker is built once per `generation` and
+    /// published for `published_picker`, so picker state such as rotation cursors
+    /// carries over between calls.
+    pub fn try_pick(&self, req: &RequestMetadata) -> Option<Result<Arc<Node>, LoadBalanceError>> {
+        if self.nodes.read().is_empty() {
+            return None;
+        }
+        Some(
+            self.current_picker()
+                .and_then(|(_, picker)| picker.pick(req)),
+        )
+    }
 
-impl BalanceStrategy for RoundRobin {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        Arc::new(RoundRobinPicker {
-            nodes,
-            idx: parking_lot::Mutex::new(0usize),
-        })
+    /// Build a picker for the current nodes and publish it for `published_picker`.
+    ///
+    /// Call after `update_nodes*`, e.g. from a background task: until the new picker is
+    /// built, readers keep using the previous one. A build for an older generation never
+    /// replaces a newer published picker.
+    pub fn prewarm(&self) -> Result<(), LoadBalanceError> {
+        let current = self.build_current()?;
+        self.publish(&current);
+        Ok(())
     }
-}
 
-struct RoundRobinPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
-    idx: parking_lot::Mutex<usize>,
-}
+    // Store `picker` for `published_picker` unless a newer generation is published
+    fn publish(&self, (generation, picker): &PublishedPicker) {
+        let mut published = self.published.write();
+        if published.as_ref().is_none_or(|(g, _)| g <= generation) {
+            *published = Some((*generation, picker.clone()));
+        }
+    }
 
-impl Picker for RoundRobinPicker {
-    fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
+    // The published picker if it was built for the current generation, else a new one
+    // published in its place
+    fn current_picker(&self) -> Result<PublishedPicker, LoadBalanceError> {
+        let generation = self.generation();
+        if let Some((g, picker)) = self.published.read().as_ref() {
+            if *g == generation {
+                return Ok((generation, picker.clone()));
+            }
+        }
+        let current = self.build_current()?;
+        self.publish(&current);
+        Ok(current)
+    }
+
+    // Picker for the current nodes with the generation they belong to
+    fn build_current(&self) -> Result<PublishedPicker, LoadBalanceError> {
+        let (generation, nodes) = {
+            let nodes = self.nodes.read();
+            (self.generation(), Arc::new(nodes.clone()))
+        };
+        Ok((generation, self.strategy.build_picker(nodes)?))
+    }
+
+    /// The picker last published by `prewarm` or `try_pick`, without rebuilding it.
+    ///
+    /// It may predate the latest `update_nodes*` call until the next `prewarm` or
+    /// `try_pick`. Without a published picker, one is built and published first.
+    pub fn published_picker(&self) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        if let Some((_, picker)) = self.published.read().as_ref() {
+            return Ok(picker.clone());
         }
+        self.prewarm()?;
+        self.published_picker()
+    }
 
-        let mut g = self.idx.lock();
-        let i = *g % len;
+    /// Build a picker over a caller-filtered subset of nodes (e.g. by tag or zone).
+    ///
+    /// The stored node list is neither read nor modified.
+    pub fn picker_for_subset(
+        &self,
+        nodes: Vec<Arc<Node>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        self.strategy.build_picker(Arc::new(nodes))
+    }
 
-        // Handle possible overflow, reset to 0 when approaching usize::MAX
-        if *g == usize::MAX {
-            *g = 0;
-        } else {
-            *g += 1;
+    /// Compare the current nodes against `new_nodes` by `endpoint.id`, without changing
+    /// anything.
+    ///
+    /// `added` keeps the order of `new_nodes`; `removed` and `unchanged` hold the current
+    /// nodes (with their stats) in their current order.
+    pub fn diff_nodes(&self, new_nodes: &[Arc<Node>]) -> NodeDiff {
+        let current = self.nodes.read();
+        let new_ids: ahash::AHashSet<u64> = new_nodes.iter().map(|n| n.endpoint.id).collect();
+        let current_ids: ahash::AHashSet<u64> = current.iter().map(|n| n.endpoint.id).collect();
+
+        let (unchanged, removed) = current
+            .iter()
+            .cloned()
+            .partition(|n| new_ids.contains(&n.endpoint.id));
+        let added = new_nodes
+            .iter()
+            .filter(|n| !current_ids.contains(&n.endpoint.id))
+            .cloned()
+            .collect();
+        NodeDiff {
+            added,
+            removed,
+            unchanged,
+        }
+    }
+
+    /// Copy of the current node list.
+    pub fn nodes_snapshot(&self) -> Vec<Arc<Node>> {
+        self.nodes.read().clone()
+    }
+
+    /// Call `f` on every current node in order, without copying the list.
+    ///
+    /// The node list stays read-locked while `f` runs, so `f` must not update the
+    /// balancer's nodes; prefer `nodes_snapshot` for slow work.
+    pub fn for_each_node<F: FnMut(&Node)>(&self, mut f: F) {
+        for node in self.nodes.read().iter() {
+            f(node);
+        }
+    }
+
+    /// Zero the runtime counters of every current node, see `Node::reset_stats`.
+    pub fn reset_all_stats(&self) {
+        self.for_each_node(Node::reset_stats);
+    }
+}
+
+/// Node list change sent to `BaseBalancer::subscribe_changes` receivers
+///
+/// Nodes are matched by `endpoint.id`; an update that only replaces a node with the same
+/// id sends nothing.
+#[derive(Clone, Debug)]
+pub enum NodeChange {
+    Added(Vec<Arc<Node>>),
+    Removed(Vec<Arc<Node>>),
+}
+
+/// Result of `BaseBalancer::diff_nodes`
+#[derive(Clone, Debug, Default)]
+pub struct NodeDiff {
+    pub added: Vec<Arc<Node>>,
+    pub removed: Vec<Arc<Node>>,
+    pub unchanged: Vec<Arc<Node>>,
+}
+
+impl NodeDiff {
+    /// Whether the node sets are identical by id.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Balancer that reuses one picker until the node list changes
+///
+/// `BaseBalancer::picker` clones the node list and builds a new picker on every call.
+/// `picker()` here returns the cached picker as long as the balancer's `generation` is
+/// unchanged, which costs one atomic load and a read lock; the first call after an
+/// `update_nodes*` or `update_weights` rebuilds it. Picker state such as rotation
+/// cursors therefore carries over between calls.
+pub struct CachingBalancer<S: BalanceStrategy> {
+    balancer: BaseBalancer<S>,
+    cached: RwLock<Option<PublishedPicker>>,
+}
+
+impl<S: BalanceStrategy> CachingBalancer<S> {
+    pub fn new(strategy: S) -> Self {
+        BaseBalancer::new(strategy).into()
+    }
+
+    /// The wrapped balancer, for node and weight updates.
+    pub fn balancer(&self) -> &BaseBalancer<S> {
+        &self.balancer
+    }
+
+    /// Replace the node list, see `BaseBalancer::update_nodes`.
+    pub fn update_nodes(&self, nodes: Vec<Arc<Node>>) {
+        self.balancer.update_nodes(nodes);
+    }
+
+    pub fn picker(&self) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let generation = self.balancer.generation();
+        if let Some((g, picker)) = self.cached.read().as_ref() {
+            if *g == generation {
+                return Ok(picker.clone());
+            }
+        }
+
+        let (generation, picker) = self.balancer.build_current()?;
+        let mut cached = self.cached.write();
+        // A concurrent rebuild may have stored a picker for a newer generation meanwhile
+        if cached.as_ref().is_none_or(|(g, _)| *g <= generation) {
+            *cached = Some((generation, picker.clone()));
+        }
+        Ok(picker)
+    }
+}
+
+impl<S: BalanceStrategy> From<BaseBalancer<S>> for CachingBalancer<S> {
+    fn from(balancer: BaseBalancer<S>) -> Self {
+        Self {
+            balancer,
+            cached: RwLock::new(None),
+        }
+    }
+}
+
+/// How `RetryableBalancer::pick_with_retry` changes the request before another attempt
+pub enum RetryPolicy {
+    /// Drop `hash_key`; strategies that need one fall back to round-robin
+    ClearHashKey,
+    /// Add the node of the failed attempt to `exclude_ids`
+    ExcludePrevious,
+    /// Called with the request and the node of the failed attempt, `None` if the pick
+    /// itself failed
+    Custom(Box<RetryFn>),
+}
+
+/// Request rewrite of `RetryPolicy::Custom`
+pub type RetryFn = dyn Fn(&mut RequestMetadata, Option<&Arc<Node>>) + Send + Sync;
+
+impl RetryPolicy {
+    fn apply(&self, req: &mut RequestMetadata, failed: Option<&Arc<Node>>) {
+        match self {
+            RetryPolicy::ClearHashKey => req.hash_key = None,
+            RetryPolicy::ExcludePrevious => {
+                if let Some(node) = failed {
+                    if !req.exclude_ids.contains(&node.endpoint.id) {
+                        req.exclude_ids.push(node.endpoint.id);
+                    }
+                }
+            }
+            RetryPolicy::Custom(f) => f(req, failed),
+        }
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetryPolicy::ClearHashKey => f.write_str("ClearHashKey"),
+            RetryPolicy::ExcludePrevious => f.write_str("ExcludePrevious"),
+            RetryPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Balancer that retries failed picks with an adjusted request
+///
+/// An attempt fails if the pick returns an error or a node `health` classifies as
+/// unhealthy; the `policy` then rewrites a copy of the request for the next attempt. A
+/// pick failing with `MissingHashKey` is retried on a round-robin picker over the same
+/// nodes, so `ClearHashKey` also works for `ConsistentHash`. Both pickers are built once
+/// per `BaseBalancer::generation` and reused, so their state carries over between calls.
+pub struct RetryableBalancer<S: BalanceStrategy> {
+    balancer: BaseBalancer<S>,
+    policy: RetryPolicy,
+    health: HealthFilter,
+    // Round-robin picker for `MissingHashKey`, with the generation it was built for
+    fallback: RwLock<Option<PublishedPicker>>,
+}
+
+impl<S: BalanceStrategy> RetryableBalancer<S> {
+    pub fn new(strategy: S, policy: RetryPolicy) -> Self {
+        Self {
+            balancer: BaseBalancer::new(strategy),
+            policy,
+            health: HealthFilter::default(),
+            fallback: RwLock::new(None),
+        }
+    }
+
+    /// Classify picked nodes with `health` instead of `HealthFilter::default()`.
+    pub fn with_health(mut self, health: HealthFilter) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// The wrapped balancer, for node and weight updates.
+    pub fn balancer(&self) -> &BaseBalancer<S> {
+        &self.balancer
+    }
+
+    /// Replace the node list, see `BaseBalancer::update_nodes`.
+    pub fn update_nodes(&self, nodes: Vec<Arc<Node>>) {
+        self.balancer.update_nodes(nodes);
+    }
+
+    // Round-robin picker over the nodes of `picker`, built for `generation`
+    fn fallback(
+        &self,
+        generation: u64,
+        picker: &Arc<dyn Picker>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        if let Some((g, fallback)) = self.fallback.read().as_ref() {
+            if *g == generation {
+                return Ok(fallback.clone());
+            }
+        }
+        let fallback = RoundRobin.build_picker(Arc::new(picker.nodes().to_vec()))?;
+        let mut cached = self.fallback.write();
+        if cached.as_ref().is_none_or(|(g, _)| *g <= generation) {
+            *cached = Some((generation, fallback.clone()));
+        }
+        Ok(fallback)
+    }
+
+    /// Pick a healthy node in at most `1 + max_retries` attempts.
+    ///
+    /// `req` itself is left unchanged. If every attempt fails, returns the last unhealthy
+    /// node picked (best effort), or the last error if no attempt returned a node.
+    pub fn pick_with_retry(
+        &self,
+        req: &RequestMetadata,
+        max_retries: usize,
+    ) -> Result<Arc<Node>, LoadBalanceError> {
+        let (generation, picker) = self.balancer.current_picker()?;
+        let mut fallback: Option<Arc<dyn Picker>> = None;
+        let mut req = Cow::Borrowed(req);
+        // Node of the previous attempt, and the last unhealthy node of any attempt
+        let mut previous = None;
+        let mut best_effort = None;
+        let mut last_err = LoadBalanceError::NoAvailableNodes;
+
+        for attempt in 0..=max_retries {
+            if attempt > 0 {
+                self.policy.apply(req.to_mut(), previous.as_ref());
+            }
+            let picked = match picker.pick(&req) {
+                Err(LoadBalanceError::MissingHashKey) => {
+                    let fallback = match &fallback {
+                        Some(fallback) => fallback,
+                        None => fallback.insert(self.fallback(generation, &picker)?),
+                    };
+                    fallback.pick(&req)
+                }
+                res => res,
+            };
+            match picked {
+                Ok(node) if self.health.is_healthy(&node) => return Ok(node),
+                Ok(node) => {
+                    best_effort = Some(node.clone());
+                    previous = Some(node);
+                }
+                Err(e) => {
+                    previous = None;
+                    last_err = e;
+                }
+            }
+        }
+        best_effort.ok_or(last_err)
+    }
+}
+
+/// Balancer with a dedicated node pool per tenant
+///
+/// Every tenant gets its own `BaseBalancer` built from a clone of the shared strategy
+/// with its state detached (see `BalanceStrategy::detach_state`), so node lists, picker
+/// state (e.g. rotation cursors) and strategy state (e.g. `PeakEwma` estimates) never
+/// leak across tenants. The tenant's picker is rebuilt on registration and reused for
+/// every pick; report responses to it through `tenant_picker`.
+pub struct MultiTenantBalancer<S: BalanceStrategy + Clone> {
+    strategy: S,
+    tenants: RwLock<HashMap<String, TenantEntry<S>>>,
+}
+
+struct TenantEntry<S: BalanceStrategy> {
+    balancer: BaseBalancer<S>,
+    picker: Arc<dyn Picker>,
+}
+
+impl<S: BalanceStrategy + Clone> MultiTenantBalancer<S> {
+    pub fn new(strategy: S) -> Self {
+        Self {
+            strategy,
+            tenants: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register a tenant or replace the nodes of an existing one.
+    pub fn register_tenant(
+        &self,
+        tenant_id: &str,
+        nodes: Vec<Arc<Node>>,
+    ) -> Result<(), LoadBalanceError> {
+        let mut strategy = self.strategy.clone();
+        strategy.detach_state();
+        let balancer = BaseBalancer::new(strategy);
+        balancer.update_nodes(nodes);
+        let picker = balancer.picker()?;
+        self.tenants
+            .write()
+            .insert(tenant_id.to_owned(), TenantEntry { balancer, picker });
+        Ok(())
+    }
+
+    /// Remove a tenant, returns whether it was registered.
+    pub fn remove_tenant(&self, tenant_id: &str) -> bool {
+        self.tenants.write().remove(tenant_id).is_some()
+    }
+
+    pub fn contains_tenant(&self, tenant_id: &str) -> bool {
+        self.tenants.read().contains_key(tenant_id)
+    }
+
+    /// Nodes currently registered for a tenant.
+    pub fn tenant_nodes(&self, tenant_id: &str) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        self.tenants
+            .read()
+            .get(tenant_id)
+            .map(|entry| entry.balancer.nodes.read().clone())
+            .ok_or_else(|| LoadBalanceError::UnknownTenant(tenant_id.to_owned()))
+    }
+
+    /// Picker of a tenant, e.g. to report responses with `Picker::on_response`.
+    pub fn tenant_picker(&self, tenant_id: &str) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        self.tenants
+            .read()
+            .get(tenant_id)
+            .map(|entry| entry.picker.clone())
+            .ok_or_else(|| LoadBalanceError::UnknownTenant(tenant_id.to_owned()))
+    }
+
+    /// Clone of a tenant's strategy, sharing its state, e.g. to read `PeakEwma` estimates.
+    pub fn tenant_strategy(&self, tenant_id: &str) -> Result<S, LoadBalanceError> {
+        self.tenants
+            .read()
+            .get(tenant_id)
+            .map(|entry| entry.balancer.strategy().clone())
+            .ok_or_else(|| LoadBalanceError::UnknownTenant(tenant_id.to_owned()))
+    }
+
+    pub fn pick_for_tenant(
+        &self,
+        tenant_id: &str,
+        req: &RequestMetadata,
+    ) -> Result<Arc<Node>, LoadBalanceError> {
+        self.tenant_picker(tenant_id)?.pick(req)
+    }
+}
+
+/// Equal Round Robin Load Balancing Strategy
+///
+/// Features:
+/// - Strict rotation over the node list, every node gets exactly the same share
+/// - Ignores `Node::weight`; use `WeightedRoundRobin` for weight-proportional rotation
+/// - Also exported as `RoundRobin`
+#[derive(Clone, Debug)]
+pub struct EqualRoundRobin;
+
+pub use self::EqualRoundRobin as RoundRobin;
+
+impl BalanceStrategy for EqualRoundRobin {
+    fn name(&self) -> Cow<'static, str> {
+        "round_robin".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(RoundRobinPicker {
+            nodes,
+            idx: AtomicUsize::new(0),
+        }))
+    }
+}
+
+struct RoundRobinPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Ever increasing cursor, reduced modulo the node count on use. `fetch_add` wraps
+    // after `usize::MAX` picks and `% len` keeps every index in range across the wrap;
+    // unless `len` is a power of two, that one step jumps back to the first node
+    idx: AtomicUsize,
+}
+
+impl RoundRobinPicker {
+    // Up to `n` consecutive nodes from the cursor, scanning at most one rotation so no
+    // node repeats; `advance` moves the cursor past the slots handed out
+    fn rotate(&self, req: &RequestMetadata, n: usize, advance: bool) -> Vec<Arc<Node>> {
+        let len = self.nodes.len();
+        if len == 0 {
+            return Vec::new();
+        }
+
+        let start = self.idx.load(Ordering::Relaxed) % len;
+        let mut picked = Vec::with_capacity(n.min(len));
+        let mut scanned = 0;
+        while scanned < len && picked.len() < n {
+            let node = &self.nodes[(start + scanned) % len];
+            if !req.is_excluded(node) {
+                picked.push(node.clone());
+            }
+            scanned += 1;
+        }
+        if advance {
+            self.idx.fetch_add(scanned, Ordering::Relaxed);
+        }
+        picked
+    }
+}
+
+impl Picker for RoundRobinPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.pick_index(req).map(|i| self.nodes[i].clone())
+    }
+
+    fn pick_index(&self, req: &RequestMetadata) -> Result<usize, LoadBalanceError> {
+        let len = self.nodes.len();
+        if len == 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // Lock-free: concurrent picks each claim their own slot
+        let start = self.idx.fetch_add(1, Ordering::Relaxed) % len;
+
+        // Excluded nodes are stepped over, at most one full rotation, and the cursor is
+        // moved past them so the next pick continues after the returned node
+        for k in 0..len {
+            let i = (start + k) % len;
+            if !req.is_excluded(&self.nodes[i]) {
+                if k > 0 {
+                    self.idx.fetch_add(k, Ordering::Relaxed);
+                }
+                return Ok(i);
+            }
+        }
+        Err(LoadBalanceError::NoAvailableNodes)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        if self.nodes.is_empty() {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        let picked = self.rotate(req, n, true);
+        if picked.is_empty() && n > 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+        Ok(picked)
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let picked = self.rotate(req, n, false);
+        if picked.len() < n {
+            return check_batch(picked, n);
+        }
+        // Only a complete batch advances the cursor
+        Ok(self.rotate(req, n, true))
+    }
+}
+
+/// Weighted Round Robin Load Balancing Strategy
+///
+/// Features:
+/// - Smooth interleaving proportional to node weights (nginx's smooth WRR): picks of
+///   each node are spread evenly over every cycle, also for weights like 1 and 1000
+/// - Supported weight range is `0..=i32::MAX`; larger weights fail with `WeightOverflow`
+/// - Degrades to plain rotation when all weights are 0
+pub struct WeightedRoundRobin;
+
+impl WeightedRoundRobin {
+    /// Use `weights` instead of the nodes' own weights, see `StaticWeightSnapshot`.
+    pub fn with_weights(self, weights: Vec<u32>) -> StaticWeightSnapshot<Self> {
+        StaticWeightSnapshot::new(self, weights)
+    }
+}
+
+impl BalanceStrategy for WeightedRoundRobin {
+    fn name(&self) -> Cow<'static, str> {
+        "weighted_round_robin".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights = node_weights(&nodes);
+        self.build_picker_with_weights(nodes, &weights)
+    }
+
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(WRRPicker::new(nodes, weights)?))
+    }
+}
+
+/// Smooth Weighted Round Robin with least-loaded tie-breaking
+///
+/// Features:
+/// - Same smooth WRR sequence and weight handling as `WeightedRoundRobin`
+/// - When several nodes share the highest current weight, picks the one with fewer
+///   `in_flight` requests among two of them (P2C) instead of the earliest one
+/// - Every cycle still holds each node's weighted share of picks; only the order
+///   within a cycle follows the load
+pub struct RoundRobinWithWeightedJump;
+
+impl RoundRobinWithWeightedJump {
+    /// Use `weights` instead of the nodes' own weights, see `StaticWeightSnapshot`.
+    pub fn with_weights(self, weights: Vec<u32>) -> StaticWeightSnapshot<Self> {
+        StaticWeightSnapshot::new(self, weights)
+    }
+}
+
+impl BalanceStrategy for RoundRobinWithWeightedJump {
+    fn name(&self) -> Cow<'static, str> {
+        "round_robin_with_weighted_jump".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights = node_weights(&nodes);
+        self.build_picker_with_weights(nodes, &weights)
+    }
+
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let mut picker = WRRPicker::new(nodes, weights)?;
+        picker.break_ties = true;
+        Ok(Arc::new(picker))
+    }
+}
+
+struct WRRPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Smooth WRR state: the current weight of every node
+    current: parking_lot::Mutex<Vec<i64>>,
+    // Rotation cursor for when no remaining node has a positive weight
+    idx: parking_lot::Mutex<usize>,
+    weights: Vec<i64>,
+    // Break ties of the highest current weight by load, see `RoundRobinWithWeightedJump`
+    break_ties: bool,
+}
+
+impl WRRPicker {
+    fn new(nodes: Arc<Vec<Arc<Node>>>, node_weights: &[u32]) -> Result<Self, LoadBalanceError> {
+        let mut weights = Vec::with_capacity(nodes.len());
+        for (n, &weight) in nodes.iter().zip(node_weights) {
+            // Keeps the sum of all weights far from `i64` overflow
+            let w = i32::try_from(weight).map_err(|_| LoadBalanceError::WeightOverflow {
+                node_id: n.endpoint.id,
+                weight,
+            })?;
+            weights.push(w as i64);
+        }
+        Ok(Self {
+            current: parking_lot::Mutex::new(vec![0; nodes.len()]),
+            nodes,
+            idx: parking_lot::Mutex::new(usize::MAX),
+            weights,
+            break_ties: false,
+        })
+    }
+
+    // The less loaded of two random nodes tied with `best` for the highest current
+    // weight, `best` itself if it has no tie
+    fn break_tie(&self, current: &[i64], best: usize, req: &RequestMetadata) -> usize {
+        let tied: SmallVec<[usize; 8]> = (0..self.nodes.len())
+            .filter(|&i| {
+                self.weights[i] != 0
+                    && current[i] == current[best]
+                    && !req.is_excluded(&self.nodes[i])
+            })
+            .collect();
+        let (a, b) = match tied.len() {
+            0 | 1 => return best,
+            2 => (tied[0], tied[1]),
+            len => {
+                let mut rng = rand::thread_rng();
+                let a = rng.gen_range(0..len);
+                let b = (a + rng.gen_range(1..len)) % len;
+                (tied[a].min(tied[b]), tied[a].max(tied[b]))
+            }
+        };
+        let load = |i: usize| self.nodes[i].in_flight.load(Ordering::Acquire);
+        if load(b) < load(a) {
+            b
+        } else {
+            a
+        }
+    }
+}
+
+impl Picker for WRRPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.pick_index(req).map(|i| self.nodes[i].clone())
+    }
+
+    fn pick_index(&self, req: &RequestMetadata) -> Result<usize, LoadBalanceError> {
+        let len = self.nodes.len();
+        if len == 0 || self.nodes.iter().all(|n| req.is_excluded(n)) {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // Smooth WRR over the nodes `req` allows: every node gains its weight, the one with
+        // the highest current weight (earliest among equals) wins and gives back the total.
+        // Excluded nodes keep their current weight for later picks.
+        {
+            let mut current = self.current.lock();
+            let mut total = 0i64;
+            let mut best: Option<usize> = None;
+            for (i, node) in self.nodes.iter().enumerate() {
+                let w = self.weights[i];
+                if w == 0 || req.is_excluded(node) {
+                    continue;
+                }
+                current[i] += w;
+                total += w;
+                if best.is_none_or(|b| current[i] > current[b]) {
+                    best = Some(i);
+                }
+            }
+            if let Some(mut best) = best {
+                if self.break_ties {
+                    best = self.break_tie(&current, best, req);
+                }
+                current[best] -= total;
+                return Ok(best);
+            }
+        }
+
+        // Only zero weights left: degrade to simple polling, stepping over excluded nodes
+        let mut i = self.idx.lock();
+        loop {
+            *i = if *i == usize::MAX { 0 } else { (*i + 1) % len };
+            if !req.is_excluded(&self.nodes[*i]) {
+                return Ok(*i);
+            }
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+}
+
+/// Weighted Round Robin over fractional weights
+///
+/// Features:
+/// - Smooth WRR like `WeightedRoundRobin`, in `f64` arithmetic over
+///   `Node::fractional_weight` (the integer weight unless a fractional one is set)
+/// - Current weights within `epsilon` of each other count as equal, so rounding noise
+///   does not reorder the sequence; the earliest node wins ties
+/// - Weights not above `epsilon` (including negative and NaN ones) are never picked,
+///   unless no other node is left
+#[derive(Clone, Debug)]
+pub struct FractionalWRR {
+    pub epsilon: f64,
+}
+
+impl FractionalWRR {
+    pub const DEFAULT_EPSILON: f64 = 1e-9;
+}
+
+impl Default for FractionalWRR {
+    fn default() -> Self {
+        Self {
+            epsilon: Self::DEFAULT_EPSILON,
+        }
+    }
+}
+
+impl BalanceStrategy for FractionalWRR {
+    fn name(&self) -> Cow<'static, str> {
+        "fractional_weighted_round_robin".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.epsilon.to_bits().hash(&mut h);
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let epsilon = if self.epsilon.is_finite() {
+            self.epsilon.abs()
+        } else {
+            Self::DEFAULT_EPSILON
+        };
+        let weights = nodes
+            .iter()
+            .map(|n| {
+                let w = n.fractional_weight();
+                if w > epsilon && w.is_finite() {
+                    w
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        Ok(Arc::new(FractionalWRRPicker {
+            current: parking_lot::Mutex::new(vec![0.0; nodes.len()]),
+            nodes,
+            idx: parking_lot::Mutex::new(usize::MAX),
+            weights,
+            epsilon,
+        }))
+    }
+}
+
+struct FractionalWRRPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    current: parking_lot::Mutex<Vec<f64>>,
+    // Rotation cursor for when no remaining node has a positive weight
+    idx: parking_lot::Mutex<usize>,
+    // Aligned with `nodes`, 0.0 for weights that are never picked
+    weights: Vec<f64>,
+    epsilon: f64,
+}
+
+impl Picker for FractionalWRRPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let len = self.nodes.len();
+        if len == 0 || self.nodes.iter().all(|n| req.is_excluded(n)) {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        {
+            let mut current = self.current.lock();
+            let mut total = 0.0;
+            let mut best: Option<usize> = None;
+            for (i, node) in self.nodes.iter().enumerate() {
+                let w = self.weights[i];
+                if w == 0.0 || req.is_excluded(node) {
+                    continue;
+                }
+                current[i] += w;
+                total += w;
+                if best.is_none_or(|b| current[i] > current[b] + self.epsilon) {
+                    best = Some(i);
+                }
+            }
+            if let Some(best) = best {
+                current[best] -= total;
+                return Ok(self.nodes[best].clone());
+            }
+        }
+
+        // Only zero weights left: degrade to simple polling, stepping over excluded nodes
+        let mut i = self.idx.lock();
+        loop {
+            *i = if *i == usize::MAX { 0 } else { (*i + 1) % len };
+            if !req.is_excluded(&self.nodes[*i]) {
+                return Ok(self.nodes[*i].clone());
+            }
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+}
+
+// P2C (Power of Two Choices)
+pub struct PowerOfTwoChoices;
+
+impl PowerOfTwoChoices {
+    /// P2C drawing candidates from a `SmallRng` seeded with `seed`, e.g. for tests.
+    ///
+    /// Every picker starts its own generator from the seed, so pickers built over nodes
+    /// with the same loads return the same sequence of picks.
+    pub fn seeded(seed: u64) -> SeededPowerOfTwoChoices {
+        SeededPowerOfTwoChoices { seed }
+    }
+}
+
+impl BalanceStrategy for PowerOfTwoChoices {
+    fn name(&self) -> Cow<'static, str> {
+        "power_of_two_choices".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(P2CPicker { nodes, rng: None }))
+    }
+}
+
+/// `PowerOfTwoChoices` with reproducible candidate draws
+///
+/// Created with `PowerOfTwoChoices::seeded`. Picks share one generator behind a mutex, so
+/// concurrent picks serialize on it; use plain `PowerOfTwoChoices` outside of tests.
+#[derive(Clone, Copy, Debug)]
+pub struct SeededPowerOfTwoChoices {
+    pub seed: u64,
+}
+
+impl BalanceStrategy for SeededPowerOfTwoChoices {
+    fn name(&self) -> Cow<'static, str> {
+        "seeded_power_of_two_choices".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.seed.hash(&mut h);
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(P2CPicker {
+            nodes,
+            rng: Some(parking_lot::Mutex::new(SmallRng::seed_from_u64(self.seed))),
+        }))
+    }
+}
+
+struct P2CPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Seeded generator of `SeededPowerOfTwoChoices`, `thread_rng` if `None`
+    rng: Option<parking_lot::Mutex<SmallRng>>,
+}
+
+impl P2CPicker {
+    fn with_rng<T>(&self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        match &self.rng {
+            Some(rng) => f(&mut *rng.lock()),
+            None => f(&mut rand::thread_rng()),
+        }
+    }
+}
+
+impl Picker for P2CPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let Some((a, b)) = self.with_rng(|rng| sample_two(&self.nodes, req, rng))? else {
+            return Ok(self.nodes[first_allowed(&self.nodes, req)].clone());
+        };
+        let na = self.nodes[a]
+            .in_flight
+            .load(std::sync::atomic::Ordering::Acquire);
+        let nb = self.nodes[b]
+            .in_flight
+            .load(std::sync::atomic::Ordering::Acquire);
+        Ok(if na <= nb {
+            self.nodes[a].clone()
+        } else {
+            self.nodes[b].clone()
+        })
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let allowed: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| !req.is_excluded(&self.nodes[i]))
+            .collect();
+        let len = allowed.len();
+        if len == 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // Two distinct candidates per requested slot, keep the n least loaded
+        let count = n.min(len);
+        let samples = n.saturating_mul(2).min(len);
+        let sampled = self.with_rng(|rng| rand::seq::index::sample(rng, len, samples));
+        let mut candidates: Vec<(usize, usize)> = sampled
+            .into_iter()
+            .map(|k| {
+                let i = allowed[k];
+                let load = self.nodes[i]
+                    .in_flight
+                    .load(std::sync::atomic::Ordering::Acquire);
+                (load, i)
+            })
+            .collect();
+        candidates.sort_unstable();
+
+        Ok(candidates
+            .into_iter()
+            .take(count)
+            .map(|(_, i)| self.nodes[i].clone())
+            .collect())
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        match self.pick_n(req, n) {
+            Ok(picked) => check_batch(picked, n),
+            Err(LoadBalanceError::NoAvailableNodes) => check_batch(Vec::new(), n),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Power of K Choices Strategy
+///
+/// Features:
+/// - Generalizes `PowerOfTwoChoices`: samples `k` distinct nodes and picks the one with
+///   the lowest `in_flight / weight` (weight 0 counts as 1), earliest sampled among equals
+/// - Larger `k` spreads load more evenly at the cost of reading more nodes per pick;
+///   `k` at or above the node count degrades to weighted least connection
+/// - `k` is clamped to the nodes `RequestMetadata` allows, 0 counts as 1
+/// - Default `k` is 2; `PowerOfTwoChoices` stays the unweighted, allocation-free variant
+#[derive(Clone, Copy, Debug)]
+pub struct PowerOfKChoices {
+    pub k: usize,
+}
+
+impl Default for PowerOfKChoices {
+    fn default() -> Self {
+        Self { k: 2 }
+    }
+}
+
+impl PowerOfKChoices {
+    pub fn new(k: usize) -> Self {
+        Self { k }
+    }
+}
+
+impl BalanceStrategy for PowerOfKChoices {
+    fn name(&self) -> Cow<'static, str> {
+        "power_of_k_choices".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        h.write_usize(self.k);
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(PowerOfKPicker {
+            nodes,
+            k: self.k.max(1),
+        }))
+    }
+}
+
+struct PowerOfKPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    k: usize,
+}
+
+impl PowerOfKPicker {
+    fn load(node: &Node) -> f64 {
+        node.in_flight.load(Ordering::Acquire) as f64 / node.weight().max(1) as f64
+    }
+
+    // `samples` distinct allowed nodes drawn at random, ranked by load
+    fn sample_ranked(
+        &self,
+        req: &RequestMetadata,
+        samples: usize,
+    ) -> Result<Vec<(f64, usize)>, LoadBalanceError> {
+        let allowed: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| !req.is_excluded(&self.nodes[i]))
+            .collect();
+        if allowed.is_empty() {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        let samples = samples.min(allowed.len());
+        let mut ranked: Vec<(f64, usize)> =
+            rand::seq::index::sample(&mut rand::thread_rng(), allowed.len(), samples)
+                .into_iter()
+                .map(|s| {
+                    let i = allowed[s];
+                    (Self::load(&self.nodes[i]), i)
+                })
+                .collect();
+        // Stable sort keeps sample order for equal loads
+        ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(ranked)
+    }
+}
+
+impl Picker for PowerOfKPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let ranked = self.sample_ranked(req, self.k)?;
+        Ok(self.nodes[ranked[0].1].clone())
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        // `k` distinct candidates per requested slot, keep the n least loaded
+        let ranked = self.sample_ranked(req, n.saturating_mul(self.k))?;
+        Ok(ranked
+            .into_iter()
+            .take(n)
+            .map(|(_, i)| self.nodes[i].clone())
+            .collect())
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        match self.pick_n(req, n) {
+            Ok(picked) => check_batch(picked, n),
+            Err(LoadBalanceError::NoAvailableNodes) => check_batch(Vec::new(), n),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Weighted Random Load Balancing Strategy
+///
+/// Features:
+/// - Random selection based on node weights
+/// - Higher weight means higher probability of being selected
+/// - Performance optimizations:
+///   - O(1) sampling via Vose's alias method after O(n) setup at build time
+///   - Uses thread-local random number generator
+///   - Handles cases where all weights are 0
+/// - Weights are snapshot at build time; if every live `Node::weight` has dropped to 0
+///   since, picks degrade to uniform like a rebuilt picker would
+#[derive(Clone, Debug)]
+pub struct WeightedRandom;
+
+impl WeightedRandom {
+    /// Like `build_picker`, but fails with `InvalidWeights` instead of falling back to
+    /// uniform picks when no node has a positive weight.
+    pub fn build_picker_checked(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        if !nodes.is_empty() && nodes.iter().all(|n| n.weight() == 0) {
+            return Err(LoadBalanceError::InvalidWeights);
+        }
+        self.build_picker(nodes)
+    }
+
+    /// Use `weights` instead of the nodes' own weights, see `StaticWeightSnapshot`.
+    pub fn with_weights(self, weights: Vec<u32>) -> StaticWeightSnapshot<Self> {
+        StaticWeightSnapshot::new(self, weights)
+    }
+}
+
+impl BalanceStrategy for WeightedRandom {
+    fn name(&self) -> Cow<'static, str> {
+        "weighted_random".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights = node_weights(&nodes);
+        Ok(Arc::new(WeightedRandomPicker::new(nodes, &weights, true)))
+    }
+
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        // Overridden weights are not the nodes' own, so there is nothing to revalidate
+        Ok(Arc::new(WeightedRandomPicker::new(nodes, weights, false)))
+    }
+}
+
+/// Slow node demotion settings of `SlowAwareWeightedRandom`
+#[derive(Clone, Debug)]
+pub struct WeightedRandomConfig {
+    /// Nodes whose `last_rtt_ns` exceeds this count as slow, see `Node::is_slow`
+    pub slow_threshold_ns: u64,
+    /// Multiplier applied to the weight of slow nodes, in `0.0..=1.0`
+    pub slow_node_weight_penalty: f64,
+}
+
+impl Default for WeightedRandomConfig {
+    fn default() -> Self {
+        Self {
+            slow_threshold_ns: 1_000_000_000,
+            slow_node_weight_penalty: 0.1,
+        }
+    }
+}
+
+/// Weighted Random with slow node demotion
+///
+/// Features:
+/// - Same as `WeightedRandom`, but nodes that are slow when the picker is built have
+///   their weight multiplied by `slow_node_weight_penalty`
+/// - Slow nodes stay reachable, with a smaller share, until a rebuild sees them recover
+/// - Created with `WeightedRandom::with_config`
+#[derive(Clone, Debug)]
+pub struct SlowAwareWeightedRandom {
+    config: WeightedRandomConfig,
+}
+
+impl WeightedRandom {
+    pub fn with_config(self, config: WeightedRandomConfig) -> SlowAwareWeightedRandom {
+        SlowAwareWeightedRandom { config }
+    }
+}
+
+impl BalanceStrategy for SlowAwareWeightedRandom {
+    fn name(&self) -> Cow<'static, str> {
+        "slow_aware_weighted_random".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.config.slow_threshold_ns.hash(&mut h);
+        self.config.slow_node_weight_penalty.to_bits().hash(&mut h);
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights = node_weights(&nodes);
+        self.build_picker_with_weights(nodes, &weights)
+    }
+
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let penalty = self.config.slow_node_weight_penalty.clamp(0.0, 1.0);
+        // All-zero weights count as equal weights, before the penalty
+        let all_zero = weights.iter().all(|&w| w == 0);
+        let weights: Vec<f64> = nodes
+            .iter()
+            .zip(weights)
+            .map(|(node, &w)| {
+                let w = if all_zero { 1.0 } else { w as f64 };
+                if node.is_slow(self.config.slow_threshold_ns) {
+                    w * penalty
+                } else {
+                    w
+                }
+            })
+            .collect();
+
+        Ok(Arc::new(WeightedRandomPicker {
+            table: AliasTable::new(&weights),
+            nodes,
+            weights,
+            live_weights: false,
+        }))
+    }
+}
+
+// Vose's alias table: each slot holds its own probability and an alias to fall back to
+struct AliasTable {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    fn new(weights: &[f64]) -> Option<Self> {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+        if n == 0 || !total.is_finite() || total <= 0.0 {
+            return None;
+        }
+
+        // Scale so the average slot probability is exactly 1
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut small = Vec::with_capacity(n);
+        let mut large = Vec::with_capacity(n);
+        for (i, &p) in scaled.iter().enumerate() {
+            if p < 1.0 {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+
+        let mut prob = vec![0.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            large.pop();
+            prob[s] = scaled[s];
+            alias[s] = l;
+
+            // The large slot donates what the small one lacks
+            scaled[l] = (scaled[l] + scaled[s]) - 1.0;
+            if scaled[l] < 1.0 {
+                small.push(l);
+            } else {
+                large.push(l);
+            }
+        }
+
+        // Whatever remains is 1 up to floating point error
+        for i in large.into_iter().chain(small) {
+            prob[i] = 1.0;
+        }
+
+        Some(Self { prob, alias })
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let i = rng.gen_range(0..self.prob.len());
+        if rng.gen::<f64>() < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+struct WeightedRandomPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Aligned with `nodes`
+    weights: Vec<f64>,
+    table: Option<AliasTable>,
+    // `weights` are the nodes' own weights, so a live all-zero state can be detected
+    live_weights: bool,
+}
+
+impl WeightedRandomPicker {
+    fn new(nodes: Arc<Vec<Arc<Node>>>, weights: &[u32], live_weights: bool) -> Self {
+        // Check if all node weights are 0
+        let all_zero = weights.iter().all(|&w| w == 0);
+
+        // If all weights are 0, use equal weights
+        let table_weights: Vec<f64> = if all_zero {
+            weights.iter().map(|_| 1.0).collect()
+        } else {
+            weights.iter().map(|&w| (w as f64).max(0.0)).collect()
+        };
+
+        let table = AliasTable::new(&table_weights);
+        Self {
+            nodes,
+            weights: weights.iter().map(|&w| w as f64).collect(),
+            table,
+            live_weights,
+        }
+    }
+
+    // O(n) weighted draw among the nodes `req` does not exclude
+    fn pick_allowed(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let allowed: Vec<(&Arc<Node>, f64)> = self
+            .nodes
+            .iter()
+            .zip(self.weights.iter().copied())
+            .filter(|(n, _)| !req.is_excluded(n))
+            .collect();
+        if allowed.is_empty() {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // Same as at build time: all-zero weights count as equal weights
+        let all_zero = allowed.iter().all(|&(_, w)| w <= 0.0)
+            || (self.live_weights && allowed.iter().all(|(n, _)| n.weight() == 0));
+        let weight = |w: f64| if all_zero { 1.0 } else { w.max(0.0) };
+        let total: f64 = allowed.iter().map(|&(_, w)| weight(w)).sum();
+
+        let mut point = rand::thread_rng().gen::<f64>() * total;
+        for &(node, w) in &allowed {
+            let w = weight(w);
+            if point < w {
+                return Ok(node.clone());
+            }
+            point -= w;
+        }
+        Ok(allowed[allowed.len() - 1].0.clone())
+    }
+}
+
+impl Picker for WeightedRandomPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let len = self.nodes.len();
+        if len == 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // The alias table covers every node, so exclusions take a linear weighted scan
+        if req.has_exclusions() {
+            return self.pick_allowed(req);
+        }
+
+        // If there is only one node, return directly
+        let idx = if len == 1 {
+            0
+        } else {
+            // Use thread-local random number generator to avoid creating a new generator each time
+            let mut rng = rand::thread_rng();
+            match &self.table {
+                Some(table) => table.sample(&mut rng),
+                // If there is no weight distribution, degrade to uniform selection
+                None => rng.gen_range(0..len),
+            }
+        };
+        // A drawn node whose weight was zeroed since the build is the cheap signal to check
+        // whether every weight is 0 now
+        let idx = if self.live_weights
+            && self.nodes[idx].weight() == 0
+            && self.nodes.iter().all(|n| n.weight() == 0)
+        {
+            rand::thread_rng().gen_range(0..len)
+        } else {
+            idx
+        };
+        // Disabled nodes are only discovered once drawn
+        if !self.nodes[idx].is_enabled() {
+            return self.pick_allowed(req);
+        }
+        Ok(self.nodes[idx].clone())
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+}
+
+/// Weighted Random Load Balancing Strategy reading weights at pick time
+///
+/// Features:
+/// - Same distribution as `WeightedRandom`, including the all-zero fallback to equal weights
+/// - A-Res weighted reservoir sampling (size 1) in one pass over the nodes on every pick
+/// - Reads `Node::weight` live, so `set_weight` takes effect without rebuilding the picker
+/// - Tradeoff: no setup cost at build time, but O(n) per pick instead of O(1); prefer
+///   `WeightedRandom` unless weights change more often than picks happen
+#[derive(Clone, Debug)]
+pub struct WeightedReservoir;
+
+impl BalanceStrategy for WeightedReservoir {
+    fn name(&self) -> Cow<'static, str> {
+        "weighted_reservoir".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(WeightedReservoirPicker { nodes }))
+    }
+}
+
+struct WeightedReservoirPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+}
+
+impl Picker for WeightedReservoirPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let mut rng = rand::thread_rng();
+        // Highest `ln(u) / w` among positive weights, i.e. the largest `u^(1/w)`
+        let mut weighted: Option<(f64, &Arc<Node>)> = None;
+        // Uniform reservoir over zero weights, used only if no positive weight is left
+        let mut zero: Option<&Arc<Node>> = None;
+        let mut zeros = 0u32;
+
+        for node in self.nodes.iter().filter(|n| !req.is_excluded(n)) {
+            let w = node.weight();
+            if w == 0 {
+                zeros += 1;
+                if rng.gen_range(0..zeros) == 0 {
+                    zero = Some(node);
+                }
+                continue;
+            }
+            // `u` in (0, 1], so the key is finite
+            let u = 1.0 - rng.gen::<f64>();
+            let key = u.ln() / w as f64;
+            if weighted.is_none_or(|(best, _)| key > best) {
+                weighted = Some((key, node));
+            }
+        }
+
+        weighted
+            .map(|(_, node)| node)
+            .or(zero)
+            .cloned()
+            .ok_or(LoadBalanceError::NoAvailableNodes)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+}
+
+// Least Connection; healthy nodes first, see `HealthFilter`
+pub struct LeastConnection;
+
+impl BalanceStrategy for LeastConnection {
+    fn name(&self) -> Cow<'static, str> {
+        "least_connection".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(LeastConnPicker {
+            nodes,
+            health: HealthFilter::default(),
+        }))
+    }
+}
+
+struct LeastConnPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    health: HealthFilter,
+}
+
+impl LeastConnPicker {
+    fn load(&self, n: &Node) -> (bool, usize) {
+        self.health.rank(n, n.in_flight.load(Ordering::Acquire))
+    }
+}
+
+impl Picker for LeastConnPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let mut iter = self.nodes.iter().filter(|n| !req.is_excluded(n));
+        let mut best = iter.next().ok_or(LoadBalanceError::NoAvailableNodes)?;
+        let mut best_load = self.load(best);
+        for n in iter {
+            let load = self.load(n);
+            if load < best_load {
+                best = n;
+                best_load = load;
+            }
+        }
+        Ok(best.clone())
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        smallest_n(&self.nodes, req, n, |node| self.load(node))
+    }
+}
+
+/// Weighted Least Connection Load Balancing Strategy
+///
+/// Features:
+/// - Picks the node with the lowest `in_flight / weight` (weight 0 counts as 1)
+/// - High-weight nodes are expected to carry proportionally more connections
+/// - Ties go to the node with the lower `error_rate`, then the higher weight, then the earliest
+/// - If every node sets `max_in_flight`, ranks by `Node::utilization` instead: the explicit
+///   capacity replaces the weight
+/// - Healthy nodes rank before unhealthy ones, see `HealthFilter`
+#[derive(Clone, Debug)]
+pub struct LeastConnectionWeighted;
+
+impl BalanceStrategy for LeastConnectionWeighted {
+    fn name(&self) -> Cow<'static, str> {
+        "least_connection_weighted".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(WeightedLeastConnPicker {
+            capped: all_capped(&nodes),
+            nodes,
+            health: HealthFilter::default(),
+        }))
+    }
+}
+
+struct WeightedLeastConnPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Every node has a `max_in_flight`, rank by utilization
+    capped: bool,
+    health: HealthFilter,
+}
+
+impl WeightedLeastConnPicker {
+    fn load(&self, n: &Node) -> (bool, f64) {
+        let load = if self.capped {
+            n.utilization()
+        } else {
+            n.in_flight.load(Ordering::Acquire) as f64 / n.weight().max(1) as f64
+        };
+        self.health.rank(n, load)
+    }
+}
+
+impl Picker for WeightedLeastConnPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let load = |n: &Node| self.load(n);
+
+        let mut iter = self.nodes.iter().filter(|n| !req.is_excluded(n));
+        let mut best = iter.next().ok_or(LoadBalanceError::NoAvailableNodes)?;
+        let mut best_load = load(best);
+        for n in iter {
+            let l = load(n);
+            let better = l < best_load
+                || (l == best_load && {
+                    let (e, best_e) = (n.error_rate(), best.error_rate());
+                    e < best_e || (e == best_e && n.weight() > best.weight())
+                });
+            if better {
+                best = n;
+                best_load = l;
+            }
+        }
+        Ok(best.clone())
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        smallest_n(&self.nodes, req, n, |node| {
+            (
+                self.load(node),
+                node.error_rate(),
+                std::cmp::Reverse(node.weight()),
+            )
+        })
+    }
+}
+
+/// Least Load Strategy for backends behind a queue
+///
+/// Features:
+/// - Picks the node with the lowest `in_flight + queue_depth * queue_multiplier`, for
+///   backends whose own queue (e.g. in a proxy) hides load that `in_flight` misses
+/// - `queue_depth` is reported by the caller with `Node::set_queue_depth`; nodes that
+///   never report one rank by `in_flight` alone
+/// - Ties go to the earliest node, like `LeastConnection`
+/// - If every node sets `max_in_flight`, the score is divided by it, so nodes of different
+///   capacity compare by utilization (see `Node::utilization`)
+/// - Default `queue_multiplier` is 2; 0 ignores queues entirely
+/// - Healthy nodes rank before unhealthy ones, see `HealthFilter`
+#[derive(Clone, Debug)]
+pub struct LeastLoad {
+    pub queue_multiplier: u32,
+}
+
+impl Default for LeastLoad {
+    fn default() -> Self {
+        Self {
+            queue_multiplier: Self::DEFAULT_QUEUE_MULTIPLIER,
+        }
+    }
+}
+
+impl LeastLoad {
+    pub const DEFAULT_QUEUE_MULTIPLIER: u32 = 2;
+
+    pub fn new(queue_multiplier: u32) -> Self {
+        Self { queue_multiplier }
+    }
+}
+
+impl BalanceStrategy for LeastLoad {
+    fn name(&self) -> Cow<'static, str> {
+        "least_load".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.queue_multiplier.hash(&mut h);
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(LeastLoadPicker {
+            capped: all_capped(&nodes),
+            nodes,
+            queue_multiplier: self.queue_multiplier as u64,
+            health: HealthFilter::default(),
+        }))
+    }
+}
+
+struct LeastLoadPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    queue_multiplier: u64,
+    // Every node has a `max_in_flight`, normalize scores by it
+    capped: bool,
+    health: HealthFilter,
+}
+
+impl LeastLoadPicker {
+    fn score(&self, node: &Node) -> (bool, f64) {
+        let queued = node.queue_depth.load(Ordering::Acquire) as u64;
+        let load = (node.in_flight.load(Ordering::Acquire) as u64)
+            .saturating_add(queued.saturating_mul(self.queue_multiplier)) as f64;
+        let score = if self.capped {
+            load / node.max_in_flight as f64
+        } else {
+            load
+        };
+        self.health.rank(node, score)
+    }
+}
+
+impl Picker for LeastLoadPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let mut iter = self.nodes.iter().filter(|n| !req.is_excluded(n));
+        let mut best = iter.next().ok_or(LoadBalanceError::NoAvailableNodes)?;
+        let mut best_score = self.score(best);
+        for n in iter {
+            let score = self.score(n);
+            if score < best_score {
+                best = n;
+                best_score = score;
+            }
+        }
+        Ok(best.clone())
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        smallest_n(&self.nodes, req, n, |node| self.score(node))
+    }
+}
+
+/// Response Time Weighted Load Balancing Strategy
+///
+/// Features:
+/// - Weighted selection based on node's recent response time (RTT)
+/// - Smaller RTT means higher weight
+/// - Also considers current load (in_flight)
+/// - Requests tagged `RequestMetadata::LATENCY_SENSITIVE_TAG` square the RTT term, so
+///   faster nodes win unless they carry much more load
+/// - Healthy nodes rank before unhealthy ones, see `HealthFilter`
+/// - Performance optimization: single-pass scan to find the highest score (O(n))
+#[derive(Clone, Debug)]
+pub struct ResponseTimeWeighted;
+
+impl BalanceStrategy for ResponseTimeWeighted {
+    fn name(&self) -> Cow<'static, str> {
+        "response_time_weighted".into()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(RTWeightedPicker {
+            nodes,
+            health: HealthFilter::default(),
+        }))
+    }
+}
+
+struct RTWeightedPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    health: HealthFilter,
+}
+
+impl Picker for RTWeightedPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        // Single pass O(n) selection; avoids allocation + sort on every pick. Healthy
+        // nodes outrank unhealthy ones, whatever the score.
+        let score_fn = score_fn(req);
+        let score = |n: &Arc<Node>| (self.health.is_healthy(n), score_fn(n));
+        let mut iter = self.nodes.iter().filter(|n| !req.is_excluded(n));
+        let first = iter.next().ok_or(LoadBalanceError::NoAvailableNodes)?;
+        let mut best_node = first.clone();
+        let mut best_score = score(first);
+
+        for node in iter {
+            let s = score(node);
+            if s > best_score {
+                best_score = s;
+                best_node = node.clone();
+            }
+        }
+
+        Ok(best_node)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let score = score_fn(req);
+        smallest_n(&self.nodes, req, n, |node| {
+            self.health.rank(node, std::cmp::Reverse(score(node)))
+        })
+    }
+}
+
+// Lowest possible score; non-finite results are clamped to it
+const MIN_SCORE: f64 = 0.0;
+
+// Scoring `ResponseTimeWeighted` applies to `req`
+fn score_fn(req: &RequestMetadata) -> fn(&Arc<Node>) -> f64 {
+    if req.is_latency_sensitive() {
+        latency_sensitive_score
+    } else {
+        score
+    }
+}
+
+// `score` with its RTT term squared; same guarantees
+fn latency_sensitive_score(n: &Arc<Node>) -> f64 {
+    let rtt = n.last_rtt_ns.load(Ordering::Acquire).max(1);
+    let inflight = n.in_flight.load(Ordering::Acquire);
+    let rtt_score = 1_000_000_000f64 / rtt as f64;
+    let s = rtt_score * rtt_score / (1.0 + inflight as f64);
+    if s.is_finite() {
+        s.max(MIN_SCORE)
+    } else {
+        MIN_SCORE
+    }
+}
+
+/// Score of a node for `ResponseTimeWeighted`, higher is better.
+///
+/// Always finite and `>= MIN_SCORE`, so plain `>` comparisons are a total order and
+/// ties keep the earliest node. Extreme RTTs (e.g. `u64::MAX`) yield tiny but ordered scores.
+fn score(n: &Arc<Node>) -> f64 {
+    // Use atomic operations to get the latest values
+    let rtt = n.last_rtt_ns.load(std::sync::atomic::Ordering::Acquire);
+    let inflight = n.in_flight.load(std::sync::atomic::Ordering::Acquire) as u64;
+
+    // Handle the case where rtt is 0
+    let rtt = rtt.max(1);
+
+    // Calculate response time score in floating point so RTTs above 1s do not truncate to 0
+    let rtt_score = 1_000_000_000f64 / rtt as f64;
+
+    // Calculate load factor
+    let load_factor = 1.0 + inflight as f64;
+
+    // Comprehensive score
+    let s = rtt_score / load_factor;
+    if s.is_finite() {
+        s.max(MIN_SCORE)
+    } else {
+        MIN_SCORE
+    }
+}
+
+/// Peak EWMA Load Balancing Strategy
+///
+/// Features:
+/// - Cost of a node is `ewma_rtt * (in_flight + 1)`, lower is better
+/// - Each pick is a P2C draw between two random nodes, the cheaper one wins
+/// - The estimate jumps to RTT peaks immediately and decays back toward later RTTs
+///   with time constant `decay`, fed through `Picker::on_response`
+/// - Nodes without samples start at `last_rtt_ns`, or `default_rtt` if that is 0
+/// - Estimates are keyed by `endpoint.id` and survive picker rebuilds, including builds
+///   over a subset of the nodes; `BalanceStrategy::prune` drops removed nodes
+/// - Defaults: `decay` 10s, `default_rtt` 30ms
+#[derive(Clone)]
+pub struct PeakEwma {
+    decay: Duration,
+    default_rtt: Duration,
+    states: Arc<parking_lot::Mutex<HashMap<u64, Arc<parking_lot::Mutex<EwmaState>>>>>,
+}
+
+impl Default for PeakEwma {
+    fn default() -> Self {
+        Self {
+            decay: Duration::from_secs(10),
+            default_rtt: Duration::from_millis(30),
+            states: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl PeakEwma {
+    pub fn new(decay: Duration, default_rtt: Duration) -> Self {
+        Self {
+            decay,
+            default_rtt,
+            ..Default::default()
+        }
+    }
+
+    /// Current RTT estimate of a node in nanoseconds, `None` if the node has never been seen.
+    pub fn estimate_ns(&self, node_id: u64) -> Option<f64> {
+        self.states
+            .lock()
+            .get(&node_id)
+            .map(|state| state.lock().rtt_ns)
+    }
+}
+
+struct EwmaState {
+    rtt_ns: f64,
+    updated_at: Instant,
+}
+
+impl EwmaState {
+    fn observe(&mut self, rtt_ns: f64, decay: Duration, now: Instant) {
+        if rtt_ns > self.rtt_ns {
+            // Peak sensitivity: slowdowns are reflected at once
+            self.rtt_ns = rtt_ns;
+        } else {
+            let elapsed = now.saturating_duration_since(self.updated_at).as_nanos() as f64;
+            let tau = (decay.as_nanos() as f64).max(1.0);
+            let w = (-elapsed / tau).exp();
+            self.rtt_ns = self.rtt_ns * w + rtt_ns * (1.0 - w);
+        }
+        self.updated_at = now;
+    }
+}
+
+impl BalanceStrategy for PeakEwma {
+    fn name(&self) -> Cow<'static, str> {
+        "peak_ewma".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.decay.hash(&mut h);
+        self.default_rtt.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.states
+            .lock()
+            .retain(|id, _| live_ids.binary_search(id).is_ok());
+    }
+
+    fn detach_state(&mut self) {
+        self.states = Default::default();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let now = Instant::now();
+        let default_ns = self.default_rtt.as_nanos() as f64;
+
+        let states = {
+            let mut states = self.states.lock();
+            nodes
+                .iter()
+                .map(|node| {
+                    states
+                        .entry(node.endpoint.id)
+                        .or_insert_with(|| {
+                            let seed = node.last_rtt_ns.load(Ordering::Relaxed);
+                            Arc::new(parking_lot::Mutex::new(EwmaState {
+                                rtt_ns: if seed > 0 { seed as f64 } else { default_ns },
+                                updated_at: now,
+                            }))
+                        })
+                        .clone()
+                })
+                .collect()
+        };
+
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.endpoint.id, i))
+            .collect();
+
+        Ok(Arc::new(PeakEwmaPicker {
+            nodes,
+            states,
+            index,
+            decay: self.decay,
+        }))
+    }
+}
+
+struct PeakEwmaPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Aligned with `nodes`
+    states: Vec<Arc<parking_lot::Mutex<EwmaState>>>,
+    index: HashMap<u64, usize>,
+    decay: Duration,
+}
+
+impl PeakEwmaPicker {
+    fn cost(&self, i: usize) -> f64 {
+        let rtt = self.states[i].lock().rtt_ns;
+        let in_flight = self.nodes[i].in_flight.load(Ordering::Acquire);
+        rtt * (in_flight as f64 + 1.0)
+    }
+}
+
+impl Picker for PeakEwmaPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let Some((a, b)) = sample_two(&self.nodes, req, &mut rand::thread_rng())? else {
+            return Ok(self.nodes[first_allowed(&self.nodes, req)].clone());
+        };
+
+        Ok(if self.cost(a) <= self.cost(b) {
+            self.nodes[a].clone()
+        } else {
+            self.nodes[b].clone()
+        })
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        let rtt_ns = rtt.as_nanos().min(u64::MAX as u128) as u64;
+        if let Some(&i) = self.index.get(&node.endpoint.id) {
+            self.states[i]
+                .lock()
+                .observe(rtt_ns as f64, self.decay, Instant::now());
+        }
+    }
+}
+
+/// Gradient descent on mean latency Strategy
+///
+/// Features:
+/// - Keeps a routing weight per node, starting at 1.0, and samples nodes in proportion
+///   to it; `Node::weight` is ignored
+/// - Every RTT reported through `Picker::on_response` updates the node's latency EWMA
+/// - At most once per `update_interval`, takes one step on the expected latency
+///   `L = sum(p_i * L_i)` with `p_i = w_i / sum(w)`: its gradient along `w_i` is
+///   proportional to `L_i - L`, so `w_i := w_i - learning_rate * (L_i - L) / L`
+/// - Weights are clamped to `MIN_WEIGHT..=1.0`, so slow nodes keep a trickle of traffic
+///   and their recovery is noticed; nodes without samples keep their weight
+/// - Weights and EWMAs are keyed by `endpoint.id` and survive picker rebuilds, including
+///   builds over a subset of the nodes; `BalanceStrategy::prune` drops removed nodes
+/// - Defaults: `learning_rate` 0.1, `update_interval` 100ms
+#[derive(Clone)]
+pub struct GradientDescent {
+    pub learning_rate: f64,
+    pub update_interval: Duration,
+    states: Arc<parking_lot::Mutex<HashMap<u64, Arc<GradientState>>>>,
+    // Time of the last step, shared by every picker of the strategy
+    last_step: Arc<parking_lot::Mutex<Option<Instant>>>,
+}
+
+impl Default for GradientDescent {
+    fn default() -> Self {
+        Self::new(0.1, Duration::from_millis(100))
+    }
+}
+
+impl GradientDescent {
+    /// Smallest routing weight, relative to the largest possible weight of 1.0
+    pub const MIN_WEIGHT: f64 = 0.01;
+    // Smoothing factor of the per-node latency EWMA
+    const LATENCY_ALPHA: f64 = 0.3;
+
+    pub fn new(learning_rate: f64, update_interval: Duration) -> Self {
+        Self {
+            learning_rate,
+            update_interval,
+            states: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            last_step: Arc::new(parking_lot::Mutex::new(None)),
+        }
+    }
+
+    /// Current routing weight of a node, `None` if the node has never been seen.
+    pub fn weight(&self, node_id: u64) -> Option<f64> {
+        self.states.lock().get(&node_id).map(|s| s.weight.load())
+    }
+}
+
+// f64 stored as its bits, for lock-free reads on the pick path
+struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    fn new(value: f64) -> Self {
+        Self(AtomicU64::new(value.to_bits()))
+    }
+
+    fn load(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Acquire))
+    }
+
+    fn store(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Release);
+    }
+
+    // Atomic read-modify-write, retried on concurrent updates
+    fn update(&self, f: impl Fn(f64) -> f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+                Some(f(f64::from_bits(bits)).to_bits())
+            });
+    }
+}
+
+struct GradientState {
+    weight: AtomicF64,
+    // Latency EWMA in nanoseconds, 0 before the first sample
+    latency_ns: AtomicF64,
+}
+
+impl BalanceStrategy for GradientDescent {
+    fn name(&self) -> Cow<'static, str> {
+        "gradient_descent".into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.learning_rate.to_bits().hash(&mut h);
+        self.update_interval.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.states
+            .lock()
+            .retain(|id, _| live_ids.binary_search(id).is_ok());
+    }
+
+    fn detach_state(&mut self) {
+        self.states = Default::default();
+        self.last_step = Default::default();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let states = {
+            let mut states = self.states.lock();
+            nodes
+                .iter()
+                .map(|node| {
+                    states
+                        .entry(node.endpoint.id)
+                        .or_insert_with(|| {
+                            Arc::new(GradientState {
+                                weight: AtomicF64::new(1.0),
+                                latency_ns: AtomicF64::new(0.0),
+                            })
+                        })
+                        .clone()
+                })
+                .collect()
+        };
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.endpoint.id, i))
+            .collect();
+
+        Ok(Arc::new(GradientDescentPicker {
+            nodes,
+            states,
+            index,
+            learning_rate: if self.learning_rate.is_finite() {
+                self.learning_rate.max(0.0)
+            } else {
+                0.0
+            },
+            update_interval: self.update_interval,
+            last_step: self.last_step.clone(),
+        }))
+    }
+}
+
+struct GradientDescentPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Aligned with `nodes`
+    states: Vec<Arc<GradientState>>,
+    index: HashMap<u64, usize>,
+    learning_rate: f64,
+    update_interval: Duration,
+    last_step: Arc<parking_lot::Mutex<Option<Instant>>>,
+}
+
+impl GradientDescentPicker {
+    // One descent step over the nodes with latency samples
+    fn step(&self) {
+        let sampled: SmallVec<[(&GradientState, f64, f64); 16]> = self
+            .states
+            .iter()
+            .map(|s| (&**s, s.weight.load(), s.latency_ns.load()))
+            .filter(|&(_, _, latency)| latency > 0.0)
+            .collect();
+        let total_weight: f64 = sampled.iter().map(|&(_, w, _)| w).sum();
+        if sampled.len() < 2 || total_weight <= 0.0 {
+            return;
+        }
+        let mean = sampled.iter().map(|&(_, w, l)| w * l).sum::<f64>() / total_weight;
+        if mean <= 0.0 {
+            return;
+        }
+        for (state, weight, latency) in sampled {
+            let gradient = (latency - mean) / mean;
+            let next = weight - self.learning_rate * gradient;
+            state
+                .weight
+                .store(next.clamp(GradientDescent::MIN_WEIGHT, 1.0));
+        }
+    }
+}
+
+impl Picker for GradientDescentPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let total: f64 = self
+            .nodes
+            .iter()
+            .zip(&self.states)
+            .filter(|(n, _)| !req.is_excluded(n))
+            .map(|(_, s)| s.weight.load())
+            .sum();
+        if total <= 0.0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        let mut point = rand::thread_rng().gen::<f64>() * total;
+        let mut last = None;
+        for (node, state) in self.nodes.iter().zip(&self.states) {
+            if req.is_excluded(node) {
+                continue;
+            }
+            let w = state.weight.load();
+            if point < w {
+                return Ok(node.clone());
+            }
+            point -= w;
+            last = Some(node);
+        }
+        // Rounding left the point past the end
+        last.cloned().ok_or(LoadBalanceError::NoAvailableNodes)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        let rtt_ns = rtt.as_nanos().min(u64::MAX as u128) as u64;
+        let Some(&i) = self.index.get(&node.endpoint.id) else {
+            return;
+        };
+        let sample = rtt_ns.max(1) as f64;
+        self.states[i].latency_ns.update(|ewma| {
+            if ewma > 0.0 {
+                ewma + GradientDescent::LATENCY_ALPHA * (sample - ewma)
+            } else {
+                sample
+            }
+        });
+
+        // Whoever finds the interval elapsed takes the step, concurrent reporters skip it
+        let Some(mut last_step) = self.last_step.try_lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if last_step.is_some_and(|at| now.saturating_duration_since(at) < self.update_interval) {
+            return;
+        }
+        *last_step = Some(now);
+        self.step();
+    }
+}
+
+/// Hash function used by `ConsistentHash` for both ring construction and key lookup.
+///
+/// Virtual node keys are passed as UTF-8 bytes, request hash keys as the
+/// little-endian bytes of the `u64`. A non-zero `ConsistentHash::seed` is prepended
+/// to both as little-endian bytes.
+pub type HashFn = Arc<dyn Fn(&[u8]) -> u64 + Send + Sync>;
+
+/// How `ConsistentHash` turns node weights into virtual nodes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VirtualNodePolicy {
+    /// `virtual_factor * weight / gcd(weights)` virtual nodes per node. If that exceeds
+    /// the per-node cap, all counts are scaled down together so weight ratios are kept.
+    #[default]
+    Proportional,
+    /// Exactly `virtual_factor` virtual nodes per node, ignoring weights.
+    Fixed,
+    /// `virtual_factor * nodes` virtual nodes in total, shared in proportion to the
+    /// weights (largest remainder, at least one per node). Uses `Node::fractional_weight`
+    /// where set, so ratios finer than integer weights need no larger `virtual_factor`.
+    Normalized,
+}
+
+// Hard cap on virtual nodes per node to keep ring size reasonable while preserving
+// relative weights
+const MAX_VNODE_PER_NODE: usize = 1024;
+
+// Consistent Hash
+pub struct ConsistentHash {
+    // Virtual node multiplier, number of virtual nodes corresponding to each real node.
+    // Must be at least 1, `build_picker` fails with `ZeroVirtualFactor` otherwise
+    pub virtual_factor: usize,
+    // Hash function for ring points and request keys, AHash by default
+    pub hasher: HashFn,
+    // Whether weights scale the number of virtual nodes
+    pub policy: VirtualNodePolicy,
+    // Remember the ring position of each hash key (up to `EXACT_KEY_CACHE_CAPACITY` keys
+    // per picker), for small and stable key sets such as shard ids
+    pub cache_exact_keys: bool,
+    // Mixed into every virtual node and request key hash. Rings built from the same nodes
+    // with the same seed agree, other seeds reshuffle placement. 0 hashes keys unchanged
+    pub seed: u64,
+    // Largest acceptable key imbalance; if set, `virtual_factor` is ignored and derived
+    // from the node count at build time, see `auto_virtual_factor`
+    pub target_imbalance: Option<f64>,
+}
+
+impl Default for ConsistentHash {
+    fn default() -> Self {
+        Self {
+            virtual_factor: 10,
+            hasher: Arc::new(hash_bytes),
+            policy: VirtualNodePolicy::default(),
+            cache_exact_keys: false,
+            seed: 0,
+            target_imbalance: None,
+        }
+    }
+}
+
+impl ConsistentHash {
+    /// Keys remembered per picker when `cache_exact_keys` is set; later keys use the ring.
+    pub const EXACT_KEY_CACHE_CAPACITY: usize = 4096;
+
+    /// Use `weights` instead of the nodes' own weights, see `StaticWeightSnapshot`.
+    pub fn with_weights(self, weights: Vec<u32>) -> StaticWeightSnapshot<Self> {
+        StaticWeightSnapshot::new(self, weights)
+    }
+
+    /// Ring whose `virtual_factor` is chosen at build time so that no node is expected
+    /// to receive more than `1 + target_imbalance` times its fair share of keys, e.g. 0.1
+    /// for at most 10% above the mean. See `virtual_factor_for`.
+    pub fn auto_virtual_factor(target_imbalance: f64) -> Self {
+        Self {
+            target_imbalance: Some(target_imbalance),
+            ..Default::default()
+        }
+    }
+
+    /// Virtual nodes per unit of weight for `node_count` nodes and `target_imbalance`.
+    ///
+    /// A node with `v` virtual nodes owns a share of the ring whose relative deviation
+    /// from the mean is about `1 / sqrt(v)`; the largest of `n` such deviations is about
+    /// `sqrt(2 ln n / v)`. That is only the typical maximum, so the factor is doubled for
+    /// headroom: `v = 4 ln n / target²`, and halving the imbalance quadruples the ring. The result is at least 1 and at most
+    /// the per-node cap of 1024, which also applies to targets that are not positive.
+    pub fn virtual_factor_for(node_count: usize, target_imbalance: f64) -> usize {
+        if target_imbalance.is_nan() || target_imbalance <= 0.0 {
+            return MAX_VNODE_PER_NODE;
+        }
+        // ln 2 for a single node, so one node still gets a handful of virtual nodes
+        let ln_n = (node_count.max(2) as f64).ln();
+        let factor = (4.0 * ln_n / (target_imbalance * target_imbalance)).ceil();
+        (factor as usize).clamp(1, MAX_VNODE_PER_NODE)
+    }
+
+    /// Replace the hash function, e.g. to match the key placement of an existing
+    /// deployment that uses CRC32 or MD5.
+    pub fn with_hasher<F>(mut self, hasher: F) -> Self
+    where
+        F: Fn(&[u8]) -> u64 + Send + Sync + 'static,
+    {
+        self.hasher = Arc::new(hasher);
+        self
+    }
+
+    /// Pin the ring placement with `seed`, see the `seed` field.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl BalanceStrategy for ConsistentHash {
+    fn name(&self) -> Cow<'static, str> {
+        "consistent_hash".into()
+    }
+
+    /// Covers every field except `hasher`, which cannot be compared.
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        h.write_usize(self.virtual_factor);
+        h.write_u8(self.policy as u8);
+        h.write_u8(self.cache_exact_keys as u8);
+        h.write_u64(self.seed);
+        match self.target_imbalance {
+            Some(target) => {
+                h.write_u8(1);
+                h.write_u64(target.to_bits());
+            }
+            None => h.write_u8(0),
+        }
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let weights = node_weights(&nodes);
+        self.build_picker_with_weights(nodes, &weights)
+    }
+
+    fn build_picker_with_weights(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+        weights: &[u32],
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let virtual_factor = match self.target_imbalance {
+            Some(target) => Self::virtual_factor_for(nodes.len(), target),
+            None => self.virtual_factor,
+        };
+        // A ring without virtual nodes has no placement to fall back on that agrees
+        // with the ring, so reject it instead of silently remapping every key
+        if virtual_factor == 0 {
+            return Err(LoadBalanceError::ZeroVirtualFactor);
+        }
+        Ok(Arc::new(ConsistentHashPicker::new(
+            nodes,
+            weights,
+            virtual_factor,
+            self.policy,
+            self.hasher.clone(),
+            self.seed,
+            self.cache_exact_keys,
+        )))
+    }
+}
+
+struct ConsistentHashPicker {
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Hash ring: (hash value, node index)
+    ring: Vec<(u64, usize)>,
+    hasher: HashFn,
+    seed: u64,
+    // Request hash key -> ring position, filled on first lookup
+    exact_keys: Option<RwLock<HashMap<u64, usize>>>,
+}
+
+impl ConsistentHashPicker {
+    fn new(
+        nodes: Arc<Vec<Arc<Node>>>,
+        node_weights: &[u32],
+        virtual_factor: usize,
+        policy: VirtualNodePolicy,
+        hasher: HashFn,
+        seed: u64,
+        cache_exact_keys: bool,
+    ) -> Self {
+        let mut ring = Vec::new();
+
+        let counts: Vec<usize> = match policy {
+            VirtualNodePolicy::Fixed => {
+                vec![virtual_factor.clamp(1, MAX_VNODE_PER_NODE); nodes.len()]
+            }
+            VirtualNodePolicy::Proportional => {
+                // Normalize weights to avoid exploding virtual nodes when weights are large.
+                let weights: Vec<usize> = node_weights.iter().map(|&w| w.max(1) as usize).collect();
+                let gcd_w = weights
+                    .iter()
+                    .copied()
+                    .fold(
+                        0usize,
+                        |acc, w| if acc == 0 { w } else { gcd_usize(acc, w) },
+                    )
+                    .max(1);
+                let wanted: Vec<usize> = weights
+                    .iter()
+                    .map(|w| (w / gcd_w).saturating_mul(virtual_factor))
+                    .collect();
+
+                // Scale every node by the same factor instead of clipping only the heavy
+                // ones, which would flatten their share of the ring
+                let max_wanted = wanted.iter().copied().max().unwrap_or(0);
+                let scale = if max_wanted > MAX_VNODE_PER_NODE {
+                    MAX_VNODE_PER_NODE as f64 / max_wanted as f64
+                } else {
+                    1.0
+                };
+                wanted
+                    .iter()
+                    .map(|&c| ((c as f64 * scale).round() as usize).clamp(1, MAX_VNODE_PER_NODE))
+                    .collect()
+            }
+            VirtualNodePolicy::Normalized => {
+                let weights: Vec<f64> = nodes
+                    .iter()
+                    .zip(node_weights)
+                    .map(|(n, &w)| n.fractional_weight.unwrap_or(w as f64))
+                    .collect();
+                normalized_vnode_counts(&weights, virtual_factor.saturating_mul(nodes.len()))
+            }
+        };
+
+        // Create virtual nodes for each node
+        for (i, node) in nodes.iter().enumerate() {
+            let vnode_count = counts[i];
+            let base_key = stable_node_key(node, i);
+
+            for j in 0..vnode_count {
+                // Generate hash value using node address and virtual node index
+                let key = format!("{base_key}#{j}");
+                let hash = seeded_hash(&hasher, seed, key.as_bytes());
+                ring.push((hash, i));
+            }
+        }
+
+        // Sort by hash value
+        ring.sort_by_key(|&(hash, _)| hash);
+
+        Self {
+            nodes,
+            ring,
+            hasher,
+            seed,
+            exact_keys: cache_exact_keys.then(|| RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn hash_key(&self, key: u64) -> u64 {
+        seeded_hash(&self.hasher, self.seed, &key.to_le_bytes())
+    }
+
+    // Ring position of the first virtual node clockwise from `key`
+    fn ring_position(&self, key: u64) -> usize {
+        let Some(cache) = &self.exact_keys else {
+            return self.search_ring(key);
+        };
+        if let Some(&pos) = cache.read().get(&key) {
+            return pos;
+        }
+
+        let pos = self.search_ring(key);
+        let mut cache = cache.write();
+        if cache.len() < ConsistentHash::EXACT_KEY_CACHE_CAPACITY {
+            cache.insert(key, pos);
+        }
+        pos
+    }
+
+    fn search_ring(&self, key: u64) -> usize {
+        let hash = self.hash_key(key);
+
+        // Binary search to find the first position greater than or equal to hash
+        match self.ring.binary_search_by(|&(h, _)| h.cmp(&hash)) {
+            // Found exact match
+            Ok(idx) => idx,
+            // No exact match found, take the next node (ring)
+            Err(idx) => {
+                if idx >= self.ring.len() {
+                    0
+                } else {
+                    idx
+                }
+            }
+        }
+    }
+}
+
+impl Picker for ConsistentHashPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.pick_index(req).map(|i| self.nodes[i].clone())
+    }
+
+    fn pick_index(&self, req: &RequestMetadata) -> Result<usize, LoadBalanceError> {
+        let len = self.nodes.len();
+        if len == 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        // Every node owns at least one virtual node, so the ring is never empty here
+        let key = req.hash_key.ok_or(LoadBalanceError::MissingHashKey)?;
+        let start = self.ring_position(key);
+
+        // Keep walking clockwise past virtual nodes of excluded nodes
+        let ring_len = self.ring.len();
+        (0..ring_len)
+            .map(|k| self.ring[(start + k) % ring_len].1)
+            .find(|&i| !req.is_excluded(&self.nodes[i]))
+            .ok_or(LoadBalanceError::NoAvailableNodes)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+}
+
+/// Node health classification shared by the failover strategies
+///
+/// A node is unhealthy once it has at least `min_requests` completed requests and an
+/// `error_rate` above `max_error_rate`, or while `in_flight` has reached a non-zero
+/// `max_in_flight`. The scan-based pickers (`LeastConnection`, `LeastConnectionWeighted`,
+/// `LeastLoad`, `ResponseTimeWeighted`) rank healthy nodes before unhealthy ones with the
+/// default filter; `FaultTolerant` applies a filter to any other strategy.
+#[derive(Clone, Copy, Debug)]
+pub struct HealthFilter {
+    pub max_error_rate: f64,
+    pub min_requests: u64,
+}
+
+impl Default for HealthFilter {
+    fn default() -> Self {
+        Self {
+            max_error_rate: 0.5,
+            min_requests: 10,
+        }
+    }
+}
+
+impl HealthFilter {
+    pub fn is_healthy(&self, node: &Node) -> bool {
+        let trusted = node.total_requests() >= self.min_requests;
+        if trusted && node.error_rate() > self.max_error_rate {
+            return false;
+        }
+        node.max_in_flight == 0
+            || node.in_flight.load(Ordering::Acquire) < node.max_in_flight as usize
+    }
+
+    // Mix the thresholds into a `config_hash`
+    fn hash_config(&self, h: &mut impl Hasher) {
+        self.max_error_rate.to_bits().hash(h);
+        self.min_requests.hash(h);
+    }
+
+    // `key` of `node` for ascending ranks, with unhealthy nodes after every healthy one
+    fn rank<K>(&self, node: &Node, key: K) -> (bool, K) {
+        (!self.is_healthy(node), key)
+    }
+
+    /// `req` with every unhealthy node of `nodes` excluded as well.
+    ///
+    /// `None` if that would leave no node to pick, i.e. only best effort is possible.
+    /// Borrows `req` unchanged if all allowed nodes are healthy.
+    pub fn exclude_unhealthy<'a>(
+        &self,
+        nodes: &[Arc<Node>],
+        req: &'a RequestMetadata,
+    ) -> Option<Cow<'a, RequestMetadata>> {
+        let mut filtered: Option<RequestMetadata> = None;
+        let mut healthy = false;
+        for node in nodes.iter().filter(|n| !req.is_excluded(n)) {
+            if self.is_healthy(node) {
+                healthy = true;
+            } else {
+                filtered
+                    .get_or_insert_with(|| req.clone())
+                    .exclude_ids
+                    .push(node.endpoint.id);
+            }
+        }
+        if !healthy {
+            return None;
+        }
+        Some(filtered.map_or(Cow::Borrowed(req), Cow::Owned))
+    }
+}
+
+/// Fault tolerant Strategy Wrapper
+///
+/// Features:
+/// - The inner strategy only sees the nodes `health` classifies as healthy, so no
+///   strategy returns an unhealthy node while a healthy one is left
+/// - If every node is unhealthy, the inner strategy picks from all of them (best effort)
+/// - Health is read from the node counters on every pick, an O(n) scan
+pub struct FaultTolerant<S: BalanceStrategy> {
+    inner: S,
+    health: HealthFilter,
+}
+
+impl<S: BalanceStrategy> FaultTolerant<S> {
+    pub fn new(inner: S) -> Self {
+        Self::with_health(inner, HealthFilter::default())
+    }
+
+    pub fn with_health(inner: S, health: HealthFilter) -> Self {
+        Self { inner, health }
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for FaultTolerant<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("fault_tolerant({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.health.hash_config(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(FaultTolerantPicker {
+            inner: self.inner.build_picker(nodes.clone())?,
+            nodes,
+            health: self.health,
+        }))
+    }
+}
+
+struct FaultTolerantPicker {
+    inner: Arc<dyn Picker>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    health: HealthFilter,
+}
+
+impl Picker for FaultTolerantPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        match self.health.exclude_unhealthy(&self.nodes, req) {
+            Some(healthy) => self.inner.pick(&healthy),
+            None => self.inner.pick(req),
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+/// Random subset Strategy Wrapper
+///
+/// Features:
+/// - Every `build_picker` samples `k` nodes uniformly at random (O(k)) and builds the
+///   inner strategy over just those, so per-pick scans stay O(k) for pools of thousands
+/// - Rebuild the picker (e.g. per request batch) to spread load across the whole pool
+/// - Pools of at most `k` nodes are used as is; `k` is at least 1
+/// - Picks fail with `NoAvailableNodes` if the request excludes every sampled node
+pub struct RandomSubset<S: BalanceStrategy> {
+    k: usize,
+    inner: S,
+}
+
+impl<S: BalanceStrategy> RandomSubset<S> {
+    pub fn new(k: usize, inner: S) -> Self {
+        Self { k: k.max(1), inner }
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for RandomSubset<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("random_subset({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.k.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        if nodes.len() <= self.k {
+            return self.inner.build_picker(nodes);
+        }
+        let subset = nodes
+            .choose_multiple(&mut rand::thread_rng(), self.k)
+            .cloned()
+            .collect();
+        self.inner.build_picker(Arc::new(subset))
+    }
+}
+
+/// Sticky consistent hash with failover Strategy Wrapper
+///
+/// Features:
+/// - Keys stick to their `ConsistentHash` ring node while it is healthy
+/// - An unhealthy node's keys go to the next healthy node clockwise on the ring, the
+///   same one for every pick of the key
+/// - Stateless: health is read from the node counters on every pick, so keys return to
+///   their ring node as soon as it counts as healthy again
+/// - Health as classified by `HealthFilter`, with its default thresholds unless set with
+///   `with_thresholds`
+/// - If every candidate is unhealthy, the ring node is returned anyway
+pub struct StickyFailover {
+    inner: ConsistentHash,
+    health: HealthFilter,
+}
+
+impl StickyFailover {
+    pub fn new(inner: ConsistentHash) -> Self {
+        Self {
+            inner,
+            health: HealthFilter::default(),
+        }
+    }
+
+    pub fn with_thresholds(mut self, max_error_rate: f64, min_requests: u64) -> Self {
+        self.health = HealthFilter {
+            max_error_rate,
+            min_requests,
+        };
+        self
+    }
+}
+
+impl BalanceStrategy for StickyFailover {
+    fn name(&self) -> Cow<'static, str> {
+        format!("sticky_failover({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.health.hash_config(&mut h);
+        h.finish()
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(StickyFailoverPicker {
+            ring: self.inner.build_picker(nodes)?,
+            health: self.health,
+        }))
+    }
+}
+
+struct StickyFailoverPicker {
+    ring: Arc<dyn Picker>,
+    health: HealthFilter,
+}
+
+impl Picker for StickyFailoverPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let sticky = self.ring.pick(req)?;
+        if self.health.is_healthy(&sticky) {
+            return Ok(sticky);
+        }
+
+        // Excluding each unhealthy node walks the ring clockwise, deterministically
+        let mut walk = req.clone().exclude(sticky.endpoint.id);
+        while let Ok(node) = self.ring.pick(&walk) {
+            if self.health.is_healthy(&node) {
+                return Ok(node);
+            }
+            walk.exclude_ids.push(node.endpoint.id);
+        }
+        Ok(sticky)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.ring.nodes()
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.ring.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.ring.update_estimates(node, rtt);
+    }
+}
+
+/// In-flight capacity limiting Strategy Wrapper
+///
+/// Features:
+/// - Nodes with `max_in_flight` or more requests in flight are excluded before `inner`
+///   picks, so load is shed to nodes with spare capacity
+/// - Fails with `Overloaded` when every allowed node is at the cap, instead of piling
+///   more requests onto saturated nodes
+/// - Without a cap (`from_config` with `max_in_flight_per_node: None`) picks pass through
+pub struct CapacityLimited<S: BalanceStrategy> {
+    inner: S,
+    max_in_flight: Option<usize>,
+}
+
+impl<S: BalanceStrategy> CapacityLimited<S> {
+    pub fn new(inner: S, max_in_flight: usize) -> Self {
+        Self {
+            inner,
+            max_in_flight: Some(max_in_flight),
+        }
+    }
+
+    /// Cap taken from `config.max_in_flight_per_node`.
+    pub fn from_config(inner: S, config: &BalanceConfig) -> Self {
+        Self {
+            inner,
+            max_in_flight: config.max_in_flight_per_node,
+        }
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for CapacityLimited<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("capacity_limited({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.max_in_flight.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let inner = self.inner.build_picker(nodes.clone())?;
+        let Some(max_in_flight) = self.max_in_flight else {
+            return Ok(inner);
+        };
+        Ok(Arc::new(CapacityLimitedPicker {
+            inner,
+            nodes,
+            max_in_flight,
+        }))
+    }
+}
+
+struct CapacityLimitedPicker {
+    inner: Arc<dyn Picker>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    max_in_flight: usize,
+}
+
+impl Picker for CapacityLimitedPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let mut filtered: Option<RequestMetadata> = None;
+        let mut available = false;
+        for node in self.nodes.iter().filter(|n| !req.is_excluded(n)) {
+            if node.in_flight.load(Ordering::Acquire) < self.max_in_flight {
+                available = true;
+            } else {
+                filtered
+                    .get_or_insert_with(|| req.clone())
+                    .exclude_ids
+                    .push(node.endpoint.id);
+            }
+        }
+        if !available {
+            return Err(if filtered.is_some() {
+                LoadBalanceError::Overloaded
+            } else {
+                LoadBalanceError::NoAvailableNodes
+            });
+        }
+        self.inner.pick(filtered.as_ref().unwrap_or(req))
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+/// Hard per-node concurrency Strategy Wrapper
+///
+/// Features:
+/// - `ConcurrencyLimitedPicker::pick_acquire` picks with `inner`, then claims a slot with
+///   `Node::try_acquire`; a node at its `max_concurrency` is excluded and `inner` picks
+///   again, until a slot is claimed or no node is left
+/// - Fails with `Overloaded` if every allowed node rejected the request
+/// - Plain `pick` skips nodes at their cap the same way but claims nothing, for callers
+///   that track `in_flight` themselves
+/// - Nodes without `max_concurrency` always accept
+pub struct ConcurrencyLimited<S: BalanceStrategy> {
+    inner: S,
+}
+
+impl<S: BalanceStrategy> ConcurrencyLimited<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    pub fn build_limited_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<ConcurrencyLimitedPicker, LoadBalanceError> {
+        Ok(ConcurrencyLimitedPicker {
+            inner: self.inner.build_picker(nodes.clone())?,
+            nodes,
+        })
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for ConcurrencyLimited<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("concurrency_limited({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(self.build_limited_picker(nodes)?))
+    }
+}
+
+pub struct ConcurrencyLimitedPicker {
+    inner: Arc<dyn Picker>,
+    nodes: Arc<Vec<Arc<Node>>>,
+}
+
+impl ConcurrencyLimitedPicker {
+    /// Pick a node and claim one of its `max_concurrency` slots, released when the
+    /// returned guard is dropped.
+    pub fn pick_acquire(&self, req: &RequestMetadata) -> Result<InFlightGuard, LoadBalanceError> {
+        self.resample(req, |node| node.try_acquire())
+    }
+
+    // Pick with `inner` until `accept` takes a node, excluding every node it rejects
+    fn resample<T>(
+        &self,
+        req: &RequestMetadata,
+        accept: impl Fn(&Arc<Node>) -> Option<T>,
+    ) -> Result<T, LoadBalanceError> {
+        let mut filtered: Option<RequestMetadata> = None;
+        // Every rejection excludes one more node, so this ends within one pass
+        for _ in 0..=self.nodes.len() {
+            let node = match self.inner.pick(filtered.as_ref().unwrap_or(req)) {
+                Err(LoadBalanceError::NoAvailableNodes) if filtered.is_some() => {
+                    return Err(LoadBalanceError::Overloaded);
+                }
+                res => res?,
+            };
+            if let Some(accepted) = accept(&node) {
+                return Ok(accepted);
+            }
+            filtered
+                .get_or_insert_with(|| req.clone())
+                .exclude_ids
+                .push(node.endpoint.id);
+        }
+        Err(LoadBalanceError::Overloaded)
+    }
+}
+
+impl Picker for ConcurrencyLimitedPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.resample(req, |node| {
+            let below_cap = node
+                .max_concurrency
+                .is_none_or(|max| node.in_flight.load(Ordering::Acquire) < max);
+            below_cap.then(|| node.clone())
+        })
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+/// Session sticky Strategy Wrapper
+///
+/// Features:
+/// - Remembers the node each `hash_key` was last routed to, in a table shared by every
+///   picker the strategy builds
+/// - A remembered node is returned while it is still in the picker's node list, healthy
+///   per `HealthFilter` and not excluded by the request
+/// - Otherwise `inner` picks and the table is updated, so the session moves with it
+/// - Entries unused for `ttl` expire; requests without a hash key bypass the table
+pub struct Sticky<S: BalanceStrategy> {
+    inner: S,
+    ttl: Duration,
+    health: HealthFilter,
+    sessions: Arc<RwLock<SessionTable>>,
+}
+
+impl<S: BalanceStrategy> Sticky<S> {
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(600);
+
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            ttl: Self::DEFAULT_TTL,
+            health: HealthFilter::default(),
+            sessions: Arc::new(RwLock::new(SessionTable::new())),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_health(mut self, health: HealthFilter) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Number of remembered sessions, expired ones included until they are swept.
+    pub fn sessions(&self) -> usize {
+        self.sessions.read().entries.len()
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for Sticky<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("sticky({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.ttl.hash(&mut h);
+        self.health.hash_config(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.sessions = Arc::new(RwLock::new(SessionTable::new()));
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.endpoint.id, i))
+            .collect();
+        Ok(Arc::new(StickyPicker {
+            inner: self.inner.build_picker(nodes.clone())?,
+            nodes,
+            index,
+            ttl_ms: self.ttl.as_millis().min(u64::MAX as u128) as u64,
+            health: self.health,
+            sessions: self.sessions.clone(),
+        }))
+    }
+}
+
+struct SessionTable {
+    // Session key -> remembered node
+    entries: HashMap<u64, SessionEntry>,
+    // Timestamps are milliseconds since `epoch`
+    epoch: Instant,
+    swept_at_ms: u64,
+}
+
+impl SessionTable {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            epoch: Instant::now(),
+            swept_at_ms: 0,
+        }
+    }
+}
+
+struct SessionEntry {
+    node_id: u64,
+    // Bumped under the read lock on every hit
+    last_used_ms: AtomicU64,
+}
+
+struct StickyPicker {
+    inner: Arc<dyn Picker>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    // Node id -> position in `nodes`
+    index: HashMap<u64, usize>,
+    ttl_ms: u64,
+    health: HealthFilter,
+    sessions: Arc<RwLock<SessionTable>>,
+}
+
+impl StickyPicker {
+    fn remembered(&self, key: u64, req: &RequestMetadata) -> Option<Arc<Node>> {
+        let sessions = self.sessions.read();
+        let entry = sessions.entries.get(&key)?;
+        let now_ms = sessions.epoch.elapsed().as_millis() as u64;
+        if now_ms.saturating_sub(entry.last_used_ms.load(Ordering::Relaxed)) >= self.ttl_ms {
+            return None;
+        }
+        let node = &self.nodes[*self.index.get(&entry.node_id)?];
+        if req.is_excluded(node) || !self.health.is_healthy(node) {
+            return None;
+        }
+        entry.last_used_ms.store(now_ms, Ordering::Relaxed);
+        Some(node.clone())
+    }
+
+    fn remember(&self, key: u64, node: &Node) {
+        let mut sessions = self.sessions.write();
+        let now_ms = sessions.epoch.elapsed().as_millis() as u64;
+        // Drop expired sessions at most once per TTL
+        if now_ms.saturating_sub(sessions.swept_at_ms) >= self.ttl_ms {
+            let ttl_ms = self.ttl_ms;
+            sessions.entries.retain(|_, e| {
+                now_ms.saturating_sub(e.last_used_ms.load(Ordering::Relaxed)) < ttl_ms
+            });
+            sessions.swept_at_ms = now_ms;
+        }
+        sessions.entries.insert(
+            key,
+            SessionEntry {
+                node_id: node.endpoint.id,
+                last_used_ms: AtomicU64::new(now_ms),
+            },
+        );
+    }
+}
+
+impl Picker for StickyPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let Some(key) = req.hash_key else {
+            return self.inner.pick(req);
+        };
+        if let Some(node) = self.remembered(key, req) {
+            return Ok(node);
+        }
+        let node = self.inner.pick(req)?;
+        self.remember(key, &node);
+        Ok(node)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+/// Deadline aware Strategy Wrapper
+///
+/// Features:
+/// - Requests with less than `threshold` left until `req.deadline` (or already past it)
+///   go to the node with the best `ResponseTimeWeighted` score: low RTT and load
+/// - Requests with more time left, or without a deadline, are delegated to `inner`,
+///   which spreads the load as usual
+pub struct DeadlineAware<S: BalanceStrategy> {
+    inner: S,
+    threshold: Duration,
+}
+
+impl<S: BalanceStrategy> DeadlineAware<S> {
+    pub fn new(inner: S, threshold: Duration) -> Self {
+        Self { inner, threshold }
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for DeadlineAware<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("deadline_aware({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.threshold.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(DeadlineAwarePicker {
+            inner: self.inner.build_picker(nodes.clone())?,
+            fastest: RTWeightedPicker {
+                nodes,
+                health: HealthFilter::default(),
+            },
+            threshold: self.threshold,
+        }))
+    }
+}
+
+struct DeadlineAwarePicker {
+    inner: Arc<dyn Picker>,
+    fastest: RTWeightedPicker,
+    threshold: Duration,
+}
+
+impl DeadlineAwarePicker {
+    fn is_tight(&self, req: &RequestMetadata) -> bool {
+        req.deadline
+            .is_some_and(|d| d.saturating_duration_since(Instant::now()) < self.threshold)
+    }
+}
+
+impl Picker for DeadlineAwarePicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        if self.is_tight(req) {
+            self.fastest.pick(req)
+        } else {
+            self.inner.pick(req)
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.inner.nodes()
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        if self.is_tight(req) {
+            self.fastest.pick_batch_distinct(req, n)
+        } else {
+            self.inner.pick_batch_distinct(req, n)
+        }
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+/// Result of a shadow pick
+///
+/// `primary` serves the request; `shadow` (if any) is where the caller may mirror
+/// the request asynchronously. Its response must never affect the caller.
+#[derive(Clone, Debug)]
+pub struct ShadowPickResult {
+    pub primary: Arc<Node>,
+    pub shadow: Option<Arc<Node>>,
+}
+
+/// Shadow (request mirroring) Strategy
+///
+/// Features:
+/// - Picks the primary node with strategy `P`
+/// - Picks a shadow node with strategy `S`, distinct from the primary
+/// - The shadow pool defaults to the primary pool, or can be a dedicated set of nodes
+/// - Shadow selection never blocks or fails the primary pick
+pub struct Shadow<P: BalanceStrategy, S: BalanceStrategy> {
+    primary: P,
+    shadow: S,
+    shadow_nodes: Option<Arc<Vec<Arc<Node>>>>,
+}
+
+impl<P: BalanceStrategy, S: BalanceStrategy> Shadow<P, S> {
+    pub fn new(primary: P, shadow: S) -> Self {
+        Self {
+            primary,
+            shadow,
+            shadow_nodes: None,
+        }
+    }
+
+    /// Mirror traffic to a dedicated node pool instead of the primary pool.
+    pub fn with_shadow_nodes(mut self, nodes: Vec<Arc<Node>>) -> Self {
+        self.shadow_nodes = Some(Arc::new(nodes));
+        self
+    }
+
+    pub fn build_shadow_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<ShadowPicker, LoadBalanceError> {
+        let shadow_nodes = self.shadow_nodes.clone().unwrap_or_else(|| nodes.clone());
+        Ok(ShadowPicker {
+            primary: self.primary.build_picker(nodes)?,
+            shadow: self.shadow.build_picker(shadow_nodes.clone())?,
+            shadow_nodes,
+        })
+    }
+}
+
+impl<P: BalanceStrategy, S: BalanceStrategy> BalanceStrategy for Shadow<P, S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("shadow({}, {})", self.primary.name(), self.shadow.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.primary.config_hash().hash(&mut h);
+        self.shadow.config_hash().hash(&mut h);
+        // The shadow pool by node ids
+        self.shadow_nodes
+            .as_ref()
+            .map(|nodes| nodes.iter().map(|n| n.endpoint.id).collect::<Vec<_>>())
+            .hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.primary.prune(live_ids);
+        self.shadow.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.primary.detach_state();
+        self.shadow.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(self.build_shadow_picker(nodes)?))
+    }
+}
+
+pub struct ShadowPicker {
+    primary: Arc<dyn Picker>,
+    shadow: Arc<dyn Picker>,
+    shadow_nodes: Arc<Vec<Arc<Node>>>,
+}
+
+impl ShadowPicker {
+    pub fn pick_shadow(&self, req: &RequestMetadata) -> Result<ShadowPickResult, LoadBalanceError> {
+        let primary = self.primary.pick(req)?;
+        let shadow = self.pick_secondary(req, &primary);
+        Ok(ShadowPickResult { primary, shadow })
+    }
+
+    fn pick_secondary(&self, req: &RequestMetadata, primary: &Arc<Node>) -> Option<Arc<Node>> {
+        let primary_id = primary.endpoint.id;
+
+        // Give the shadow strategy a bounded number of tries to land on another node
+        for _ in 0..self.shadow_nodes.len() {
+            match self.shadow.pick(req) {
+                Ok(node) if node.endpoint.id != primary_id => return Some(node),
+                Ok(_) => continue,
+                Err(_) => break,
+            }
+        }
+
+        // Deterministic strategies (e.g. consistent hash) may keep returning the primary
+        self.shadow_nodes
+            .iter()
+            .find(|n| n.endpoint.id != primary_id && !req.is_excluded(n))
+            .cloned()
+    }
+}
+
+impl Picker for ShadowPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.primary.pick(req)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.primary.nodes()
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        // Only the primary strategy adapts to responses
+        self.primary.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.primary.update_estimates(node, rtt);
+    }
+}
+
+/// Circuit breaker state of a single node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Traffic flows normally
+    Closed,
+    /// Node is skipped until the cooldown elapses
+    Open,
+    /// Cooldown elapsed, a single probe request is allowed through
+    HalfOpen,
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that trip a node open
+    pub failure_threshold: u64,
+    /// How long a tripped node stays open before probing
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(10),
+        }
+    }
+}
+
+struct BreakerEntry {
+    last_success: u64,
+    last_fail: u64,
+    consecutive_failures: u64,
+    state: CircuitState,
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+impl BreakerEntry {
+    fn new(node: &Node) -> Self {
+        Self {
+            last_success: node.success.load(Ordering::Acquire),
+            last_fail: node.fail.load(Ordering::Acquire),
+            consecutive_failures: 0,
+            state: CircuitState::Closed,
+            open_until: None,
+            probing: false,
+        }
+    }
+
+    fn trip(&mut self, now: Instant, cooldown: Duration) {
+        self.state = CircuitState::Open;
+        self.open_until = Some(now + cooldown);
+        self.probing = false;
+    }
+
+    // Fold the counter deltas since the last observation into the breaker state
+    fn observe(&mut self, node: &Node, config: &CircuitBreakerConfig, now: Instant) {
+        // Common case between responses: nothing new to fold, only the cooldown can move
+        let (ds, df) = if node.total_requests() == self.last_success.saturating_add(self.last_fail)
+        {
+            (0, 0)
+        } else {
+            let success = node.success.load(Ordering::Acquire);
+            let fail = node.fail.load(Ordering::Acquire);
+            let deltas = (
+                success.saturating_sub(self.last_success),
+                fail.saturating_sub(self.last_fail),
+            );
+            self.last_success = success;
+            self.last_fail = fail;
+            deltas
+        };
+
+        // The relative order of successes and failures between two observations is
+        // unknown, so any success resets the streak
+        if ds > 0 {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures += df;
+        }
+
+        match self.state {
+            CircuitState::Closed => {
+                if self.consecutive_failures >= config.failure_threshold {
+                    self.trip(now, config.cooldown);
+                }
+            }
+            CircuitState::Open => {
+                if self.open_until.is_some_and(|until| now >= until) {
+                    self.state = CircuitState::HalfOpen;
+                    self.open_until = None;
+                    self.probing = false;
+                }
+            }
+            CircuitState::HalfOpen => {
+                if ds > 0 {
+                    self.state = CircuitState::Closed;
+                    self.probing = false;
+                } else if df > 0 {
+                    self.trip(now, config.cooldown);
+                }
+            }
+        }
+    }
+
+    // Whether a pick may return this node; claims the probe slot when half-open
+    fn admit(&mut self) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if self.probing => false,
+            CircuitState::HalfOpen => {
+                self.probing = true;
+                true
+            }
+        }
+    }
+}
+
+/// Circuit Breaker Strategy Wrapper
+///
+/// Features:
+/// - Tracks consecutive failures per node from `success` / `fail` counter deltas
+/// - Trips a node open after `failure_threshold` consecutive failures
+/// - Skips open nodes during pick, then half-opens after `cooldown` to let one probe through
+/// - A successful probe closes the breaker, a failed probe re-opens it
+/// - Breaker state is keyed by `endpoint.id` and survives picker rebuilds, including
+///   builds over a subset of the nodes; `BalanceStrategy::prune` drops removed nodes
+pub struct CircuitBreaker<S: BalanceStrategy> {
+    inner: S,
+    config: CircuitBreakerConfig,
+    states: Arc<parking_lot::Mutex<HashMap<u64, BreakerEntry>>>,
+}
+
+impl<S: BalanceStrategy> CircuitBreaker<S> {
+    pub fn new(inner: S) -> Self {
+        Self::with_config(inner, CircuitBreakerConfig::default())
+    }
+
+    pub fn with_config(inner: S, config: CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            config,
+            states: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Current breaker state of a node, `None` if the node has never been seen.
+    pub fn state(&self, node_id: u64) -> Option<CircuitState> {
+        self.states.lock().get(&node_id).map(|e| e.state)
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for CircuitBreaker<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("circuit_breaker({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.config.failure_threshold.hash(&mut h);
+        self.config.cooldown.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.states
+            .lock()
+            .retain(|id, _| live_ids.binary_search(id).is_ok());
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.states = Default::default();
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        // Build the inner picker first so a rejected node list leaves breaker state untouched
+        let inner = self.inner.build_picker(nodes.clone())?;
+        {
+            let mut states = self.states.lock();
+            for node in nodes.iter() {
+                states
+                    .entry(node.endpoint.id)
+                    .or_insert_with(|| BreakerEntry::new(node));
+            }
+        }
+
+        Ok(Arc::new(CircuitBreakerPicker {
+            inner,
+            nodes,
+            config: self.config.clone(),
+            states: self.states.clone(),
+        }))
+    }
+}
+
+struct CircuitBreakerPicker {
+    inner: Arc<dyn Picker>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    config: CircuitBreakerConfig,
+    states: Arc<parking_lot::Mutex<HashMap<u64, BreakerEntry>>>,
+}
+
+impl CircuitBreakerPicker {
+    fn admit(&self, node: &Node, now: Instant) -> bool {
+        let mut states = self.states.lock();
+        let entry = states
+            .entry(node.endpoint.id)
+            .or_insert_with(|| BreakerEntry::new(node));
+        entry.observe(node, &self.config, now);
+        entry.admit()
+    }
+}
+
+impl Picker for CircuitBreakerPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let len = self.nodes.len();
+        if len == 0 {
+            return Err(LoadBalanceError::NoAvailableNodes);
+        }
+
+        let now = Instant::now();
+
+        // Let the inner strategy choose first, skipping nodes whose breaker is open
+        for _ in 0..len {
+            let node = self.inner.pick(req)?;
+            if self.admit(&node, now) {
+                return Ok(node);
+            }
+        }
+
+        // Deterministic inner strategies may keep returning the same open node
+        self.nodes
+            .iter()
+            .find(|n| !req.is_excluded(n) && self.admit(n, now))
+            .cloned()
+            .ok_or(LoadBalanceError::NoAvailableNodes)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
+}
+
+/// Zone Aware (locality preference) Strategy Wrapper
+///
+/// Features:
+/// - Splits nodes into local (`endpoint.zone == local_zone`) and remote ones; nodes
+///   without a zone count as remote
+/// - Picks among healthy local nodes with the inner strategy, see `HealthFilter`
+/// - Spills to remote nodes only when the local pick fails with `NoAvailableNodes`, i.e.
+///   no healthy local node is left or an inner `CircuitBreaker` has every local node
+///   open; other errors such as `MissingHashKey` are returned as they are
+/// - Remote nodes are picked healthy-first, with best effort if none is healthy
+pub struct ZoneAware<S: BalanceStrategy> {
+    inner: S,
+    local_zone: FastStr,
+    health: HealthFilter,
+}
+
+impl<S: BalanceStrategy> ZoneAware<S> {
+    pub fn new(inner: S, local_zone: impl Into<FastStr>) -> Self {
+        Self {
+            inner,
+            local_zone: local_zone.into(),
+            health: HealthFilter::default(),
+        }
+    }
+
+    pub fn with_health(mut self, health: HealthFilter) -> Self {
+        self.health = health;
+        self
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for ZoneAware<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("zone_aware({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.local_zone.as_str().hash(&mut h);
+        self.health.hash_config(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let (local, remote): (Vec<_>, Vec<_>) = nodes
+            .iter()
+            .cloned()
+            .partition(|n| n.endpoint.zone.as_ref() == Some(&self.local_zone));
+
+        let (local_nodes, remote_nodes) = (Arc::new(local), Arc::new(remote));
+        Ok(Arc::new(ZoneAwarePicker {
+            local: self.inner.build_picker(local_nodes.clone())?,
+            remote: self.inner.build_picker(remote_nodes.clone())?,
+            local_nodes,
+            remote_nodes,
+            nodes,
+            local_zone: self.local_zone.clone(),
+            health: self.health,
+        }))
+    }
+}
+
+struct ZoneAwarePicker {
+    local: Arc<dyn Picker>,
+    local_nodes: Arc<Vec<Arc<Node>>>,
+    remote: Arc<dyn Picker>,
+    remote_nodes: Arc<Vec<Arc<Node>>>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    local_zone: FastStr,
+    health: HealthFilter,
+}
+
+impl ZoneAwarePicker {
+    // Picker of the partition `node` was put in
+    fn partition(&self, node: &Node) -> &Arc<dyn Picker> {
+        if node.endpoint.zone.as_ref() == Some(&self.local_zone) {
+            &self.local
+        } else {
+            &self.remote
+        }
+    }
+}
+
+impl Picker for ZoneAwarePicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        if let Some(healthy) = self.health.exclude_unhealthy(&self.local_nodes, req) {
+            match self.local.pick(&healthy) {
+                Err(LoadBalanceError::NoAvailableNodes) => {}
+                result => return result,
+            }
+        }
+
+        match self.health.exclude_unhealthy(&self.remote_nodes, req) {
+            Some(healthy) => self.remote.pick(&healthy),
+            None => self.remote.pick(req),
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.partition(node).on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.partition(node).update_estimates(node, rtt);
+    }
+}
+
+/// Hot Standby Strategy Wrapper
+///
+/// Features:
+/// - Nodes carrying the `active_tag` tag (any value) are active, nodes carrying the
+///   `standby_tag` tag are standbys; nodes with neither tag are never picked
+/// - Picks among healthy active nodes with the inner strategy, see `HealthFilter`
+/// - Falls back to the standbys only when the active pick fails with `NoAvailableNodes`,
+///   i.e. every active node is excluded, disabled or unhealthy; other errors such as
+///   `MissingHashKey` are returned as they are
+/// - Standbys are picked healthy-first, with best effort if none is healthy
+pub struct HotStandby<S: BalanceStrategy> {
+    inner: S,
+    active_tag: String,
+    standby_tag: String,
+    health: HealthFilter,
+}
+
+impl<S: BalanceStrategy> HotStandby<S> {
+    pub fn new(inner: S, active_tag: impl Into<String>, standby_tag: impl Into<String>) -> Self {
+        Self {
+            inner,
+            active_tag: active_tag.into(),
+            standby_tag: standby_tag.into(),
+            health: HealthFilter::default(),
+        }
+    }
+
+    pub fn with_health(mut self, health: HealthFilter) -> Self {
+        self.health = health;
+        self
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for HotStandby<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("hot_standby({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.active_tag.hash(&mut h);
+        self.standby_tag.hash(&mut h);
+        self.health.hash_config(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let tagged = |tag: &str| -> Arc<Vec<Arc<Node>>> {
+            Arc::new(
+                nodes
+                    .iter()
+                    .filter(|n| n.tags.contains_key(tag))
+                    .cloned()
+                    .collect(),
+            )
+        };
+        TieredPicker::build(
+            &self.inner,
+            nodes.clone(),
+            tagged(&self.active_tag),
+            tagged(&self.standby_tag),
+            self.health,
+        )
+    }
+}
+
+/// Node predicate of `Tiered`
+pub type NodePredicate = Arc<dyn Fn(&Node) -> bool + Send + Sync>;
+
+/// Primary / backup pool Strategy Wrapper
+///
+/// Features:
+/// - Partitions the node list at build time with two predicates, e.g. `by_tag("tier",
+///   "primary", "backup")`; nodes matching neither are never picked, nodes matching both
+///   are in both pools
+/// - Routes to healthy primary nodes with the inner strategy, see `HealthFilter`
+/// - Switches to the backup pool once no primary node is left, i.e. the primary pick
+///   fails with `NoAvailableNodes`; backups are picked healthy-first, with best effort if
+///   none is healthy
+/// - Same routing as `HotStandby`, for pools that are not told apart by tag presence
+#[derive(Clone)]
+pub struct Tiered<S: BalanceStrategy> {
+    inner: S,
+    primary: NodePredicate,
+    backup: NodePredicate,
+    health: HealthFilter,
+}
+
+impl<S: BalanceStrategy> Tiered<S> {
+    pub fn new<P, B>(inner: S, primary: P, backup: B) -> Self
+    where
+        P: Fn(&Node) -> bool + Send + Sync + 'static,
+        B: Fn(&Node) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            inner,
+            primary: Arc::new(primary),
+            backup: Arc::new(backup),
+            health: HealthFilter::default(),
+        }
+    }
+
+    /// Pools of the nodes whose `key` tag is `primary` and `backup`, respectively.
+    pub fn by_tag(
+        inner: S,
+        key: impl Into<String>,
+        primary: impl Into<String>,
+        backup: impl Into<String>,
+    ) -> Self {
+        let key: Arc<str> = key.into().into();
+        let backup_key = key.clone();
+        let primary = primary.into();
+        let backup = backup.into();
+        Self::new(
+            inner,
+            move |n: &Node| n.tags.get(&*key) == Some(&primary),
+            move |n: &Node| n.tags.get(&*backup_key) == Some(&backup),
+        )
+    }
+
+    pub fn with_health(mut self, health: HealthFilter) -> Self {
+        self.health = health;
+        self
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for Tiered<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("tiered({})", self.inner.name()).into()
+    }
+
+    /// Covers every field except the pool predicates, which cannot be compared.
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.health.hash_config(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        let pool = |pred: &NodePredicate| -> Arc<Vec<Arc<Node>>> {
+            Arc::new(nodes.iter().filter(|n| pred(n)).cloned().collect())
+        };
+        TieredPicker::build(
+            &self.inner,
+            nodes.clone(),
+            pool(&self.primary),
+            pool(&self.backup),
+            self.health,
+        )
+    }
+}
+
+// Primary pool first, backup pool once no primary node is left; shared by `HotStandby`
+// and `Tiered`
+struct TieredPicker {
+    primary: Arc<dyn Picker>,
+    primary_nodes: Arc<Vec<Arc<Node>>>,
+    backup: Arc<dyn Picker>,
+    backup_nodes: Arc<Vec<Arc<Node>>>,
+    // Routes responses to the pool that holds the node
+    primary_ids: ahash::AHashSet<u64>,
+    // Full list the pools were taken from, see `Picker::nodes`
+    nodes: Arc<Vec<Arc<Node>>>,
+    health: HealthFilter,
+}
+
+impl TieredPicker {
+    fn build<S: BalanceStrategy>(
+        inner: &S,
+        nodes: Arc<Vec<Arc<Node>>>,
+        primary_nodes: Arc<Vec<Arc<Node>>>,
+        backup_nodes: Arc<Vec<Arc<Node>>>,
+        health: HealthFilter,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(Self {
+            primary: inner.build_picker(primary_nodes.clone())?,
+            backup: inner.build_picker(backup_nodes.clone())?,
+            primary_ids: primary_nodes.iter().map(|n| n.endpoint.id).collect(),
+            primary_nodes,
+            backup_nodes,
+            nodes,
+            health,
+        }))
+    }
+
+    // Picker of the pool holding `node`; nodes in neither pool go to the backup, whose
+    // picker ignores them
+    fn pool(&self, node: &Node) -> &Arc<dyn Picker> {
+        if self.primary_ids.contains(&node.endpoint.id) {
+            &self.primary
+        } else {
+            &self.backup
+        }
+    }
+}
+
+impl Picker for TieredPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        if let Some(healthy) = self.health.exclude_unhealthy(&self.primary_nodes, req) {
+            match self.primary.pick(&healthy) {
+                Err(LoadBalanceError::NoAvailableNodes) => {}
+                result => return result,
+            }
+        }
+
+        match self.health.exclude_unhealthy(&self.backup_nodes, req) {
+            Some(healthy) => self.backup.pick(&healthy),
+            None => self.backup.pick(req),
+        }
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.pool(node).on_response(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.pool(node).update_estimates(node, rtt);
+    }
+}
+
+/// Topology Spread Strategy Wrapper
+///
+/// Features:
+/// - Groups nodes into zones by the value of their `zone_tag` tag; nodes without the tag
+///   form one zone of their own
+/// - A zone is eligible if sending the request there keeps the difference between the
+///   most and least loaded zone (total `in_flight`) within `max_skew`
+/// - Picks with the inner strategy in the eligible zone with the lowest `in_flight` per
+///   node, so zones with fewer nodes receive fewer requests
+/// - Falls back to the least loaded zone if no zone is eligible, and to the next zone if
+///   the inner pick fails, e.g. with every node of a zone excluded or open
+pub struct TopologySpread<S: BalanceStrategy> {
+    inner: S,
+    zone_tag: String,
+    max_skew: usize,
+}
+
+impl<S: BalanceStrategy> TopologySpread<S> {
+    pub fn new(inner: S, zone_tag: impl Into<String>, max_skew: usize) -> Self {
+        Self {
+            inner,
+            zone_tag: zone_tag.into(),
+            max_skew,
+        }
+    }
+}
+
+impl<S: BalanceStrategy> BalanceStrategy for TopologySpread<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("topology_spread({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        self.zone_tag.hash(&mut h);
+        self.max_skew.hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        // Zones in order of first appearance
+        let mut index: HashMap<Option<&str>, usize> = HashMap::new();
+        let mut members: Vec<Vec<Arc<Node>>> = Vec::new();
+        let mut zone_of = HashMap::with_capacity(nodes.len());
+        for node in nodes.iter() {
+            let zone = node.tags.get(&self.zone_tag).map(String::as_str);
+            let i = *index.entry(zone).or_insert_with(|| {
+                members.push(Vec::new());
+                members.len() - 1
+            });
+            members[i].push(node.clone());
+            zone_of.insert(node.endpoint.id, i);
+        }
+
+        let zones = members
+            .into_iter()
+            .map(|nodes| {
+                let nodes = Arc::new(nodes);
+                Ok(SpreadZone {
+                    picker: self.inner.build_picker(nodes.clone())?,
+                    nodes,
+                })
+            })
+            .collect::<Result<_, LoadBalanceError>>()?;
+        Ok(Arc::new(TopologySpreadPicker {
+            zones,
+            zone_of,
+            nodes,
+            max_skew: self.max_skew,
+        }))
+    }
+}
+
+struct SpreadZone {
+    nodes: Arc<Vec<Arc<Node>>>,
+    picker: Arc<dyn Picker>,
+}
+
+struct TopologySpreadPicker {
+    zones: Vec<SpreadZone>,
+    // Zone index by `endpoint.id`, routes responses to the zone that holds the node
+    zone_of: HashMap<u64, usize>,
+    nodes: Arc<Vec<Arc<Node>>>,
+    max_skew: usize,
+}
+
+impl TopologySpreadPicker {
+    // Zone holding `node`, if it is one of the picker's nodes
+    fn zone(&self, node: &Node) -> Option<&SpreadZone> {
+        self.zone_of.get(&node.endpoint.id).map(|&i| &self.zones[i])
+    }
+
+    // Difference between the most and least loaded zone after adding one request to `zone`
+    fn skew_with(loads: &[usize], zone: usize) -> usize {
+        let mut max = 0;
+        let mut min = usize::MAX;
+        for (i, &load) in loads.iter().enumerate() {
+            let load = if i == zone { load + 1 } else { load };
+            max = max.max(load);
+            min = min.min(load);
+        }
+        max - min
+    }
+}
+
+impl Picker for TopologySpreadPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let loads: SmallVec<[usize; 8]> = self
+            .zones
+            .iter()
+            .map(|z| {
+                z.nodes
+                    .iter()
+                    .map(|n| n.in_flight.load(Ordering::Relaxed))
+                    .sum()
+            })
+            .collect();
+
+        // Eligible zones by load per node, then the others by total load; the sort is
+        // stable so ties go to the earliest zone
+        let mut order: SmallVec<[(bool, f64, usize); 8]> = self
+            .zones
+            .iter()
+            .enumerate()
+            .filter(|(_, z)| z.nodes.iter().any(|n| !req.is_excluded(n)))
+            .map(|(i, z)| {
+                if Self::skew_with(&loads, i) <= self.max_skew {
+                    (false, loads[i] as f64 / z.nodes.len() as f64, i)
+                } else {
+                    (true, loads[i] as f64, i)
+                }
+            })
+            .collect();
+        order.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        let mut last_err = LoadBalanceError::NoAvailableNodes;
+        for &(_, _, i) in &order {
+            match self.zones[i].picker.pick(req) {
+                Ok(node) => return Ok(node),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        &self.nodes
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        match self.zone(node) {
+            Some(zone) => zone.picker.on_response(node, rtt),
+            None => node.record_rtt(rtt.as_nanos().min(u64::MAX as u128) as u64),
+        }
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        if let Some(zone) = self.zone(node) {
+            zone.picker.update_estimates(node, rtt);
+        }
+    }
+}
+
+/// Composite Strategy: hash-keyed requests go to `A`, keyless ones to `B`
+///
+/// Features:
+/// - Both sub-pickers are built from the same node list
+/// - Typical use is `ConsistentHash` for session-keyed requests and `RoundRobin` otherwise
+/// - Response feedback reaches both sub-pickers
+pub struct StrategyCompose<A: BalanceStrategy, B: BalanceStrategy> {
+    keyed: A,
+    keyless: B,
+}
+
+impl<A: BalanceStrategy, B: BalanceStrategy> StrategyCompose<A, B> {
+    pub fn new(keyed: A, keyless: B) -> Self {
+        Self { keyed, keyless }
+    }
+}
+
+impl<A: BalanceStrategy, B: BalanceStrategy> BalanceStrategy for StrategyCompose<A, B> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("compose({}, {})", self.keyed.name(), self.keyless.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.keyed.config_hash().hash(&mut h);
+        self.keyless.config_hash().hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.keyed.prune(live_ids);
+        self.keyless.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.keyed.detach_state();
+        self.keyless.detach_state();
+    }
+
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(ComposePicker {
+            keyed: self.keyed.build_picker(nodes.clone())?,
+            keyless: self.keyless.build_picker(nodes)?,
+        }))
+    }
+}
+
+struct ComposePicker {
+    keyed: Arc<dyn Picker>,
+    keyless: Arc<dyn Picker>,
+}
+
+impl ComposePicker {
+    fn route(&self, req: &RequestMetadata) -> &Arc<dyn Picker> {
+        if req.hash_key.is_some() {
+            &self.keyed
+        } else {
+            &self.keyless
+        }
+    }
+}
+
+impl Picker for ComposePicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.route(req).pick(req)
+    }
+
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.keyed.nodes()
+    }
+
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        self.route(req).pick_n(req, n)
+    }
+
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        self.route(req).pick_batch_distinct(req, n)
+    }
+
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        // The response does not say which route served it: record it once, let both
+        // update their estimates
+        self.keyless.on_response(node, rtt);
+        self.keyed.update_estimates(node, rtt);
+    }
+
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.keyed.update_estimates(node, rtt);
+        self.keyless.update_estimates(node, rtt);
+    }
+}
+
+/// Picker wrapper that enforces each node's `rate_limit`
+///
+/// Features:
+/// - Every pick takes a token from the picked node's bucket (`Node::try_acquire_token`)
+/// - A node out of tokens is excluded and the inner picker asked again, up to
+///   `max_retries` times, so deterministic strategies move on to another node too
+/// - Fails with `Overloaded` once the retries are used up or no other node is left
+pub struct RateAwarePicker<P: Picker + ?Sized = dyn Picker> {
+    inner: Arc<P>,
+    max_retries: usize,
+}
+
+impl<P: Picker + ?Sized> RateAwarePicker<P> {
+    pub const DEFAULT_MAX_RETRIES: usize = 3;
+
+    pub fn new(inner: Arc<P>) -> Self {
+        Self {
+            inner,
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    /// Extra picks after the first one hits a rate limited node.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+impl<P: Picker + ?Sized> Picker for RateAwarePicker<P> {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let node = self.inner.pick(req)?;
+        if node.try_acquire_token() {
+            return Ok(node);
+        }
+
+        let mut req = req.clone();
+        let mut limited = node;
+        for _ in 0..self.max_retries {
+            req.exclude_ids.push(limited.endpoint.id);
+            limited = match self.inner.pick(&req) {
+                Ok(node) => node,
+                Err(LoadBalanceError::NoAvailableNodes) => break,
+                Err(e) => return Err(e),
+            };
+            if limited.try_acquire_token() {
+                return Ok(limited);
+            }
         }
+        Err(LoadBalanceError::Overloaded)
+    }
 
-        Ok(self.nodes[i].clone())
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.inner.nodes()
     }
-}
 
-// Weighted Round Robin (smooth)
-pub struct WeightedRoundRobin;
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
 
-impl BalanceStrategy for WeightedRoundRobin {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        Arc::new(WRRPicker::new(nodes))
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
     }
 }
 
-struct WRRPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
-    cw: parking_lot::Mutex<i32>,
-    idx: parking_lot::Mutex<usize>,
-    max_w: i32,
-    gcd_w: i32,
-    weights: Vec<i32>,
+/// Picker wrapper that records request latency automatically
+///
+/// Features:
+/// - `pick_timed` returns a `TimedPickHandle` that measures the request from the pick on
+/// - Dropping (or `finish`ing) the handle folds the elapsed time into `node.last_rtt_ns`
+///   as an EWMA with weight `alpha` for the new sample (an unset RTT takes the sample as is)
+/// - The sample is also passed to the inner picker's `on_response`, so adaptive
+///   strategies such as `PeakEwma` update without caller boilerplate
+pub struct TimeoutAwarePicker<P: Picker + ?Sized = dyn Picker> {
+    inner: Arc<P>,
+    alpha: f64,
 }
 
-impl WRRPicker {
-    fn gcd(a: i32, b: i32) -> i32 {
-        if b == 0 {
-            a
-        } else {
-            Self::gcd(b, a % b)
-        }
-    }
-    fn new(nodes: Arc<Vec<Arc<Node>>>) -> Self {
-        let mut max_w = 0i32;
-        let mut gcd_w = 0i32;
-        let mut weights = Vec::new();
-        for n in nodes.iter() {
-            let w = n.weight as i32;
-            if w > 0 {
-                max_w = max_w.max(w);
-                gcd_w = if gcd_w == 0 { w } else { Self::gcd(gcd_w, w) };
-            }
-            weights.push(w);
-        }
+impl<P: Picker + ?Sized> TimeoutAwarePicker<P> {
+    pub const DEFAULT_ALPHA: f64 = 0.3;
+
+    pub fn new(inner: Arc<P>) -> Self {
         Self {
-            nodes,
-            cw: parking_lot::Mutex::new(0),
-            idx: parking_lot::Mutex::new(usize::MAX),
-            max_w,
-            gcd_w: gcd_w.max(1),
-            weights,
+            inner,
+            alpha: Self::DEFAULT_ALPHA,
         }
     }
-}
-
-impl Picker for WRRPicker {
-    fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
-        }
 
-        // Check if all node weights are 0
-        if self.max_w <= 0 {
-            // If all weights are 0, degrade to simple polling
-            let mut i = self.idx.lock();
-            *i = if *i == usize::MAX { 0 } else { (*i + 1) % len };
-            return Ok(self.nodes[*i].clone());
-        }
+    /// Weight of the newest sample, clamped to `0.0..=1.0`; 1.0 keeps only the last RTT.
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = if alpha.is_nan() {
+            Self::DEFAULT_ALPHA
+        } else {
+            alpha.clamp(0.0, 1.0)
+        };
+        self
+    }
 
-        let mut i = self.idx.lock();
-        let mut cw = self.cw.lock();
+    pub fn pick_timed(
+        &self,
+        req: &RequestMetadata,
+    ) -> Result<TimedPickHandle<P>, LoadBalanceError> {
+        let node = self.inner.pick(req)?;
+        Ok(TimedPickHandle {
+            node,
+            start: Instant::now(),
+            picker: self.inner.clone(),
+            alpha: self.alpha,
+            recorded: false,
+        })
+    }
+}
 
-        // Prevent infinite loops, loop at most len*2 times
-        let mut attempts = 0;
-        let max_attempts = len * 2;
+impl<P: Picker + ?Sized> Picker for TimeoutAwarePicker<P> {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        self.inner.pick(req)
+    }
 
-        loop {
-            *i = if *i == usize::MAX { 0 } else { (*i + 1) % len };
-            if *i == 0 {
-                *cw = (*cw - self.gcd_w).max(0);
-                if *cw == 0 {
-                    *cw = self.max_w;
-                }
-            }
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.inner.nodes()
+    }
 
-            // If a suitable node is found or too many attempts, return
-            if self.weights[*i] >= *cw || attempts >= max_attempts {
-                return Ok(self.nodes[*i].clone());
-            }
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        self.inner.pick_n(req, n)
+    }
 
-            attempts += 1;
-        }
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        self.inner.pick_batch_distinct(req, n)
     }
-}
 
-// P2C (Power of Two Choices)
-pub struct PowerOfTwoChoices;
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
 
-impl BalanceStrategy for PowerOfTwoChoices {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        Arc::new(P2CPicker { nodes })
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
     }
 }
 
-struct P2CPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
+/// In-flight request on a node picked by `TimeoutAwarePicker::pick_timed`
+///
+/// Records the elapsed time when dropped.
+pub struct TimedPickHandle<P: Picker + ?Sized = dyn Picker> {
+    pub node: Arc<Node>,
+    pub start: Instant,
+    picker: Arc<P>,
+    alpha: f64,
+    recorded: bool,
 }
 
-impl Picker for P2CPicker {
-    fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
-        }
-        if len == 1 {
-            return Ok(self.nodes[0].clone());
-        }
+impl<P: Picker + ?Sized> TimedPickHandle<P> {
+    /// Record the elapsed time now and return the sample.
+    pub fn finish(mut self) -> Duration {
+        self.record()
+    }
 
-        let mut rng = rand::thread_rng();
-        let a = rng.gen_range(0..len);
+    fn record(&mut self) -> Duration {
+        let elapsed = self.start.elapsed();
+        if self.recorded {
+            return elapsed;
+        }
+        self.recorded = true;
 
-        let b = loop {
-            let x = rng.gen_range(0..len);
-            if x != a {
-                break x;
-            }
-        };
-        let na = self.nodes[a]
-            .in_flight
-            .load(std::sync::atomic::Ordering::Acquire);
-        let nb = self.nodes[b]
-            .in_flight
-            .load(std::sync::atomic::Ordering::Acquire);
-        Ok(if na <= nb {
-            self.nodes[a].clone()
+        let sample = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+        let previous = self.node.last_rtt_ns.load(Ordering::Relaxed);
+        let ewma = if previous == 0 {
+            sample
         } else {
-            self.nodes[b].clone()
-        })
+            (self.alpha * sample as f64 + (1.0 - self.alpha) * previous as f64) as u64
+        };
+
+        // The inner hook records the raw sample (histogram included), so the EWMA only
+        // overwrites the last RTT afterwards
+        self.picker.on_response(&self.node, elapsed);
+        self.node.last_rtt_ns.store(ewma, Ordering::Relaxed);
+        self.node
+            .last_rtt_updated_at
+            .store(unix_now_ns(), Ordering::Relaxed);
+        elapsed
     }
 }
 
-/// Weighted Random Load Balancing Strategy
+impl<P: Picker + ?Sized> Drop for TimedPickHandle<P> {
+    fn drop(&mut self) {
+        self.record();
+    }
+}
+
+/// Tracing wrapper Strategy
 ///
 /// Features:
-/// - Random selection based on node weights
-/// - Higher weight means higher probability of being selected
-/// - Performance optimizations:
-///   - Uses thread-local random number generator
-///   - Handles cases where all weights are 0
-#[derive(Clone, Debug)]
-pub struct WeightedRandom;
+/// - Emits a `trace!` event for every pick with the strategy name, selected `endpoint.id`
+///   and the request `hash_key`
+/// - Pick errors are reported as `trace!` events with the error
+/// - Only available with the `tracing` feature
+#[cfg(feature = "tracing")]
+pub struct TracedStrategy<S: BalanceStrategy> {
+    inner: S,
+}
 
-impl BalanceStrategy for WeightedRandom {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        // Check if all node weights are 0
-        let all_zero = nodes.iter().all(|n| n.weight == 0);
+#[cfg(feature = "tracing")]
+impl<S: BalanceStrategy> TracedStrategy<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
 
-        // If all weights are 0, use equal weights
-        let weights: Vec<f64> = if all_zero {
-            nodes.iter().map(|_| 1.0).collect()
-        } else {
-            nodes.iter().map(|n| (n.weight as f64).max(0.0)).collect()
-        };
+#[cfg(feature = "tracing")]
+impl<S: BalanceStrategy> BalanceStrategy for TracedStrategy<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("traced({})", self.inner.name()).into()
+    }
+
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        h.finish()
+    }
+
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
+
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
 
-        let dist = WeightedIndex::new(&weights).ok();
-        Arc::new(WeightedRandomPicker { nodes, dist })
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(TracedPicker {
+            inner: self.inner.build_picker(nodes)?,
+            strategy: self.inner.name(),
+        }))
     }
 }
 
-struct WeightedRandomPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
-    dist: Option<WeightedIndex<f64>>,
+#[cfg(feature = "tracing")]
+struct TracedPicker {
+    inner: Arc<dyn Picker>,
+    strategy: Cow<'static, str>,
 }
 
-impl Picker for WeightedRandomPicker {
-    fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
+#[cfg(feature = "tracing")]
+impl Picker for TracedPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let result = self.inner.pick(req);
+        match &result {
+            Ok(node) => tracing::trace!(
+                strategy = %self.strategy,
+                endpoint_id = node.endpoint.id,
+                hash_key = ?req.hash_key,
+                "picked node"
+            ),
+            Err(err) => tracing::trace!(
+                strategy = %self.strategy,
+                hash_key = ?req.hash_key,
+                error = %err,
+                "pick failed"
+            ),
         }
+        result
+    }
 
-        // If there is only one node, return directly
-        if len == 1 {
-            return Ok(self.nodes[0].clone());
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.inner.nodes()
+    }
+
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        // Forward to keep the inner strategy's own `pick_n`, e.g. distinct rotation slots
+        let result = self.inner.pick_n(req, n);
+        match &result {
+            Ok(nodes) => tracing::trace!(
+                strategy = %self.strategy,
+                endpoint_ids = ?nodes.iter().map(|n| n.endpoint.id).collect::<Vec<_>>(),
+                hash_key = ?req.hash_key,
+                "picked nodes"
+            ),
+            Err(err) => tracing::trace!(
+                strategy = %self.strategy,
+                hash_key = ?req.hash_key,
+                error = %err,
+                "pick failed"
+            ),
         }
+        result
+    }
 
-        // Use weighted distribution to select nodes
-        if let Some(dist) = &self.dist {
-            // Use thread-local random number generator to avoid creating a new generator each time
-            let mut rng = rand::thread_rng();
-            let idx = dist.sample(&mut rng);
-            Ok(self.nodes[idx].clone())
-        } else {
-            // If there is no weight distribution, degrade to polling
-            let mut rng = rand::thread_rng();
-            let idx = rng.gen_range(0..len);
-            Ok(self.nodes[idx].clone())
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let result = self.inner.pick_batch_distinct(req, n);
+        match &result {
+            Ok(nodes) => tracing::trace!(
+                strategy = %self.strategy,
+                endpoint_ids = ?nodes.iter().map(|n| n.endpoint.id).collect::<Vec<_>>(),
+                hash_key = ?req.hash_key,
+                "picked batch"
+            ),
+            Err(err) => tracing::trace!(
+                strategy = %self.strategy,
+                hash_key = ?req.hash_key,
+                error = %err,
+                "pick failed"
+            ),
         }
+        result
     }
-}
 
-// Least Connection
-pub struct LeastConnection;
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
 
-impl BalanceStrategy for LeastConnection {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        Arc::new(LeastConnPicker { nodes })
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
     }
 }
 
-struct LeastConnPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
+/// Metrics wrapper Strategy
+///
+/// Features:
+/// - Increments `lb_picks_total{strategy,endpoint}` for every picked node
+/// - Increments `lb_pick_errors_total{reason}` for every failed pick
+/// - Sets the `lb_node_in_flight{endpoint}` gauge of the picked node
+/// - Reports through the `metrics` facade, so any installed recorder (e.g. Prometheus) works
+/// - Only available with the `metrics` feature
+#[cfg(feature = "metrics")]
+pub struct MeteredStrategy<S: BalanceStrategy> {
+    inner: S,
 }
 
-impl Picker for LeastConnPicker {
-    fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
-        }
-        let mut best = &self.nodes[0];
-        let mut best_load = best.in_flight.load(std::sync::atomic::Ordering::Acquire);
-        for n in self.nodes.iter().skip(1) {
-            let load = n.in_flight.load(std::sync::atomic::Ordering::Acquire);
-            if load < best_load {
-                best = n;
-                best_load = load;
-            }
-        }
-        Ok(best.clone())
+#[cfg(feature = "metrics")]
+impl<S: BalanceStrategy> MeteredStrategy<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
     }
 }
 
-/// Response Time Weighted Load Balancing Strategy
-///
-/// Features:
-/// - Weighted selection based on node's recent response time (RTT)
-/// - Smaller RTT means higher weight
-/// - Also considers current load (in_flight)
-/// - Performance optimization: single-pass scan to find the highest score (O(n))
-#[derive(Clone, Debug)]
-pub struct ResponseTimeWeighted;
+#[cfg(feature = "metrics")]
+impl<S: BalanceStrategy> BalanceStrategy for MeteredStrategy<S> {
+    fn name(&self) -> Cow<'static, str> {
+        format!("metered({})", self.inner.name()).into()
+    }
 
-impl BalanceStrategy for ResponseTimeWeighted {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        Arc::new(RTWeightedPicker { nodes })
+    fn config_hash(&self) -> u64 {
+        let mut h = config_hasher(&self.name());
+        self.inner.config_hash().hash(&mut h);
+        h.finish()
     }
-}
 
-struct RTWeightedPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
-}
+    fn prune(&self, live_ids: &[u64]) {
+        self.inner.prune(live_ids);
+    }
 
-impl Picker for RTWeightedPicker {
-    fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
-        }
+    fn detach_state(&mut self) {
+        self.inner.detach_state();
+    }
 
-        // Single pass O(n) selection; avoids allocation + sort on every pick
-        let mut iter = self.nodes.iter();
-        let first = iter.next().unwrap();
-        let mut best_node = first.clone();
-        let mut best_score = score(first);
+    fn build_picker(
+        &self,
+        nodes: Arc<Vec<Arc<Node>>>,
+    ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+        Ok(Arc::new(MeteredPicker {
+            inner: self.inner.build_picker(nodes)?,
+            strategy: self.inner.name(),
+        }))
+    }
+}
 
-        for node in iter {
-            let s = score(node);
-            if s > best_score {
-                best_score = s;
-                best_node = node.clone();
-            }
-        }
+#[cfg(feature = "metrics")]
+struct MeteredPicker {
+    inner: Arc<dyn Picker>,
+    strategy: Cow<'static, str>,
+}
 
-        Ok(best_node)
+#[cfg(feature = "metrics")]
+impl MeteredPicker {
+    fn record_pick(&self, node: &Node) {
+        let endpoint = node.endpoint.id.to_string();
+        metrics::counter!(
+            "lb_picks_total",
+            "strategy" => self.strategy.clone(),
+            "endpoint" => endpoint.clone()
+        )
+        .increment(1);
+        metrics::gauge!("lb_node_in_flight", "endpoint" => endpoint)
+            .set(node.in_flight.load(Ordering::Relaxed) as f64);
+    }
+
+    fn record_error(&self, err: &LoadBalanceError) {
+        metrics::counter!("lb_pick_errors_total", "reason" => error_reason(err)).increment(1);
     }
 }
 
-fn score(n: &Arc<Node>) -> f64 {
-    // Use atomic operations to get the latest values
-    let rtt = n.last_rtt_ns.load(std::sync::atomic::Ordering::Acquire);
-    let inflight = n.in_flight.load(std::sync::atomic::Ordering::Acquire) as u64;
+#[cfg(feature = "metrics")]
+impl Picker for MeteredPicker {
+    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+        let result = self.inner.pick(req);
+        match &result {
+            Ok(node) => self.record_pick(node),
+            Err(err) => self.record_error(err),
+        }
+        result
+    }
 
-    // Handle the case where rtt is 0
-    let rtt = if rtt == 0 { 1 } else { rtt };
+    fn nodes(&self) -> &[Arc<Node>] {
+        self.inner.nodes()
+    }
 
-    // Calculate response time score
-    let rtt_score = (1_000_000_000u64 / rtt) as f64;
+    fn pick_n(&self, req: &RequestMetadata, n: usize) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let result = self.inner.pick_n(req, n);
+        match &result {
+            Ok(nodes) => nodes.iter().for_each(|node| self.record_pick(node)),
+            Err(err) => self.record_error(err),
+        }
+        result
+    }
 
-    // Calculate load factor
-    let load_factor = 1.0 + inflight as f64;
+    fn pick_batch_distinct(
+        &self,
+        req: &RequestMetadata,
+        n: usize,
+    ) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+        let result = self.inner.pick_batch_distinct(req, n);
+        match &result {
+            Ok(nodes) => nodes.iter().for_each(|node| self.record_pick(node)),
+            Err(err) => self.record_error(err),
+        }
+        result
+    }
 
-    // Comprehensive score
-    rtt_score / load_factor
-}
+    fn on_response(&self, node: &Node, rtt: Duration) {
+        self.inner.on_response(node, rtt);
+    }
 
-// Consistent Hash
-pub struct ConsistentHash {
-    // Virtual node multiplier, number of virtual nodes corresponding to each real node
-    pub virtual_factor: usize,
+    fn update_estimates(&self, node: &Node, rtt: Duration) {
+        self.inner.update_estimates(node, rtt);
+    }
 }
 
-impl Default for ConsistentHash {
-    fn default() -> Self {
-        Self { virtual_factor: 10 }
+// Low-cardinality label for `lb_pick_errors_total`
+#[cfg(feature = "metrics")]
+fn error_reason(err: &LoadBalanceError) -> &'static str {
+    // Exhaustive on purpose, `#[non_exhaustive]` only applies outside the crate: a new
+    // variant has to pick its own label here
+    match err {
+        LoadBalanceError::NoAvailableNodes => "no_available_nodes",
+        LoadBalanceError::MissingHashKey => "missing_hash_key",
+        LoadBalanceError::WeightOverflow { .. } => "weight_overflow",
+        LoadBalanceError::WeightCountMismatch { .. } => "weight_count_mismatch",
+        LoadBalanceError::InsufficientNodes { .. } => "insufficient_nodes",
+        LoadBalanceError::ZeroVirtualFactor => "zero_virtual_factor",
+        LoadBalanceError::UnknownTenant(_) => "unknown_tenant",
+        LoadBalanceError::Overloaded => "overloaded",
+        LoadBalanceError::InvalidWeights => "invalid_weights",
+        LoadBalanceError::Timeout => "timeout",
+        LoadBalanceError::EmptyInstances { .. } => "empty_instances",
+        LoadBalanceError::NodeNotFound(_) => "node_not_found",
+        LoadBalanceError::DuplicateNode(_) => "duplicate_node",
+        LoadBalanceError::Custom(_) => "custom",
     }
 }
 
-impl BalanceStrategy for ConsistentHash {
-    fn build_picker(&self, nodes: Arc<Vec<Arc<Node>>>) -> Arc<dyn Picker> {
-        Arc::new(ConsistentHashPicker::new(nodes, self.virtual_factor))
+// `Ok` only if a `pick_batch_distinct` call found all `requested` nodes
+fn check_batch(
+    picked: Vec<Arc<Node>>,
+    requested: usize,
+) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+    if picked.len() < requested {
+        return Err(LoadBalanceError::InsufficientNodes {
+            requested,
+            available: picked.len(),
+        });
     }
+    Ok(picked)
 }
 
-struct ConsistentHashPicker {
-    nodes: Arc<Vec<Arc<Node>>>,
-    // Hash ring: (hash value, node index)
-    ring: Vec<(u64, usize)>,
+// The `n` nodes `req` does not exclude with the smallest `key`, earliest first among
+// equals; a full `pick_batch_distinct` result or `InsufficientNodes`
+fn smallest_n<K: PartialOrd>(
+    nodes: &[Arc<Node>],
+    req: &RequestMetadata,
+    n: usize,
+    key: impl Fn(&Arc<Node>) -> K,
+) -> Result<Vec<Arc<Node>>, LoadBalanceError> {
+    let mut ranked: Vec<(K, &Arc<Node>)> = nodes
+        .iter()
+        .filter(|node| !req.is_excluded(node))
+        .map(|node| (key(node), node))
+        .collect();
+    // Stable sort keeps node order for equal keys
+    ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    check_batch(
+        ranked
+            .into_iter()
+            .take(n)
+            .map(|(_, node)| node.clone())
+            .collect(),
+        n,
+    )
 }
 
-impl ConsistentHashPicker {
-    fn new(nodes: Arc<Vec<Arc<Node>>>, virtual_factor: usize) -> Self {
-        let mut ring = Vec::new();
-
-        // Normalize weights to avoid exploding virtual nodes when weights are large.
-        let weights: Vec<usize> = nodes.iter().map(|n| n.weight.max(1) as usize).collect();
-        let gcd_w = weights
-            .iter()
-            .copied()
-            .fold(
-                0usize,
-                |acc, w| if acc == 0 { w } else { gcd_usize(acc, w) },
-            )
-            .max(1);
-
-        // Hard cap to keep ring size reasonable while preserving relative weights.
-        const MAX_VNODE_PER_NODE: usize = 1024;
-
-        // Create virtual nodes for each node
-        for (i, node) in nodes.iter().enumerate() {
-            let normalized = (weights[i] / gcd_w).max(1);
-            let vnode_count = normalized
-                .saturating_mul(virtual_factor)
-                .min(MAX_VNODE_PER_NODE)
-                .max(1);
+// Index of the first node `req` does not exclude; callers check that one exists
+fn first_allowed(nodes: &[Arc<Node>], req: &RequestMetadata) -> usize {
+    nodes.iter().position(|n| !req.is_excluded(n)).unwrap_or(0)
+}
 
-            let base_key = stable_node_key(node, i);
+// Two distinct random indices of nodes `req` does not exclude, resampling excluded draws.
+// `Ok(None)` if exactly one node is left to pick from.
+fn sample_two<R: Rng + ?Sized>(
+    nodes: &[Arc<Node>],
+    req: &RequestMetadata,
+    rng: &mut R,
+) -> Result<Option<(usize, usize)>, LoadBalanceError> {
+    let len = nodes.len();
+    // Counted even without exclusions, since disabled nodes are excluded as well
+    let allowed = nodes.iter().filter(|n| !req.is_excluded(n)).count();
+    match allowed {
+        0 => return Err(LoadBalanceError::NoAvailableNodes),
+        1 => return Ok(None),
+        _ => {}
+    }
 
-            for j in 0..vnode_count {
-                // Generate hash value using node address and virtual node index
-                let key = format!("{base_key}#{j}");
-                let hash = hash_str(&key);
-                ring.push((hash, i));
-            }
+    let mut draw = |other: Option<usize>| loop {
+        let x = rng.gen_range(0..len);
+        if Some(x) != other && !req.is_excluded(&nodes[x]) {
+            break x;
         }
+    };
+    let a = draw(None);
+    let b = draw(Some(a));
+    Ok(Some((a, b)))
+}
 
-        // Sort by hash value
-        ring.sort_by_key(|&(hash, _)| hash);
+// Share `total` virtual nodes by weight with the largest remainder method. Weights that are
+// not positive and finite get the minimum of one; if no weight is, all count as equal.
+fn normalized_vnode_counts(weights: &[f64], total: usize) -> Vec<usize> {
+    let valid = |w: f64| w.is_finite() && w > 0.0;
+    let all_invalid = !weights.iter().copied().any(valid);
+    let weights: Vec<f64> = weights
+        .iter()
+        .map(|&w| match (all_invalid, valid(w)) {
+            (true, _) => 1.0,
+            (false, true) => w,
+            (false, false) => 0.0,
+        })
+        .collect();
+    let sum: f64 = weights.iter().sum();
 
-        Self { nodes, ring }
+    let exact: Vec<f64> = weights.iter().map(|w| w / sum * total as f64).collect();
+    let mut counts: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+    let assigned: usize = counts.iter().sum();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let ra = exact[a] - exact[a].floor();
+        let rb = exact[b] - exact[b].floor();
+        rb.total_cmp(&ra)
+    });
+    for &i in by_remainder.iter().take(total.saturating_sub(assigned)) {
+        counts[i] += 1;
     }
+    // Like the other policies, every node keeps at least one virtual node
+    counts.iter_mut().for_each(|c| *c = (*c).max(1));
+    counts
 }
 
-impl Picker for ConsistentHashPicker {
-    fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
-        let len = self.nodes.len();
-        if len == 0 {
-            return Err(LoadBalanceError::NoAvailableNodes);
-        }
-
-        // If there are no virtual nodes, degrade to simple hashing
-        if self.ring.is_empty() {
-            let key = req.hash_key.ok_or(LoadBalanceError::MissingHashKey)?;
-            let idx = (hash64(key) % (len as u64)) as usize;
-            return Ok(self.nodes[idx].clone());
-        }
+// Hasher for `BalanceStrategy::config_hash`, seeded with the strategy's `name`
+fn config_hasher(name: &str) -> ahash::AHasher {
+    let mut h = RING_HASH_STATE.build_hasher();
+    h.write(name.as_bytes());
+    h
+}
 
-        let key = req.hash_key.ok_or(LoadBalanceError::MissingHashKey)?;
-        let hash = hash64(key);
+// Fixed keys, so rings agree across processes (`AHasher::default` is keyed per process)
+const RING_HASH_STATE: RandomState = RandomState::with_seeds(
+    0x243f_6a88_85a3_08d3,
+    0x1319_8a2e_0370_7344,
+    0xa409_3822_299f_31d0,
+    0x082e_fa98_ec4e_6c89,
+);
 
-        // Binary search to find the first position greater than or equal to hash
-        match self.ring.binary_search_by(|&(h, _)| h.cmp(&hash)) {
-            Ok(idx) => {
-                // Found exact match
-                let (_, node_idx) = self.ring[idx];
-                Ok(self.nodes[node_idx].clone())
-            }
-            Err(idx) => {
-                // No exact match found, take the next node (ring)
-                let idx = if idx >= self.ring.len() { 0 } else { idx };
-                let (_, node_idx) = self.ring[idx];
-                Ok(self.nodes[node_idx].clone())
-            }
-        }
-    }
+// Default consistent hash function
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut h = RING_HASH_STATE.build_hasher();
+    h.write(bytes);
+    h.finish()
 }
 
-// Hash a string
-fn hash_str(s: &str) -> u64 {
-    let mut h = AHasher::default();
-    s.hash(&mut h);
-    h.finish()
+// `hasher` over `bytes` prefixed with the little-endian seed, or unchanged for seed 0 so
+// custom hashers keep matching other implementations
+fn seeded_hash(hasher: &HashFn, seed: u64, bytes: &[u8]) -> u64 {
+    if seed == 0 {
+        return hasher(bytes);
+    }
+    let mut buf: SmallVec<[u8; 64]> = SmallVec::with_capacity(8 + bytes.len());
+    buf.extend_from_slice(&seed.to_le_bytes());
+    buf.extend_from_slice(bytes);
+    hasher(&buf)
 }
 
 fn gcd_usize(a: usize, b: usize) -> usize {
@@ -491,36 +5946,29 @@ fn gcd_usize(a: usize, b: usize) -> usize {
 }
 
 fn stable_node_key(node: &Arc<Node>, idx: usize) -> String {
-    let addr = format_address(&node.endpoint.address);
-    format!("id:{}|addr:{}|idx:{idx}", node.endpoint.id, addr)
-}
-
-#[cfg(feature = "volo-adapter")]
-fn format_address(addr: &volo::net::Address) -> String {
-    format!("{addr:?}")
+    format!(
+        "id:{}|addr:{}|idx:{idx}",
+        node.endpoint.id, node.endpoint.address
+    )
 }
 
-#[cfg(not(feature = "volo-adapter"))]
-fn format_address(addr: &String) -> String {
-    addr.clone()
-}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::node::Endpoint;
+    use crate::node::{Endpoint, NodeBuilder};
     use std::net::SocketAddr;
 
     fn create_test_node(weight: i32, _in_flight: u64, _rtt: u64) -> Arc<Node> {
-        Arc::new(Node::new(
-            Endpoint {
-                id: 1,
-                #[cfg(feature = "volo-adapter")]
-                address: volo::net::Address::from(SocketAddr::from(([127, 0, 0, 1], 8080))),
-                #[cfg(not(feature = "volo-adapter"))]
-                address: "127.0.0.1:8080".to_string(),
-            },
-            weight as u32,
-        ))
+        Arc::new(
+            NodeBuilder::default()
+                .endpoint(Endpoint {
+                    id: 1,
+                    address: SocketAddr::from(([127, 0, 0, 1], 8080)).into(),
+                    zone: None,
+                })
+                .weight(weight as u32)
+                .build(),
+        )
     }
 
     #[test]
@@ -529,9 +5977,15 @@ mod tests {
         let balancer = BaseBalancer::new(RoundRobin);
         balancer.update_nodes(nodes.clone());
 
-        let picker = balancer.picker();
-        assert_eq!(picker.pick(&RequestMetadata::default()).unwrap().weight, 1);
-        assert_eq!(picker.pick(&RequestMetadata::default()).unwrap().weight, 1);
+        let picker = balancer.picker().unwrap();
+        assert_eq!(
+            picker.pick(&RequestMetadata::default()).unwrap().weight(),
+            1
+        );
+        assert_eq!(
+            picker.pick(&RequestMetadata::default()).unwrap().weight(),
+            1
+        );
     }
 
     #[test]
@@ -540,7 +5994,7 @@ mod tests {
         let balancer = BaseBalancer::new(WeightedRandom);
         balancer.update_nodes(nodes.clone());
 
-        let picker = balancer.picker();
+        let picker = balancer.picker().unwrap();
         let mut counts = [0; 2];
         for _ in 0..1000 {
             let node = picker.pick(&RequestMetadata::default()).unwrap();
@@ -552,9 +6006,3 @@ mod tests {
         assert!(counts[0] > (counts[1] as f64 * 1.5) as usize);
     }
 }
-
-fn hash64(v: u64) -> u64 {
-    let mut h = AHasher::default();
-    v.hash(&mut h);
-    h.finish()
-}
@@ -0,0 +1,222 @@
+//! Deterministic load simulation
+//!
+//! Drives a strategy with synthetic requests on a virtual clock, so strategies can be
+//! compared without real servers. Requests arrive at a fixed interval; each node serves up
+//! to `capacity` requests at once for `service_time_ns` each and queues the rest. Completed
+//! requests update `in_flight` and `success` and are reported through
+//! `Picker::on_response`, which records the simulated latency (see `Node::record_rtt`).
+//!
+//! Given a deterministic strategy the results are identical on every run.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::LoadBalanceError;
+use crate::node::Node;
+use crate::strategy::{BalanceStrategy, Picker, RequestMetadata};
+
+/// A simulated backend
+#[derive(Clone, Debug)]
+pub struct SimNode {
+    pub id: u64,
+    // Time to serve one request once it leaves the queue
+    pub service_time_ns: u64,
+    // Requests served concurrently, 0 is treated as 1
+    pub capacity: usize,
+}
+
+/// Per-node outcome of [`Simulator::run`]
+#[derive(Clone, Debug, Default)]
+pub struct SimNodeStats {
+    pub id: u64,
+    /// Requests picked for the node
+    pub picks: usize,
+    /// Longest queue (requests waiting, not in service) seen by an arriving request
+    pub max_queue_depth: usize,
+    /// Queue length seen by arriving requests, averaged
+    pub mean_queue_depth: f64,
+    /// Busy share of the node's capacity over the whole run, in `0.0..=1.0`
+    pub utilization: f64,
+    /// Time from arrival to completion of every request, queueing included, sorted
+    pub latencies_ns: Vec<u64>,
+}
+
+impl SimNodeStats {
+    /// Latency at quantile `q` in `0.0..=1.0` (nearest rank), 0 without requests.
+    pub fn latency_percentile(&self, q: f64) -> u64 {
+        if self.latencies_ns.is_empty() {
+            return 0;
+        }
+        let rank = (q.clamp(0.0, 1.0) * (self.latencies_ns.len() - 1) as f64).round();
+        self.latencies_ns[rank as usize]
+    }
+}
+
+/// Outcome of [`Simulator::run`]
+#[derive(Clone, Debug, Default)]
+pub struct SimResult {
+    /// Stats in the order of the simulator's nodes
+    pub nodes: Vec<SimNodeStats>,
+    /// Picks that returned an error; these requests are dropped
+    pub failed_picks: usize,
+    /// Virtual time until the last request completed
+    pub elapsed_ns: u64,
+}
+
+impl SimResult {
+    pub fn node(&self, id: u64) -> Option<&SimNodeStats> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+}
+
+/// Discrete-event simulator driving strategy `S`
+pub struct Simulator<S: BalanceStrategy> {
+    strategy: S,
+    nodes: Vec<SimNode>,
+    arrival_interval_ns: u64,
+}
+
+impl<S: BalanceStrategy> Simulator<S> {
+    pub const DEFAULT_ARRIVAL_INTERVAL: Duration = Duration::from_millis(1);
+
+    pub fn new(strategy: S, nodes: Vec<SimNode>) -> Self {
+        Self {
+            strategy,
+            nodes,
+            arrival_interval_ns: Self::DEFAULT_ARRIVAL_INTERVAL.as_nanos() as u64,
+        }
+    }
+
+    /// Virtual time between two request arrivals.
+    pub fn with_arrival_interval(mut self, interval: Duration) -> Self {
+        self.arrival_interval_ns = interval.as_nanos().min(u64::MAX as u128) as u64;
+        self
+    }
+
+    /// Simulate `requests` arrivals on fresh nodes and wait for all of them to complete.
+    ///
+    /// Request `i` arrives at `i * arrival_interval` with `hash_key` `i`. Fails only if the
+    /// picker cannot be built; failed picks are counted in `failed_picks`.
+    pub fn run(&self, requests: usize) -> Result<SimResult, LoadBalanceError> {
+        let nodes: Vec<Arc<Node>> = self
+            .nodes
+            .iter()
+            .map(|n| Arc::new(Node::builder().id(n.id).weight(1).build()))
+            .collect();
+        let picker = self.strategy.build_picker(Arc::new(nodes.clone()))?;
+
+        let mut state: Vec<NodeState> = self
+            .nodes
+            .iter()
+            .map(|n| NodeState {
+                free_at: vec![0; n.capacity.max(1)],
+                queue_depth_sum: 0,
+                busy_ns: 0,
+                stats: SimNodeStats {
+                    id: n.id,
+                    ..Default::default()
+                },
+            })
+            .collect();
+        // Pending completions: (finish time, node index, arrival time)
+        let mut completions = BinaryHeap::new();
+        let mut failed_picks = 0;
+        let mut req = RequestMetadata::default();
+
+        for i in 0..requests {
+            let now = i as u64 * self.arrival_interval_ns;
+            complete_until(now, &mut completions, &nodes, &picker, &mut state);
+
+            req.hash_key = Some(i as u64);
+            let node = match picker.pick(&req) {
+                Ok(node) => node,
+                Err(_) => {
+                    failed_picks += 1;
+                    continue;
+                }
+            };
+            // Pickers only return nodes of the list they were built from
+            let Some(idx) = nodes.iter().position(|n| Arc::ptr_eq(n, &node)) else {
+                failed_picks += 1;
+                continue;
+            };
+
+            let sim = &self.nodes[idx];
+            let st = &mut state[idx];
+            let in_flight = node.in_flight.fetch_add(1, Ordering::Relaxed);
+            let queue_depth = in_flight.saturating_sub(st.free_at.len());
+            st.stats.picks += 1;
+            st.stats.max_queue_depth = st.stats.max_queue_depth.max(queue_depth);
+            st.queue_depth_sum += queue_depth;
+
+            // FIFO: the request starts on the server that frees up first
+            let server = (0..st.free_at.len())
+                .min_by_key(|&s| st.free_at[s])
+                .unwrap_or(0);
+            let finish = st.free_at[server].max(now) + sim.service_time_ns;
+            st.free_at[server] = finish;
+            st.busy_ns += sim.service_time_ns;
+            completions.push(Reverse((finish, idx, now)));
+        }
+        complete_until(u64::MAX, &mut completions, &nodes, &picker, &mut state);
+        let elapsed_ns = state
+            .iter()
+            .flat_map(|st| st.free_at.iter().copied())
+            .max()
+            .unwrap_or(0);
+
+        let nodes = state
+            .into_iter()
+            .map(|mut st| {
+                if st.stats.picks > 0 {
+                    st.stats.mean_queue_depth = st.queue_depth_sum as f64 / st.stats.picks as f64;
+                }
+                let capacity_ns = elapsed_ns as f64 * st.free_at.len() as f64;
+                if capacity_ns > 0.0 {
+                    st.stats.utilization = (st.busy_ns as f64 / capacity_ns).min(1.0);
+                }
+                st.stats.latencies_ns.sort_unstable();
+                st.stats
+            })
+            .collect();
+        Ok(SimResult {
+            nodes,
+            failed_picks,
+            elapsed_ns,
+        })
+    }
+}
+
+struct NodeState {
+    // Virtual time each server becomes idle
+    free_at: Vec<u64>,
+    queue_depth_sum: usize,
+    busy_ns: u64,
+    stats: SimNodeStats,
+}
+
+// Complete every request finishing at or before `now`
+fn complete_until(
+    now: u64,
+    completions: &mut BinaryHeap<Reverse<(u64, usize, u64)>>,
+    nodes: &[Arc<Node>],
+    picker: &Arc<dyn Picker>,
+    state: &mut [NodeState],
+) {
+    while let Some(&Reverse((finish, idx, arrival))) = completions.peek() {
+        if finish > now {
+            break;
+        }
+        completions.pop();
+
+        let node = &nodes[idx];
+        let latency = finish - arrival;
+        node.in_flight.fetch_sub(1, Ordering::Relaxed);
+        node.success.fetch_add(1, Ordering::Relaxed);
+        picker.on_response(node, Duration::from_nanos(latency));
+        state[idx].stats.latencies_ns.push(latency);
+    }
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "bench")]
+
+use std::sync::Arc;
+
+use volo_loadbalance::{bench::run_pick_bench, node::Node, strategy::RoundRobin};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin_bench() {
+        let nodes: Vec<_> = (0..4)
+            .map(|i| Arc::new(Node::builder().id(i).weight(1).build()))
+            .collect();
+
+        let result = run_pick_bench(&RoundRobin, Arc::new(nodes), 1000).unwrap();
+        assert_eq!(result.requests, 1000);
+        assert!(result.picks_per_sec > 0.0);
+        assert_eq!(result.distribution.len(), 4);
+        for count in result.distribution.values() {
+            assert_eq!(*count, 250);
+        }
+    }
+}
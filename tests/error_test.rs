@@ -13,6 +13,79 @@ mod tests {
         // Test MissingHashKey error
         let missing_key_error = LoadBalanceError::MissingHashKey;
         assert_eq!(format!("{}", missing_key_error), "hash key missing");
+
+        // Test WeightOverflow error
+        let overflow_error = LoadBalanceError::WeightOverflow {
+            node_id: 7,
+            weight: u32::MAX,
+        };
+        assert_eq!(
+            format!("{}", overflow_error),
+            "weight 4294967295 of node 7 overflows the supported range"
+        );
+
+        // Test WeightCountMismatch error
+        let mismatch_error = LoadBalanceError::WeightCountMismatch {
+            nodes: 3,
+            weights: 2,
+        };
+        assert_eq!(format!("{}", mismatch_error), "2 weights given for 3 nodes");
+
+        // Test InsufficientNodes error
+        let insufficient_error = LoadBalanceError::InsufficientNodes {
+            requested: 3,
+            available: 1,
+        };
+        assert_eq!(
+            format!("{}", insufficient_error),
+            "3 distinct nodes requested but only 1 available"
+        );
+
+        // Test ZeroVirtualFactor error
+        let vf_error = LoadBalanceError::ZeroVirtualFactor;
+        assert_eq!(format!("{}", vf_error), "virtual_factor must be at least 1");
+
+        // Test UnknownTenant error
+        let tenant_error = LoadBalanceError::UnknownTenant("acme".to_string());
+        assert_eq!(format!("{}", tenant_error), "unknown tenant: acme");
+
+        // Test Overloaded error
+        let overloaded_error = LoadBalanceError::Overloaded;
+        assert_eq!(
+            format!("{}", overloaded_error),
+            "every candidate node is at its rate limit or in-flight cap"
+        );
+
+        // Test InvalidWeights error
+        let invalid_weights_error = LoadBalanceError::InvalidWeights;
+        assert_eq!(
+            format!("{}", invalid_weights_error),
+            "no node has a positive weight"
+        );
+
+        // Test Timeout error
+        let timeout_error = LoadBalanceError::Timeout;
+        assert_eq!(
+            format!("{}", timeout_error),
+            "pick did not complete before its deadline"
+        );
+
+        // Test EmptyInstances error
+        let empty_error = LoadBalanceError::EmptyInstances {
+            service: "echo".to_string(),
+        };
+        assert_eq!(
+            format!("{}", empty_error),
+            "discovery returned no instances for service echo"
+        );
+
+        // Test NodeNotFound error
+        let not_found_error = LoadBalanceError::NodeNotFound(9);
+        assert_eq!(format!("{}", not_found_error), "node 9 not found");
+
+        // Test DuplicateNode error
+        let duplicate_error = LoadBalanceError::DuplicateNode(4);
+        assert_eq!(format!("{}", duplicate_error), "node 4 already exists");
     }
 
     #[test]
@@ -34,4 +107,56 @@ mod tests {
         fn assert_send_sync<T: Send + Sync>() {}
         assert_send_sync::<LoadBalanceError>();
     }
+
+    #[test]
+    fn test_custom_error() {
+        #[derive(Debug)]
+        struct QuotaExceeded(u32);
+
+        impl std::fmt::Display for QuotaExceeded {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "tenant quota of {} exceeded", self.0)
+            }
+        }
+
+        impl std::error::Error for QuotaExceeded {}
+
+        // Display delegates to the inner error, which can be downcast back
+        let err = LoadBalanceError::Custom(Box::new(QuotaExceeded(10)));
+        assert_eq!(err.to_string(), "tenant quota of 10 exceeded");
+        match &err {
+            LoadBalanceError::Custom(inner) => {
+                assert_eq!(inner.downcast_ref::<QuotaExceeded>().unwrap().0, 10);
+            }
+            other => panic!("unexpected error {other:?}"),
+        }
+
+        let err = LoadBalanceError::custom("quota exceeded");
+        assert_eq!(err.to_string(), "quota exceeded");
+        assert!(
+            matches!(&err, LoadBalanceError::Custom(inner) if inner.downcast_ref::<QuotaExceeded>().is_none())
+        );
+
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&err);
+    }
+
+    #[test]
+    fn test_error_is_non_exhaustive() {
+        // Downstream matches need a wildcard arm, so new variants are not breaking
+        fn is_retryable(err: &LoadBalanceError) -> bool {
+            match err {
+                LoadBalanceError::NoAvailableNodes
+                | LoadBalanceError::Overloaded
+                | LoadBalanceError::Timeout => true,
+                LoadBalanceError::MissingHashKey | LoadBalanceError::Custom(_) => false,
+                _ => false,
+            }
+        }
+        assert!(is_retryable(&LoadBalanceError::Overloaded));
+        assert!(!is_retryable(&LoadBalanceError::ZeroVirtualFactor));
+
+        fn assert_error<T: std::error::Error + Send + Sync + 'static>() {}
+        assert_error::<LoadBalanceError>();
+    }
 }
@@ -0,0 +1,150 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::Arc;
+
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use metrics_util::MetricKind;
+use volo_loadbalance::{
+    node::Node,
+    strategy::{BalanceStrategy, MeteredStrategy, RequestMetadata, RoundRobin},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_nodes(count: u64) -> Arc<Vec<Arc<Node>>> {
+        Arc::new(
+            (0..count)
+                .map(|i| Arc::new(Node::builder().id(i).weight(1).build()))
+                .collect(),
+        )
+    }
+
+    // Value of the metric `name` whose labels contain all of `labels`
+    fn metric_value(
+        snapshotter: &Snapshotter,
+        kind: MetricKind,
+        name: &str,
+        labels: &[(&str, &str)],
+    ) -> Option<DebugValue> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .find(|(key, _, _, _)| {
+                key.kind() == kind
+                    && key.key().name() == name
+                    && labels
+                        .iter()
+                        .all(|(k, v)| key.key().labels().any(|l| l.key() == *k && l.value() == *v))
+            })
+            .map(|(_, _, _, value)| value)
+    }
+
+    #[test]
+    fn test_metered_picks_counter() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let nodes = create_test_nodes(2);
+            nodes[0]
+                .in_flight
+                .store(3, std::sync::atomic::Ordering::Relaxed);
+            let picker = MeteredStrategy::new(RoundRobin)
+                .build_picker(nodes)
+                .unwrap();
+            for _ in 0..4 {
+                picker.pick(&RequestMetadata::default()).unwrap();
+            }
+        });
+
+        assert_eq!(
+            metric_value(
+                &snapshotter,
+                MetricKind::Counter,
+                "lb_picks_total",
+                &[("strategy", "round_robin"), ("endpoint", "0")]
+            ),
+            Some(DebugValue::Counter(2))
+        );
+        assert_eq!(
+            metric_value(
+                &snapshotter,
+                MetricKind::Counter,
+                "lb_picks_total",
+                &[("endpoint", "1")]
+            ),
+            Some(DebugValue::Counter(2))
+        );
+        assert!(matches!(
+            metric_value(
+                &snapshotter,
+                MetricKind::Gauge,
+                "lb_node_in_flight",
+                &[("endpoint", "0")]
+            ),
+            Some(DebugValue::Gauge(v)) if v.into_inner() == 3.0
+        ));
+    }
+
+    #[test]
+    fn test_metered_pick_errors_counter() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let picker = MeteredStrategy::new(RoundRobin)
+                .build_picker(create_test_nodes(0))
+                .unwrap();
+            assert!(picker.pick(&RequestMetadata::default()).is_err());
+        });
+
+        assert_eq!(
+            metric_value(
+                &snapshotter,
+                MetricKind::Counter,
+                "lb_pick_errors_total",
+                &[("reason", "no_available_nodes")]
+            ),
+            Some(DebugValue::Counter(1))
+        );
+    }
+
+    #[test]
+    fn test_metered_pick_batch_distinct() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let picker = MeteredStrategy::new(RoundRobin)
+                .build_picker(create_test_nodes(3))
+                .unwrap();
+            let req = RequestMetadata::default();
+            assert_eq!(picker.pick_batch_distinct(&req, 3).unwrap().len(), 3);
+            // RoundRobin's own batch leaves the cursor alone when it fails
+            assert!(picker.pick_batch_distinct(&req, 4).is_err());
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+        });
+
+        assert_eq!(
+            metric_value(
+                &snapshotter,
+                MetricKind::Counter,
+                "lb_picks_total",
+                &[("endpoint", "1")]
+            ),
+            Some(DebugValue::Counter(1))
+        );
+        assert_eq!(
+            metric_value(
+                &snapshotter,
+                MetricKind::Counter,
+                "lb_pick_errors_total",
+                &[("reason", "insufficient_nodes")]
+            ),
+            Some(DebugValue::Counter(1))
+        );
+    }
+}
@@ -1,5 +1,11 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
 use std::sync::Arc;
-use volo_loadbalance::node::{Endpoint, Node};
+use volo_loadbalance::node::{
+    dominant_zone, nodes_by_zone, Endpoint, EndpointAddress, Node, NodeBuilder, NodePool,
+    WindowedErrorRate, UNTAGGED_ZONE,
+};
 
 #[cfg(test)]
 mod tests {
@@ -9,17 +15,12 @@ mod tests {
     fn test_node_creation() {
         let endpoint = Endpoint {
             id: 1,
-            #[cfg(feature = "volo-adapter")]
-            address: "127.0.0.1:8080"
-                .parse::<std::net::SocketAddr>()
-                .unwrap()
-                .into(),
-            #[cfg(not(feature = "volo-adapter"))]
-            address: "127.0.0.1:8080".to_string(),
+            address: "127.0.0.1:8080".into(),
+            zone: None,
         };
-        let node = Node::new(endpoint, 10);
+        let node = NodeBuilder::default().endpoint(endpoint).weight(10).build();
 
-        assert_eq!(node.weight, 10);
+        assert_eq!(node.weight(), 10);
         assert_eq!(node.in_flight.load(std::sync::atomic::Ordering::Relaxed), 0);
         assert_eq!(node.success.load(std::sync::atomic::Ordering::Relaxed), 0);
         assert_eq!(node.fail.load(std::sync::atomic::Ordering::Relaxed), 0);
@@ -33,15 +34,10 @@ mod tests {
     fn test_node_atomic_operations() {
         let endpoint = Endpoint {
             id: 2,
-            #[cfg(feature = "volo-adapter")]
-            address: "127.0.0.1:8081"
-                .parse::<std::net::SocketAddr>()
-                .unwrap()
-                .into(),
-            #[cfg(not(feature = "volo-adapter"))]
-            address: "127.0.0.1:8081".to_string(),
+            address: "127.0.0.1:8081".into(),
+            zone: None,
         };
-        let node = Arc::new(Node::new(endpoint, 5));
+        let node = Arc::new(NodeBuilder::default().endpoint(endpoint).weight(5).build());
 
         // Test atomic increment operations
         node.in_flight
@@ -65,21 +61,705 @@ mod tests {
     fn test_node_clone() {
         let endpoint = Endpoint {
             id: 3,
-            #[cfg(feature = "volo-adapter")]
-            address: "127.0.0.1:8082"
-                .parse::<std::net::SocketAddr>()
-                .unwrap()
-                .into(),
-            #[cfg(not(feature = "volo-adapter"))]
-            address: "127.0.0.1:8082".to_string(),
+            address: "127.0.0.1:8082".into(),
+            zone: None,
         };
-        let node = Node::new(endpoint, 8);
+        let node = NodeBuilder::default().endpoint(endpoint).weight(8).build();
 
         // Test node can be safely cloned and shared
         let node_arc = Arc::new(node);
         let cloned_node = node_arc.clone();
 
-        assert_eq!(node_arc.weight, cloned_node.weight);
+        assert_eq!(node_arc.weight(), cloned_node.weight());
         assert_eq!(node_arc.endpoint.id, cloned_node.endpoint.id);
     }
+
+    #[test]
+    fn test_node_builder_defaults() {
+        let node = NodeBuilder::default().build();
+
+        assert_eq!(node.endpoint.id, 0);
+        assert_eq!(node.weight(), 0);
+        assert_eq!(node.max_in_flight, 0);
+        assert_eq!(node.priority, 0);
+        assert!(node.tags.is_empty());
+        assert_eq!(
+            node.last_rtt_ns.load(std::sync::atomic::Ordering::Relaxed),
+            0
+        );
+    }
+
+    #[test]
+    fn test_node_builder_all_fields() {
+        let endpoint = Endpoint {
+            id: 4,
+            address: "127.0.0.1:8083".into(),
+            zone: None,
+        };
+        let tags = HashMap::from([("zone".to_string(), "us-east-1a".to_string())]);
+        let node = NodeBuilder::default()
+            .endpoint(endpoint)
+            .weight(7)
+            .max_in_flight(64)
+            .initial_rtt_ns(5_000)
+            .tags(tags.clone())
+            .priority(2)
+            .build();
+
+        assert_eq!(node.endpoint.id, 4);
+        assert_eq!(node.weight(), 7);
+        assert_eq!(node.max_in_flight, 64);
+        assert_eq!(
+            node.last_rtt_ns.load(std::sync::atomic::Ordering::Relaxed),
+            5_000
+        );
+        assert_eq!(node.tags, tags);
+        assert_eq!(node.priority, 2);
+        assert_eq!(node.in_flight.load(std::sync::atomic::Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_clone_with_metadata_keeps_builder_fields() {
+        let node = NodeBuilder::default()
+            .weight(1)
+            .max_in_flight(8)
+            .priority(3)
+            .tags(HashMap::from([("tier".to_string(), "primary".to_string())]))
+            .build();
+        node.success
+            .fetch_add(2, std::sync::atomic::Ordering::Relaxed);
+
+        let cloned = node.clone_with_metadata(node.endpoint.clone(), 5);
+        assert_eq!(cloned.weight(), 5);
+        assert_eq!(cloned.max_in_flight, 8);
+        assert_eq!(cloned.priority, 3);
+        assert_eq!(cloned.tags, node.tags);
+        assert_eq!(cloned.success.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_node_new_shorthand() {
+        let node = Node::new(Endpoint::default(), 3);
+        assert_eq!(node.weight(), 3);
+        assert_eq!(node.max_in_flight, 0);
+    }
+
+    #[test]
+    fn test_node_builder_with_address() {
+        let addr: std::net::SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let node = Node::builder()
+            .id(9)
+            .address(addr)
+            .weight(3)
+            .tag("zone", "a")
+            .tag("tier", "primary")
+            .build();
+
+        assert_eq!(node.endpoint.id, 9);
+        assert_eq!(node.endpoint.address, EndpointAddress::Socket(addr));
+        assert_eq!(node.weight(), 3);
+        assert_eq!(node.tags.len(), 2);
+        assert_eq!(node.tags.get("zone").map(String::as_str), Some("a"));
+
+        let named = Node::builder().address("backend-9:8080").build();
+        assert_eq!(
+            named.endpoint.address,
+            EndpointAddress::Named("backend-9:8080".into())
+        );
+    }
+
+    #[test]
+    fn test_node_builder_id_overrides_endpoint() {
+        let node = Node::builder()
+            .id(5)
+            .endpoint(Endpoint {
+                id: 1,
+                ..Default::default()
+            })
+            .build();
+        assert_eq!(node.endpoint.id, 5);
+    }
+
+    #[test]
+    fn test_node_pool_recycles_released_nodes() {
+        let pool = NodePool::new(4);
+
+        let mut node = pool.acquire();
+        node.endpoint.id = 7;
+        *node.weight.get_mut() = 10;
+        node.tags.insert("zone".to_string(), "a".to_string());
+        node.fail.store(3, std::sync::atomic::Ordering::Relaxed);
+        let arc = node.into_arc();
+        let ptr = Arc::as_ptr(&arc);
+        assert!(pool.is_empty());
+
+        pool.release(arc);
+        assert_eq!(pool.len(), 1);
+
+        // The same allocation comes back, reset to defaults
+        let reused = pool.acquire();
+        assert_eq!(&*reused as *const Node, ptr);
+        assert_eq!(reused.endpoint.id, 0);
+        assert_eq!(reused.weight(), 0);
+        assert!(reused.tags.is_empty());
+        assert_eq!(reused.fail.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        // Dropping a pooled node returns it to the pool
+        drop(reused);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_node_pool_skips_referenced_nodes() {
+        let pool = NodePool::new(4);
+        let arc = pool.acquire().into_arc();
+        let still_used = arc.clone();
+        pool.release(arc);
+
+        // A node still referenced elsewhere is never handed out
+        let fresh = pool.acquire();
+        assert_ne!(&*fresh as *const Node, Arc::as_ptr(&still_used));
+        drop(fresh);
+        assert_eq!(pool.len(), 2);
+
+        drop(still_used);
+        let reused = pool.acquire();
+        assert_eq!(reused.endpoint.id, 0);
+    }
+
+    #[test]
+    fn test_node_pool_capacity() {
+        let pool = NodePool::new(2);
+        for _ in 0..5 {
+            pool.release(Arc::new(Node::builder().build()));
+        }
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.capacity(), 2);
+    }
+
+    #[test]
+    fn test_node_builder_zone() {
+        let node = Node::builder().id(1).zone("us-east-1a").build();
+        assert_eq!(node.endpoint.zone.as_deref(), Some("us-east-1a"));
+        assert_eq!(Node::builder().build().endpoint.zone, None);
+    }
+
+    #[test]
+    fn test_node_success_rate_and_health_score() {
+        use std::sync::atomic::Ordering;
+
+        // No requests yet: neutral, fully healthy
+        let node = Node::builder().id(1).build();
+        assert_eq!(node.total_requests(), 0);
+        assert_eq!(node.success_rate(), 1.0);
+        assert_eq!(node.health_score(), 1.0);
+
+        // All success
+        node.success.store(10, Ordering::Relaxed);
+        assert_eq!(node.total_requests(), 10);
+        assert_eq!(node.success_rate(), 1.0);
+        assert_eq!(node.health_score(), 1.0);
+
+        // Slow nodes score lower: 100ms halves the score
+        node.last_rtt_ns.store(100_000_000, Ordering::Relaxed);
+        assert!((node.health_score() - 0.5).abs() < 1e-9);
+
+        // Mixed
+        node.fail.store(30, Ordering::Relaxed);
+        assert_eq!(node.total_requests(), 40);
+        assert!((node.success_rate() - 0.25).abs() < 1e-9);
+        assert!((node.health_score() - 0.125).abs() < 1e-9);
+
+        // All fail
+        let node = Node::builder().id(2).initial_rtt_ns(1_000).build();
+        node.fail.store(5, Ordering::Relaxed);
+        assert_eq!(node.success_rate(), 0.0);
+        assert_eq!(node.health_score(), 0.0);
+    }
+
+    #[test]
+    fn test_node_error_rate() {
+        use std::sync::atomic::Ordering;
+
+        let node = Node::builder().id(1).build();
+        assert_eq!(node.error_rate(), 0.0);
+        assert_eq!(node.total_requests(), 0);
+
+        node.fail.store(4, Ordering::Relaxed);
+        assert_eq!(node.error_rate(), 1.0);
+
+        node.success.store(12, Ordering::Relaxed);
+        assert_eq!(node.total_requests(), 16);
+        assert!((node.error_rate() - 0.25).abs() < 1e-9);
+        assert!((node.error_rate() + node.success_rate() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_node_reset_stats() {
+        use std::sync::atomic::Ordering;
+
+        let node = Node::builder().id(1).weight(7).initial_rtt_ns(500).build();
+        node.in_flight.store(3, Ordering::Relaxed);
+        node.success.store(10, Ordering::Relaxed);
+        node.fail.store(2, Ordering::Relaxed);
+
+        node.reset_stats();
+        assert_eq!(node.in_flight.load(Ordering::Relaxed), 0);
+        assert_eq!(node.success.load(Ordering::Relaxed), 0);
+        assert_eq!(node.fail.load(Ordering::Relaxed), 0);
+        assert_eq!(node.last_rtt_ns.load(Ordering::Relaxed), 0);
+        // Configuration is kept
+        assert_eq!(node.endpoint.id, 1);
+        assert_eq!(node.weight(), 7);
+    }
+
+    #[test]
+    fn test_node_rate_limit_tokens() {
+        let unlimited = Node::builder().id(1).build();
+        assert!(unlimited.rate_limit.is_none());
+        assert!((0..1000).all(|_| unlimited.try_acquire_token()));
+
+        let blocked = Node::builder().id(2).rate_limit(0).build();
+        assert!(!blocked.try_acquire_token());
+
+        // A full bucket holds one second of tokens
+        let node = Node::builder().id(3).rate_limit(100).build();
+        let burst = (0..200).filter(|_| node.try_acquire_token()).count();
+        assert!((100..=102).contains(&burst), "burst of {burst}");
+
+        // 100 rps refills about ten tokens in 100ms
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let refilled = (0..200).filter(|_| node.try_acquire_token()).count();
+        assert!((9..=15).contains(&refilled), "refilled {refilled}");
+
+        // Cloning keeps the limit with a fresh bucket
+        let cloned = node.clone_with_metadata(Endpoint::default(), 1);
+        assert_eq!(cloned.rate_limit, Some(100));
+        assert!(cloned.try_acquire_token());
+    }
+
+    #[test]
+    fn test_endpoint_identity_by_id() {
+        use std::collections::HashSet;
+
+        let endpoint = |id, addr: &str| Endpoint {
+            id,
+            address: addr.into(),
+            zone: None,
+        };
+        let a = endpoint(1, "10.0.0.1:80");
+        let b = endpoint(1, "10.0.0.2:8080");
+        let c = endpoint(2, "10.0.0.1:80");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let endpoints: HashSet<_> = [a, b, c].into_iter().collect();
+        assert_eq!(endpoints.len(), 2);
+
+        // Nodes deduplicate by endpoint id as well, whatever their weight. The atomics
+        // do not take part in hashing, so clippy's interior mutability lint is moot here.
+        #[allow(clippy::mutable_key_type)]
+        let nodes: HashSet<Arc<Node>> = [
+            Arc::new(Node::builder().id(1).weight(5).build()),
+            Arc::new(Node::builder().id(1).weight(9).build()),
+            Arc::new(Node::builder().id(3).build()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.contains(&Arc::new(Node::builder().id(3).build())));
+    }
+
+    #[test]
+    fn test_endpoint_address_parsing() {
+        use std::net::{SocketAddr, ToSocketAddrs};
+
+        // Socket strings parse into `Socket`
+        let socket: EndpointAddress = "10.0.0.1:8080".parse().unwrap();
+        let expected: SocketAddr = "10.0.0.1:8080".parse().unwrap();
+        assert_eq!(socket, EndpointAddress::Socket(expected));
+        assert_eq!(socket.as_socket_addr(), Some(expected));
+        assert_eq!(socket.to_string(), "10.0.0.1:8080");
+        assert_eq!(
+            socket.to_socket_addrs().unwrap().collect::<Vec<_>>(),
+            [expected]
+        );
+        let v6 = EndpointAddress::from("[::1]:53".to_string());
+        assert!(matches!(v6, EndpointAddress::Socket(addr) if addr.is_ipv6()));
+
+        // Anything else stays `Named` and resolves on demand
+        let named: EndpointAddress = "localhost:9000".parse().unwrap();
+        assert_eq!(named, EndpointAddress::Named("localhost:9000".into()));
+        assert_eq!(named.as_socket_addr(), None);
+        assert_eq!(named.to_string(), "localhost:9000");
+        let resolved: Vec<_> = named.to_socket_addrs().unwrap().collect();
+        assert!(!resolved.is_empty());
+        assert!(resolved
+            .iter()
+            .all(|a| a.port() == 9000 && a.ip().is_loopback()));
+
+        // A name without a port cannot be resolved
+        assert!(EndpointAddress::from("backend").to_socket_addrs().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "volo-adapter")]
+    fn test_endpoint_address_volo_conversion() {
+        let addr: std::net::SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let endpoint = EndpointAddress::from(volo::net::Address::from(addr));
+        assert_eq!(endpoint, EndpointAddress::Socket(addr));
+        assert_eq!(
+            volo::net::Address::try_from(&endpoint).unwrap(),
+            volo::net::Address::from(addr)
+        );
+
+        #[cfg(unix)]
+        {
+            let uds = std::os::unix::net::SocketAddr::from_pathname("/tmp/lb.sock").unwrap();
+            let endpoint = EndpointAddress::from(volo::net::Address::Unix(uds));
+            assert_eq!(endpoint.to_string(), "unix:/tmp/lb.sock");
+            assert!(matches!(
+                volo::net::Address::try_from(&endpoint),
+                Ok(volo::net::Address::Unix(_))
+            ));
+        }
+
+        assert!(volo::net::Address::try_from(&EndpointAddress::from("backend:80")).is_err());
+    }
+
+    #[test]
+    fn test_node_meta_round_trip() {
+        use volo_loadbalance::config::{BalanceConfig, NodeMeta};
+
+        let endpoint = Endpoint {
+            id: 4,
+            address: "127.0.0.1:8084".into(),
+            zone: None,
+        };
+        let node = Node::from((&endpoint, NodeMeta { weight: 30 }));
+        assert_eq!(node.endpoint.id, 4);
+        assert_eq!(node.endpoint.address, endpoint.address);
+        assert_eq!(node.weight(), 30);
+
+        let meta = NodeMeta::from(&node);
+        assert_eq!(meta.weight, 30);
+        assert_eq!(Node::from((&endpoint, meta)).weight(), 30);
+
+        // A zero weight is kept as is, or picks up the configured default
+        node.set_weight(0);
+        assert_eq!(NodeMeta::from(&node).weight, 0);
+        let config = BalanceConfig {
+            default_weight: 25,
+            ..Default::default()
+        };
+        assert_eq!(NodeMeta::from_node(&node, &config).weight, 25);
+        node.set_weight(30);
+        assert_eq!(NodeMeta::from_node(&node, &config).weight, 30);
+    }
+
+    #[test]
+    fn test_named_address_clones_share_buffer() {
+        let endpoint = Endpoint {
+            id: 1,
+            address: "backend-1.internal:8080".into(),
+            zone: None,
+        };
+        let EndpointAddress::Named(name) = &endpoint.address else {
+            panic!("expected a named address");
+        };
+
+        let clones: Vec<Endpoint> = (0..1000).map(|_| endpoint.clone()).collect();
+        for clone in &clones {
+            let EndpointAddress::Named(cloned) = &clone.address else {
+                panic!("expected a named address");
+            };
+            assert!(Arc::ptr_eq(name, cloned));
+        }
+    }
+
+    #[test]
+    fn test_is_slow() {
+        let node = Node::builder().id(1).build();
+        // No RTT reported yet
+        assert!(!node.is_slow(0));
+
+        node.last_rtt_ns
+            .store(2_000, std::sync::atomic::Ordering::Relaxed);
+        assert!(node.is_slow(1_999));
+        assert!(!node.is_slow(2_000));
+    }
+
+    #[test]
+    fn test_node_enabled() {
+        let node = Node::builder().id(1).weight(10).build();
+        assert!(node.is_enabled());
+
+        node.set_enabled(false);
+        assert!(!node.is_enabled());
+        let endpoint = node.endpoint.clone();
+        assert!(!node.clone_with_metadata(endpoint, 20).is_enabled());
+
+        node.set_enabled(true);
+        assert!(node.is_enabled());
+    }
+
+    #[test]
+    fn test_record_rtt_stamps_update_time() {
+        let node = Node::builder().id(1).initial_rtt_ns(5_000).build();
+        assert_eq!(node.rtt_staleness(u64::MAX), None);
+
+        node.record_rtt(7_000);
+        assert_eq!(
+            node.last_rtt_ns.load(std::sync::atomic::Ordering::Relaxed),
+            7_000
+        );
+        let at = node
+            .last_rtt_updated_at
+            .load(std::sync::atomic::Ordering::Relaxed);
+        assert!(at > 0);
+        assert_eq!(
+            node.rtt_staleness(at + 2_000_000_000),
+            Some(std::time::Duration::from_secs(2))
+        );
+
+        node.reset_stats();
+        assert_eq!(node.rtt_staleness(u64::MAX), None);
+    }
+
+    fn zoned(zones: &[Option<&str>]) -> Vec<Arc<Node>> {
+        zones
+            .iter()
+            .enumerate()
+            .map(|(i, zone)| {
+                let builder = Node::builder().id(i as u64);
+                let builder = match zone {
+                    Some(zone) => builder.tag("az", *zone),
+                    None => builder,
+                };
+                Arc::new(builder.build())
+            })
+            .collect()
+    }
+
+    fn ids(nodes: &[Arc<Node>]) -> Vec<u64> {
+        nodes.iter().map(|n| n.endpoint.id).collect()
+    }
+
+    #[test]
+    fn test_nodes_by_zone_groups_by_tag() {
+        let nodes = zoned(&[Some("a"), Some("b"), None, Some("a"), Some("b"), Some("b")]);
+        let zones = nodes_by_zone(&nodes, "az");
+
+        assert_eq!(zones.len(), 3);
+        assert_eq!(ids(&zones["a"]), [0, 3]);
+        assert_eq!(ids(&zones["b"]), [1, 4, 5]);
+        assert_eq!(ids(&zones[UNTAGGED_ZONE]), [2]);
+        assert_eq!(dominant_zone(&nodes, "az").as_deref(), Some("b"));
+
+        // Ties go to the smallest zone name
+        let tied = zoned(&[Some("b"), Some("a")]);
+        assert_eq!(dominant_zone(&tied, "az").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_nodes_by_zone_untagged_and_small_inputs() {
+        let untagged = zoned(&[None, None]);
+        let zones = nodes_by_zone(&untagged, "az");
+        assert_eq!(zones.len(), 1);
+        assert_eq!(ids(&zones[UNTAGGED_ZONE]), [0, 1]);
+        assert_eq!(dominant_zone(&untagged, "az"), None);
+
+        let single = zoned(&[Some("a")]);
+        assert_eq!(ids(&nodes_by_zone(&single, "az")["a"]), [0]);
+        assert_eq!(dominant_zone(&single, "az").as_deref(), Some("a"));
+
+        assert!(nodes_by_zone(&[], "az").is_empty());
+        assert_eq!(dominant_zone(&[], "az"), None);
+    }
+
+    #[test]
+    fn test_windowed_error_rate_excludes_live_bucket() {
+        let window = WindowedErrorRate::new(4, std::time::Duration::from_millis(100));
+        assert_eq!(window.error_rate_at(0), 0.0);
+
+        // Bucket 0: 3 successes, 1 failure
+        for _ in 0..3 {
+            window.record_at(true, 10);
+        }
+        window.record_at(false, 50);
+        // Still the live bucket
+        assert_eq!(window.error_rate_at(99), 0.0);
+        assert_eq!(window.error_rate_at(100), 0.25);
+
+        // Bucket 1: 4 failures, live until 200
+        for _ in 0..4 {
+            window.record_at(false, 150);
+        }
+        assert_eq!(window.error_rate_at(199), 0.25);
+        assert_eq!(window.error_rate_at(200), 5.0 / 8.0);
+    }
+
+    #[test]
+    fn test_windowed_error_rate_rotates_buckets() {
+        let window = WindowedErrorRate::new(4, std::time::Duration::from_millis(100));
+        window.record_at(false, 0);
+        window.record_at(true, 100);
+
+        // The window covers the 3 buckets before the live one
+        assert_eq!(window.error_rate_at(300), 0.5);
+        assert_eq!(window.error_rate_at(400), 0.0);
+        assert_eq!(window.error_rate_at(500), 0.0);
+
+        // Bucket 4 reuses the slot of bucket 0 and starts from zero
+        window.record_at(true, 400);
+        assert_eq!(window.error_rate_at(500), 0.0);
+        window.record_at(false, 450);
+        assert_eq!(window.error_rate_at(500), 0.5);
+
+        // Results older than the reused slot are dropped
+        window.record_at(false, 0);
+        assert_eq!(window.error_rate_at(500), 0.5);
+
+        window.clear();
+        assert_eq!(window.error_rate_at(500), 0.0);
+    }
+
+    #[test]
+    fn test_node_error_window() {
+        let plain = Node::builder().id(1).build();
+        plain.record_result(false);
+        assert_eq!(plain.fail.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert_eq!(plain.windowed_error_rate(), None);
+
+        let node = Node::builder()
+            .id(2)
+            .error_window(60, std::time::Duration::from_secs(1))
+            .build();
+        node.record_result(true);
+        node.record_result(false);
+        assert_eq!(node.total_requests(), 2);
+        // Both results are in the live bucket
+        assert_eq!(node.windowed_error_rate(), Some(0.0));
+    }
+
+    #[test]
+    #[cfg(feature = "rtt-histogram")]
+    fn test_rtt_percentiles() {
+        let node = Node::builder().id(1).build();
+        assert_eq!(node.rtt_percentile(0.5), None);
+
+        // 1µs..=100µs in 1µs steps
+        for i in 1..=100u64 {
+            node.record_rtt(i * 1_000);
+        }
+        assert_eq!(node.rtt_histogram.len(), 100);
+        let within_bucket = |q: f64, exact: u64| {
+            let p = node.rtt_percentile(q).unwrap();
+            assert!(
+                p >= exact && p as f64 <= exact as f64 * 1.125,
+                "p{q} = {p}, recorded {exact}"
+            );
+        };
+        within_bucket(0.5, 50_000);
+        within_bucket(0.99, 99_000);
+        within_bucket(1.0, 100_000);
+        within_bucket(0.0, 1_000);
+
+        // A slow tail moves p99 but not p50
+        for _ in 0..5 {
+            node.record_rtt(1_000_000_000);
+        }
+        within_bucket(0.5, 53_000);
+        within_bucket(0.99, 1_000_000_000);
+
+        // Small and extreme values stay exact or in range
+        let edges = Node::builder().id(2).build();
+        edges.record_rtt(3);
+        assert_eq!(edges.rtt_percentile(1.0), Some(3));
+        edges.record_rtt(u64::MAX);
+        assert_eq!(edges.rtt_percentile(1.0), Some(u64::MAX));
+
+        node.reset_stats();
+        assert_eq!(node.rtt_percentile(0.5), None);
+    }
+
+    #[test]
+    fn test_display_format() {
+        let node = Node::builder()
+            .id(7)
+            .address("10.0.0.7:8080")
+            .weight(5)
+            .build();
+        node.in_flight
+            .store(2, std::sync::atomic::Ordering::Relaxed);
+        node.success.store(40, std::sync::atomic::Ordering::Relaxed);
+        node.fail.store(3, std::sync::atomic::Ordering::Relaxed);
+        node.record_rtt(1_500_000);
+
+        // The exact strings are part of the log output, keep them stable
+        assert_eq!(node.endpoint.to_string(), "Endpoint(7@10.0.0.7:8080)");
+        assert_eq!(
+            node.to_string(),
+            "Node(id=7, addr=10.0.0.7:8080, weight=5, in_flight=2, success=40, fail=3, rtt=1500000ns)"
+        );
+
+        let named = Node::builder().id(1).address("backend-1:80").build();
+        assert_eq!(named.endpoint.to_string(), "Endpoint(1@backend-1:80)");
+        assert!(named
+            .to_string()
+            .starts_with("Node(id=1, addr=backend-1:80, weight="));
+    }
+
+    #[test]
+    fn test_try_acquire_at_boundary() {
+        let node = Arc::new(Node::builder().id(1).max_concurrency(2).build());
+        let in_flight = || node.in_flight.load(std::sync::atomic::Ordering::Relaxed);
+
+        let first = node.try_acquire().unwrap();
+        let second = node.try_acquire().unwrap();
+        assert_eq!(in_flight(), 2);
+        assert!(node.try_acquire().is_none());
+        assert_eq!(in_flight(), 2);
+
+        // Dropping a guard frees its slot for the next caller
+        drop(first);
+        assert_eq!(in_flight(), 1);
+        let third = node.try_acquire().unwrap();
+        assert!(Arc::ptr_eq(third.node(), &node));
+        drop((second, third));
+        assert_eq!(in_flight(), 0);
+
+        // A reset while guards are held does not wrap the counter
+        let guard = node.try_acquire().unwrap();
+        node.reset_stats();
+        drop(guard);
+        assert_eq!(in_flight(), 0);
+
+        let unlimited = Arc::new(Node::builder().id(2).build());
+        let guards: Vec<_> = (0..100).filter_map(|_| unlimited.try_acquire()).collect();
+        assert_eq!(guards.len(), 100);
+    }
+
+    #[test]
+    fn test_utilization() {
+        let node = Node::builder().id(1).max_in_flight(4).build();
+        let set = |n: usize| {
+            node.in_flight
+                .store(n, std::sync::atomic::Ordering::Relaxed)
+        };
+
+        assert_eq!(node.utilization(), 0.0);
+        set(2);
+        assert_eq!(node.utilization(), 0.5);
+        set(4);
+        assert_eq!(node.utilization(), 1.0);
+
+        // Without a cap utilization is unknown
+        let uncapped = Node::builder().id(2).build();
+        uncapped
+            .in_flight
+            .store(50, std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(uncapped.utilization(), 0.0);
+    }
 }
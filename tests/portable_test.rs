@@ -0,0 +1,77 @@
+use volo_loadbalance::{
+    error::LoadBalanceError,
+    portable::{ConsistentHash, PortablePicker, Rendezvous, RoundRobin},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_round_robin() {
+        let picker = RoundRobin::new(vec![10, 20, 30]);
+        let picks: Vec<u64> = (0..6).map(|_| picker.pick(None).unwrap()).collect();
+        assert_eq!(picks, vec![10, 20, 30, 10, 20, 30]);
+
+        let empty = RoundRobin::new(Vec::new());
+        assert!(matches!(
+            empty.pick(None),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_portable_consistent_hash() {
+        let ids: Vec<u64> = (0..5).collect();
+        let picker = ConsistentHash::new(&ids, 64);
+
+        // Stable for the same key, and spreads keys over all nodes
+        let mut seen = [false; 5];
+        for key in 0..200u64 {
+            let id = picker.pick(Some(key)).unwrap();
+            assert_eq!(picker.pick(Some(key)).unwrap(), id);
+            seen[id as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+
+        // Removing a node only remaps the keys that were on it
+        let smaller = ConsistentHash::new(&ids[..4], 64);
+        for key in 0..200u64 {
+            let before = picker.pick(Some(key)).unwrap();
+            if before != 4 {
+                assert_eq!(smaller.pick(Some(key)).unwrap(), before);
+            }
+        }
+
+        assert!(matches!(
+            picker.pick(None),
+            Err(LoadBalanceError::MissingHashKey)
+        ));
+        assert!(matches!(
+            ConsistentHash::new(&[], 64).pick(Some(1)),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_portable_rendezvous() {
+        let picker = Rendezvous::new((0..5).collect());
+        let smaller = Rendezvous::new((0..4).collect());
+
+        let mut seen = [false; 5];
+        for key in 0..200u64 {
+            let id = picker.pick(Some(key)).unwrap();
+            assert_eq!(picker.pick(Some(key)).unwrap(), id);
+            seen[id as usize] = true;
+            if id != 4 {
+                assert_eq!(smaller.pick(Some(key)).unwrap(), id);
+            }
+        }
+        assert!(seen.iter().all(|&s| s));
+
+        assert!(matches!(
+            picker.pick(None),
+            Err(LoadBalanceError::MissingHashKey)
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use proptest::prelude::*;
+use volo_loadbalance::{
+    error::LoadBalanceError,
+    node::Node,
+    strategy::{
+        BalanceStrategy, CircuitBreaker, ConsistentHash, LeastConnection, LeastConnectionWeighted,
+        PeakEwma, PowerOfTwoChoices, RequestMetadata, ResponseTimeWeighted, RoundRobin,
+        WeightedRandom, WeightedRoundRobin,
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes_with_weights(weights: &[u32]) -> Arc<Vec<Arc<Node>>> {
+        Arc::new(
+            weights
+                .iter()
+                .enumerate()
+                .map(|(i, &w)| Arc::new(Node::builder().id(i as u64).weight(w).build()))
+                .collect(),
+        )
+    }
+
+    fn keyed(key: u64) -> RequestMetadata {
+        RequestMetadata {
+            hash_key: Some(key),
+            ..Default::default()
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn round_robin_visits_every_node_per_cycle(
+            count in 1usize..=100,
+            cycles in 1usize..=5,
+        ) {
+            let picker = RoundRobin
+                .build_picker(nodes_with_weights(&vec![1; count]))
+                .unwrap();
+            let req = RequestMetadata::default();
+
+            for _ in 0..cycles {
+                let mut seen = vec![false; count];
+                for _ in 0..count {
+                    seen[picker.pick(&req).unwrap().endpoint.id as usize] = true;
+                }
+                prop_assert!(seen.iter().all(|&s| s));
+            }
+        }
+
+        #[test]
+        fn weighted_random_within_three_times_expected(
+            weights in prop::collection::vec(0u32..=1000, 1..=100),
+            requests in 10usize..=10_000,
+        ) {
+            let total: u64 = weights.iter().map(|&w| w as u64).sum();
+            prop_assume!(total > 0);
+
+            let picker = WeightedRandom
+                .build_picker(nodes_with_weights(&weights))
+                .unwrap();
+            let req = RequestMetadata::default();
+            let mut counts = vec![0usize; weights.len()];
+            for _ in 0..requests {
+                counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+            }
+
+            for (&w, &c) in weights.iter().zip(&counts) {
+                if w == 0 {
+                    prop_assert_eq!(c, 0);
+                    continue;
+                }
+                let expected = requests as f64 * w as f64 / total as f64;
+                // Too few expected picks to say anything about the lower bound
+                if expected >= 50.0 {
+                    prop_assert!(c as f64 >= expected / 3.0, "weight {} got {} of {}", w, c, expected);
+                }
+                prop_assert!(c as f64 <= expected * 3.0 + 10.0, "weight {} got {} of {}", w, c, expected);
+            }
+        }
+
+        #[test]
+        fn consistent_hash_ignores_pick_order(
+            weights in prop::collection::vec(0u32..=1000, 1..=100),
+            keys in prop::collection::vec(any::<u64>(), 1..=200),
+        ) {
+            let nodes = nodes_with_weights(&weights);
+            let strategy = ConsistentHash::default();
+            let picker = strategy.build_picker(nodes.clone()).unwrap();
+
+            let forward: HashMap<u64, u64> = keys
+                .iter()
+                .map(|&k| (k, picker.pick(&keyed(k)).unwrap().endpoint.id))
+                .collect();
+
+            // Reverse order on the same picker, then on a freshly built one
+            for &k in keys.iter().rev() {
+                prop_assert_eq!(picker.pick(&keyed(k)).unwrap().endpoint.id, forward[&k]);
+            }
+            let rebuilt = strategy.build_picker(nodes).unwrap();
+            for &k in keys.iter().rev() {
+                prop_assert_eq!(rebuilt.pick(&keyed(k)).unwrap().endpoint.id, forward[&k]);
+            }
+        }
+
+        #[test]
+        fn least_connection_picks_minimum_in_flight(
+            loads in prop::collection::vec(0usize..=50, 1..=100),
+        ) {
+            let nodes = nodes_with_weights(&vec![1; loads.len()]);
+            for (node, &load) in nodes.iter().zip(&loads) {
+                node.in_flight.store(load, Ordering::Relaxed);
+            }
+            let picker = LeastConnection.build_picker(nodes).unwrap();
+
+            let picked = picker.pick(&RequestMetadata::default()).unwrap();
+            let min = *loads.iter().min().unwrap();
+            prop_assert_eq!(picked.in_flight.load(Ordering::Relaxed), min);
+        }
+    }
+
+    #[test]
+    fn all_strategies_fail_on_empty_input() {
+        let strategies: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("rr", Box::new(RoundRobin)),
+            ("wrr", Box::new(WeightedRoundRobin)),
+            ("p2c", Box::new(PowerOfTwoChoices)),
+            ("wr", Box::new(WeightedRandom)),
+            ("lc", Box::new(LeastConnection)),
+            ("lcw", Box::new(LeastConnectionWeighted)),
+            ("rtw", Box::new(ResponseTimeWeighted)),
+            ("peak_ewma", Box::new(PeakEwma::default())),
+            ("ch", Box::new(ConsistentHash::default())),
+            ("circuit_breaker", Box::new(CircuitBreaker::new(RoundRobin))),
+        ];
+
+        for (name, strategy) in strategies {
+            let picker = strategy.build_picker(Arc::new(Vec::new())).unwrap();
+            assert!(
+                matches!(
+                    picker.pick(&keyed(1)),
+                    Err(LoadBalanceError::NoAvailableNodes)
+                ),
+                "{name} did not fail on empty input"
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "std")]
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use volo_loadbalance::{
+    error::LoadBalanceError,
+    node::Node,
+    strategy::{
+        sim::{SimNode, Simulator},
+        BalanceStrategy, LeastConnection, Picker, RequestMetadata, RoundRobin,
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sim_node(id: u64, service_ms: u64) -> SimNode {
+        SimNode {
+            id,
+            service_time_ns: service_ms * 1_000_000,
+            capacity: 1,
+        }
+    }
+
+    #[test]
+    fn test_sim_round_robin_equal_nodes() {
+        let sim = Simulator::new(RoundRobin, vec![sim_node(0, 1), sim_node(1, 1)]);
+        let result = sim.run(1000).unwrap();
+
+        assert_eq!(result.failed_picks, 0);
+        for stats in &result.nodes {
+            assert_eq!(stats.picks, 500);
+            assert_eq!(stats.latencies_ns.len(), 500);
+            // Each node is idle every other millisecond and never queues
+            assert_eq!(stats.max_queue_depth, 0);
+            assert_eq!(stats.latency_percentile(0.99), 1_000_000);
+            assert!((stats.utilization - 0.5).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_sim_slow_node_queues() {
+        let nodes = vec![sim_node(0, 1), sim_node(1, 5)];
+
+        // Round robin keeps feeding the slow node, which falls behind
+        let rr = Simulator::new(RoundRobin, nodes.clone()).run(200).unwrap();
+        let slow = rr.node(1).unwrap();
+        assert_eq!(slow.picks, 100);
+        assert!(slow.max_queue_depth > 10);
+        assert!(slow.latency_percentile(0.99) > 100_000_000);
+        assert!(slow.utilization > 0.95);
+
+        // Least connection steers around the queue
+        let lc = Simulator::new(LeastConnection, nodes).run(200).unwrap();
+        let lc_slow = lc.node(1).unwrap();
+        assert!(lc_slow.picks < slow.picks);
+        assert!(lc_slow.max_queue_depth <= 1);
+        assert!(lc.elapsed_ns < rr.elapsed_ns);
+    }
+
+    #[test]
+    fn test_sim_is_deterministic() {
+        let sim = Simulator::new(LeastConnection, vec![sim_node(0, 2), sim_node(1, 3)])
+            .with_arrival_interval(Duration::from_micros(700));
+        let first = sim.run(500).unwrap();
+        let second = sim.run(500).unwrap();
+
+        assert_eq!(first.elapsed_ns, second.elapsed_ns);
+        for (a, b) in first.nodes.iter().zip(&second.nodes) {
+            assert_eq!(a.picks, b.picks);
+            assert_eq!(a.latencies_ns, b.latencies_ns);
+        }
+    }
+
+    #[test]
+    fn test_sim_empty_nodes_fail_picks() {
+        let result = Simulator::new(RoundRobin, Vec::new()).run(10).unwrap();
+        assert_eq!(result.failed_picks, 10);
+        assert!(result.nodes.is_empty());
+        assert_eq!(result.elapsed_ns, 0);
+    }
+
+    // Round robin whose response hook only counts, keeping the nodes for inspection
+    #[derive(Clone, Default)]
+    struct CountingStrategy {
+        nodes: Arc<Mutex<Vec<Arc<Node>>>>,
+        responses: Arc<AtomicUsize>,
+    }
+
+    struct CountingPicker {
+        inner: Arc<dyn Picker>,
+        responses: Arc<AtomicUsize>,
+    }
+
+    impl BalanceStrategy for CountingStrategy {
+        fn name(&self) -> Cow<'static, str> {
+            "counting".into()
+        }
+
+        fn build_picker(
+            &self,
+            nodes: Arc<Vec<Arc<Node>>>,
+        ) -> Result<Arc<dyn Picker>, LoadBalanceError> {
+            *self.nodes.lock().unwrap() = nodes.to_vec();
+            Ok(Arc::new(CountingPicker {
+                inner: RoundRobin.build_picker(nodes)?,
+                responses: self.responses.clone(),
+            }))
+        }
+    }
+
+    impl Picker for CountingPicker {
+        fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+            self.inner.pick(req)
+        }
+
+        fn on_response(&self, _node: &Node, _rtt: Duration) {
+            self.responses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_sim_leaves_rtt_recording_to_picker() {
+        let strategy = CountingStrategy::default();
+        Simulator::new(strategy.clone(), vec![sim_node(0, 1)])
+            .run(10)
+            .unwrap();
+
+        // Every completion goes through the hook, nothing is recorded behind its back
+        assert_eq!(strategy.responses.load(Ordering::Relaxed), 10);
+        let nodes = strategy.nodes.lock().unwrap().clone();
+        assert_eq!(nodes[0].last_rtt_ns.load(Ordering::Relaxed), 0);
+    }
+}
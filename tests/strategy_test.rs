@@ -1,19 +1,33 @@
+#![cfg(feature = "std")]
+
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use volo_loadbalance::{
+    config::BalanceConfig,
     error::LoadBalanceError,
     node::Node,
     strategy::{
-        BalanceStrategy, BaseBalancer, ConsistentHash, LeastConnection, PowerOfTwoChoices,
-        RequestMetadata, ResponseTimeWeighted, RoundRobin, WeightedRandom, WeightedRoundRobin,
+        BalanceStrategy, BaseBalancer, CachingBalancer, CapacityLimited, CircuitBreaker,
+        CircuitBreakerConfig, CircuitState, ConcurrencyLimited, ConsistentHash, DeadlineAware,
+        DynamicWeightStrategy, EqualRoundRobin, FaultTolerant, FractionalWRR, GradientDescent,
+        HealthFilter, HotStandby, LeastConnection, LeastConnectionWeighted, LeastLoad,
+        MultiTenantBalancer, NodeChange, NodeDiff, PeakEwma, Picker, PowerOfKChoices,
+        PowerOfTwoChoices, RandomSubset, RateAwarePicker, RequestMetadata, ResponseTimeWeighted,
+        RetryExhaustion, RetryPolicy, RetryableBalancer, RoundRobin, RoundRobinWithWeightedJump,
+        SeededPowerOfTwoChoices, Shadow, StaticWeightSnapshot, Sticky, StickyFailover,
+        StrategyCompose, Tiered, TimeoutAwarePicker, TopologySpread, VirtualNodePolicy,
+        WeightDecay, WeightedRandom, WeightedRandomConfig, WeightedReservoir, WeightedRoundRobin,
+        ZoneAware,
     },
 };
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use volo_loadbalance::node::Endpoint;
+    use volo_loadbalance::node::{Endpoint, NodeBuilder, NodePool, NodeStatsSnapshot};
 
     // Create test nodes
     fn create_test_nodes(count: usize, base_weight: u32) -> Vec<Arc<Node>> {
@@ -21,15 +35,33 @@ mod tests {
             .map(|i| {
                 let endpoint = Endpoint {
                     id: i as u64,
-                    #[cfg(feature = "volo-adapter")]
-                    address: format!("127.0.0.1:{}", 8080 + i)
-                        .parse::<std::net::SocketAddr>()
-                        .unwrap()
-                        .into(),
-                    #[cfg(not(feature = "volo-adapter"))]
-                    address: format!("127.0.0.1:{}", 8080 + i),
+                    address: format!("127.0.0.1:{}", 8080 + i).into(),
+                    zone: None,
                 };
-                Arc::new(Node::new(endpoint, base_weight + i as u32))
+                Arc::new(
+                    NodeBuilder::default()
+                        .endpoint(endpoint)
+                        .weight(base_weight + i as u32)
+                        .build(),
+                )
+            })
+            .collect()
+    }
+
+    // Equal-weight nodes with ids 0.., node i carrying tag `tags[i]`; an empty key leaves
+    // it untagged. A `zone` tag also sets `endpoint.zone`, as the volo adapter does.
+    fn tagged_nodes(tags: &[(&str, &str)]) -> Vec<Arc<Node>> {
+        tags.iter()
+            .enumerate()
+            .map(|(i, &(key, value))| {
+                let mut builder = Node::builder().id(i as u64).weight(1);
+                if !key.is_empty() {
+                    builder = builder.tag(key, value);
+                }
+                if key == "zone" {
+                    builder = builder.zone(value.to_string());
+                }
+                Arc::new(builder.build())
             })
             .collect()
     }
@@ -37,45 +69,36 @@ mod tests {
     // Create weighted test nodes
     fn create_weighted_test_nodes() -> Vec<Arc<Node>> {
         vec![
-            Arc::new(Node::new(
-                Endpoint {
-                    id: 1,
-                    #[cfg(feature = "volo-adapter")]
-                    address: "127.0.0.1:8081"
-                        .parse::<std::net::SocketAddr>()
-                        .unwrap()
-                        .into(),
-                    #[cfg(not(feature = "volo-adapter"))]
-                    address: "127.0.0.1:8081".to_string(),
-                },
-                10, // weight 10
-            )),
-            Arc::new(Node::new(
-                Endpoint {
-                    id: 2,
-                    #[cfg(feature = "volo-adapter")]
-                    address: "127.0.0.1:8082"
-                        .parse::<std::net::SocketAddr>()
-                        .unwrap()
-                        .into(),
-                    #[cfg(not(feature = "volo-adapter"))]
-                    address: "127.0.0.1:8082".to_string(),
-                },
-                20, // weight 20
-            )),
-            Arc::new(Node::new(
-                Endpoint {
-                    id: 3,
-                    #[cfg(feature = "volo-adapter")]
-                    address: "127.0.0.1:8083"
-                        .parse::<std::net::SocketAddr>()
-                        .unwrap()
-                        .into(),
-                    #[cfg(not(feature = "volo-adapter"))]
-                    address: "127.0.0.1:8083".to_string(),
-                },
-                30, // weight 30
-            )),
+            Arc::new(
+                NodeBuilder::default()
+                    .endpoint(Endpoint {
+                        id: 1,
+                        address: "127.0.0.1:8081".into(),
+                        zone: None,
+                    })
+                    .weight(10) // weight 10
+                    .build(),
+            ),
+            Arc::new(
+                NodeBuilder::default()
+                    .endpoint(Endpoint {
+                        id: 2,
+                        address: "127.0.0.1:8082".into(),
+                        zone: None,
+                    })
+                    .weight(20) // weight 20
+                    .build(),
+            ),
+            Arc::new(
+                NodeBuilder::default()
+                    .endpoint(Endpoint {
+                        id: 3,
+                        address: "127.0.0.1:8083".into(),
+                        zone: None,
+                    })
+                    .weight(30) // weight 30
+                    .build(),
+            ),
         ]
     }
 
@@ -83,10 +106,13 @@ mod tests {
     fn test_round_robin_basic() {
         let nodes = create_test_nodes(3, 1);
         let strategy = RoundRobin;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
         // Test round-robin selection
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let node1 = picker.pick(&req).unwrap();
         let node2 = picker.pick(&req).unwrap();
         let node3 = picker.pick(&req).unwrap();
@@ -101,9 +127,12 @@ mod tests {
     #[test]
     fn test_round_robin_empty_nodes() {
         let strategy = RoundRobin;
-        let picker = strategy.build_picker(Arc::new(Vec::new()));
+        let picker = strategy.build_picker(Arc::new(Vec::new())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let result = picker.pick(&req);
 
         assert!(matches!(result, Err(LoadBalanceError::NoAvailableNodes)));
@@ -113,9 +142,12 @@ mod tests {
     fn test_weighted_round_robin_distribution() {
         let nodes = create_weighted_test_nodes();
         let strategy = WeightedRoundRobin;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let mut selection_count = HashMap::new();
 
         // Select enough times to verify the distribution
@@ -140,9 +172,12 @@ mod tests {
     fn test_power_of_two_choices() {
         let nodes = create_test_nodes(4, 1);
         let strategy = PowerOfTwoChoices;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
 
         // Verify the algorithm works by multiple selections
         for _ in 0..10 {
@@ -155,9 +190,12 @@ mod tests {
     fn test_power_of_two_choices_single_node() {
         let nodes = create_test_nodes(1, 1);
         let strategy = PowerOfTwoChoices;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let node = picker.pick(&req).unwrap();
 
         assert_eq!(node.endpoint.id, 0);
@@ -167,9 +205,12 @@ mod tests {
     fn test_weighted_random_distribution() {
         let nodes = create_weighted_test_nodes();
         let strategy = WeightedRandom;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let mut selection_count = HashMap::new();
 
         // Select enough times to verify the distribution
@@ -198,9 +239,12 @@ mod tests {
     fn test_least_connection() {
         let nodes = create_test_nodes(3, 1);
         let strategy = LeastConnection;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
 
         // Initially, all nodes have 0 connections, so the first node should be selected
         let node1 = picker.pick(&req).unwrap();
@@ -231,9 +275,12 @@ mod tests {
     fn test_response_time_weighted() {
         let nodes = create_test_nodes(3, 1);
         let strategy = ResponseTimeWeighted;
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
 
         // Set different response times
         nodes[0]
@@ -256,12 +303,14 @@ mod tests {
         let nodes = create_test_nodes(3, 1);
         let strategy = ConsistentHash {
             virtual_factor: 160,
+            ..Default::default()
         };
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
         // Test valid hash key
         let req = RequestMetadata {
             hash_key: Some(12345),
+            ..Default::default()
         };
         let node = picker.pick(&req).unwrap();
 
@@ -272,6 +321,7 @@ mod tests {
         // Different hash keys may return different nodes
         let req3 = RequestMetadata {
             hash_key: Some(67890),
+            ..Default::default()
         };
         let _node3 = picker.pick(&req3).unwrap();
         // Note: Different hash keys may return the same node, which is normal
@@ -282,11 +332,15 @@ mod tests {
         let nodes = create_test_nodes(3, 1);
         let strategy = ConsistentHash {
             virtual_factor: 160,
+            ..Default::default()
         };
-        let picker = strategy.build_picker(Arc::new(nodes.clone()));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
 
         // Test missing hash key scenario
-        let req = RequestMetadata { hash_key: None };
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let result = picker.pick(&req);
 
         assert!(matches!(result, Err(LoadBalanceError::MissingHashKey)));
@@ -301,8 +355,11 @@ mod tests {
         balancer.update_nodes(nodes.clone());
 
         // Get the picker and test selection
-        let picker = balancer.picker();
-        let req = RequestMetadata { hash_key: None };
+        let picker = balancer.picker().unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
 
         let node1 = picker.pick(&req).unwrap();
         let node2 = picker.pick(&req).unwrap();
@@ -320,8 +377,11 @@ mod tests {
         // Initialize with an empty node list
         balancer.update_nodes(Vec::new());
 
-        let picker = balancer.picker();
-        let req = RequestMetadata { hash_key: None };
+        let picker = balancer.picker().unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         let result = picker.pick(&req);
 
         assert!(matches!(result, Err(LoadBalanceError::NoAvailableNodes)));
@@ -329,14 +389,4085 @@ mod tests {
 
     #[test]
     fn test_request_metadata() {
-        let metadata = RequestMetadata { hash_key: Some(42) };
+        let metadata = RequestMetadata {
+            hash_key: Some(42),
+            ..Default::default()
+        };
         assert_eq!(metadata.hash_key, Some(42));
 
-        let metadata2 = RequestMetadata { hash_key: None };
+        let metadata2 = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
         assert_eq!(metadata2.hash_key, None);
 
         // Test cloning
         let cloned = metadata.clone();
         assert_eq!(cloned.hash_key, Some(42));
     }
+
+    #[test]
+    fn test_shadow_primary_always_set() {
+        let nodes = create_test_nodes(3, 1);
+        let strategy = Shadow::new(RoundRobin, RoundRobin);
+        let picker = strategy
+            .build_shadow_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        for i in 0..9 {
+            let result = picker.pick_shadow(&req).unwrap();
+            assert_eq!(result.primary.endpoint.id, (i % 3) as u64);
+        }
+    }
+
+    #[test]
+    fn test_shadow_differs_from_primary() {
+        let nodes = create_test_nodes(2, 1);
+
+        // A deterministic shadow strategy still has to land on a different node
+        let strategy = Shadow::new(RoundRobin, ConsistentHash::default());
+        let picker = strategy
+            .build_shadow_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let req = RequestMetadata {
+            hash_key: Some(12345),
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            let result = picker.pick_shadow(&req).unwrap();
+            let shadow = result.shadow.expect("shadow should be picked");
+            assert_ne!(result.primary.endpoint.id, shadow.endpoint.id);
+        }
+    }
+
+    #[test]
+    fn test_shadow_empty_shadow_pool() {
+        let nodes = create_test_nodes(3, 1);
+        let strategy = Shadow::new(RoundRobin, RoundRobin).with_shadow_nodes(Vec::new());
+        let picker = strategy
+            .build_shadow_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let result = picker.pick_shadow(&req).unwrap();
+        assert!(result.shadow.is_none());
+
+        // A single-node pool has nothing to mirror to either
+        let strategy = Shadow::new(RoundRobin, RoundRobin);
+        let picker = strategy
+            .build_shadow_picker(Arc::new(create_test_nodes(1, 1)))
+            .unwrap();
+        let result = picker.pick_shadow(&req).unwrap();
+        assert_eq!(result.primary.endpoint.id, 0);
+        assert!(result.shadow.is_none());
+    }
+
+    #[test]
+    fn test_shadow_as_plain_picker() {
+        let nodes = create_test_nodes(3, 1);
+        let balancer = BaseBalancer::new(Shadow::new(RoundRobin, RoundRobin));
+        balancer.update_nodes(nodes);
+
+        let picker = balancer.picker().unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+    }
+
+    #[test]
+    fn test_base_balancer_with_config_default_weight() {
+        let nodes = create_test_nodes(2, 0);
+        nodes[0]
+            .success
+            .fetch_add(3, std::sync::atomic::Ordering::Relaxed);
+
+        let balancer = BaseBalancer::with_config(RoundRobin, BalanceConfig::default());
+        balancer.update_nodes(nodes.clone());
+
+        let picker = balancer.picker().unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let node0 = picker.pick(&req).unwrap();
+        let node1 = picker.pick(&req).unwrap();
+
+        // Zero weight is replaced by the default, other weights are kept
+        assert_eq!(node0.weight(), 100);
+        assert_eq!(node1.weight(), 1);
+        // The weight is set on the given node, so its stats stay with it
+        assert!(Arc::ptr_eq(&node0, &nodes[0]));
+        assert_eq!(node0.success.load(std::sync::atomic::Ordering::Relaxed), 3);
+
+        // Without a config zero weights are passed through untouched
+        let plain = BaseBalancer::new(RoundRobin);
+        plain.update_nodes(create_test_nodes(2, 0));
+        assert_eq!(plain.picker().unwrap().pick(&req).unwrap().weight(), 0);
+    }
+
+    #[test]
+    fn test_base_balancer_with_config_distribution() {
+        // Weights 0 (-> 10) and 10 should split traffic evenly
+        let nodes = vec![
+            create_test_nodes(1, 0).remove(0),
+            create_weighted_test_nodes().remove(0),
+        ];
+        let config = BalanceConfig {
+            default_weight: 10,
+            ..Default::default()
+        };
+
+        let balancer = BaseBalancer::with_config(WeightedRoundRobin, config.clone());
+        balancer.update_nodes(nodes.clone());
+        let picker = balancer.picker().unwrap();
+
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let mut selection_count = HashMap::new();
+        for _ in 0..100 {
+            let node = picker.pick(&req).unwrap();
+            *selection_count.entry(node.endpoint.id).or_insert(0) += 1;
+        }
+        assert_eq!(selection_count.get(&0), Some(&50));
+        assert_eq!(selection_count.get(&1), Some(&50));
+
+        let balancer = BaseBalancer::with_config(WeightedRandom, config);
+        balancer.update_nodes(nodes);
+        let picker = balancer.picker().unwrap();
+
+        let mut selection_count = HashMap::new();
+        for _ in 0..2000 {
+            let node = picker.pick(&req).unwrap();
+            *selection_count.entry(node.endpoint.id).or_insert(0) += 1;
+        }
+        let ratio = *selection_count.get(&0).unwrap_or(&0) as f64 / 2000.0;
+        assert!((ratio - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_pick_n_round_robin() {
+        let nodes = create_test_nodes(4, 1);
+        let picker = RoundRobin.build_picker(Arc::new(nodes.clone())).unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        let ids: Vec<u64> = picker
+            .pick_n(&req, 3)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        // The cursor advanced by three slots
+        let ids: Vec<u64> = picker
+            .pick_n(&req, 2)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![3, 0]);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+    }
+
+    #[test]
+    fn test_pick_n_more_than_available() {
+        let nodes = create_test_nodes(3, 1);
+        let req = RequestMetadata {
+            hash_key: Some(7),
+            ..Default::default()
+        };
+
+        let strategies: Vec<Box<dyn BalanceStrategy>> = vec![
+            Box::new(RoundRobin),
+            Box::new(WeightedRoundRobin),
+            Box::new(PowerOfTwoChoices),
+            Box::new(WeightedRandom),
+            Box::new(LeastConnection),
+            Box::new(ConsistentHash::default()),
+        ];
+        for strategy in strategies {
+            let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+            let picked = picker.pick_n(&req, 10).unwrap();
+            assert!(!picked.is_empty() && picked.len() <= 3);
+
+            let mut ids: Vec<u64> = picked.iter().map(|n| n.endpoint.id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids.len(), picked.len());
+        }
+
+        // Strategies that can reach every node return all of them
+        for strategy in [
+            Box::new(RoundRobin) as Box<dyn BalanceStrategy>,
+            Box::new(PowerOfTwoChoices),
+        ] {
+            let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+            assert_eq!(picker.pick_n(&req, 10).unwrap().len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_pick_n_power_of_two_choices_distinct() {
+        let nodes = create_test_nodes(10, 1);
+        nodes[3]
+            .in_flight
+            .fetch_add(100, std::sync::atomic::Ordering::Relaxed);
+        let picker = PowerOfTwoChoices
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        for _ in 0..50 {
+            let picked = picker.pick_n(&req, 4).unwrap();
+            assert_eq!(picked.len(), 4);
+
+            let mut ids: Vec<u64> = picked.iter().map(|n| n.endpoint.id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids.len(), 4);
+            // Eight candidates are sampled, the busy node never beats four idle ones
+            assert!(!ids.contains(&3));
+        }
+    }
+
+    #[test]
+    fn test_pick_n_edge_cases() {
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        let picker = RoundRobin
+            .build_picker(Arc::new(create_test_nodes(3, 1)))
+            .unwrap();
+        assert!(picker.pick_n(&req, 0).unwrap().is_empty());
+
+        let picker = LeastConnection.build_picker(Arc::new(Vec::new())).unwrap();
+        assert!(matches!(
+            picker.pick_n(&req, 2),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_circuit_breaker_trips_after_consecutive_failures() {
+        let nodes = create_test_nodes(3, 1);
+        let strategy = CircuitBreaker::with_config(
+            RoundRobin,
+            CircuitBreakerConfig {
+                failure_threshold: 3,
+                cooldown: Duration::from_secs(60),
+            },
+        );
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        // Two failures stay below the threshold
+        nodes[1].fail.fetch_add(2, Ordering::Relaxed);
+        for _ in 0..3 {
+            picker.pick(&req).unwrap();
+        }
+        assert_eq!(strategy.state(1), Some(CircuitState::Closed));
+
+        // A success resets the streak
+        nodes[1].success.fetch_add(1, Ordering::Relaxed);
+        nodes[1].fail.fetch_add(1, Ordering::Relaxed);
+        for _ in 0..3 {
+            picker.pick(&req).unwrap();
+        }
+        assert_eq!(strategy.state(1), Some(CircuitState::Closed));
+
+        nodes[1].fail.fetch_add(3, Ordering::Relaxed);
+        for _ in 0..3 {
+            picker.pick(&req).unwrap();
+        }
+        assert_eq!(strategy.state(1), Some(CircuitState::Open));
+    }
+
+    #[test]
+    fn test_circuit_breaker_skips_open_node() {
+        let nodes = create_test_nodes(3, 1);
+        let strategy = CircuitBreaker::with_config(
+            RoundRobin,
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                cooldown: Duration::from_secs(60),
+            },
+        );
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+
+        nodes[0].fail.fetch_add(1, Ordering::Relaxed);
+        for _ in 0..30 {
+            assert_ne!(picker.pick(&req).unwrap().endpoint.id, 0);
+        }
+
+        // State survives picker rebuilds, also for deterministic inner strategies
+        let strategy = CircuitBreaker::with_config(
+            ConsistentHash::default(),
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                cooldown: Duration::from_secs(60),
+            },
+        );
+        let req = RequestMetadata {
+            hash_key: Some(42),
+            ..Default::default()
+        };
+        let target = strategy
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap()
+            .pick(&req)
+            .unwrap();
+        target.fail.fetch_add(1, Ordering::Relaxed);
+
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        for _ in 0..10 {
+            assert_ne!(picker.pick(&req).unwrap().endpoint.id, target.endpoint.id);
+        }
+
+        // Every node open means nothing can be picked
+        for node in &nodes {
+            node.fail.fetch_add(1, Ordering::Relaxed);
+        }
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_circuit_breaker_recovers_after_cooldown() {
+        let nodes = create_test_nodes(2, 1);
+        let strategy = CircuitBreaker::with_config(
+            RoundRobin,
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown: Duration::from_millis(50),
+            },
+        );
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        nodes[0].fail.fetch_add(2, Ordering::Relaxed);
+        picker.pick(&req).unwrap();
+        assert_eq!(strategy.state(0), Some(CircuitState::Open));
+
+        // Half-open lets exactly one probe through
+        std::thread::sleep(Duration::from_millis(60));
+        let probes = (0..4)
+            .filter(|_| picker.pick(&req).unwrap().endpoint.id == 0)
+            .count();
+        assert_eq!(probes, 1);
+        assert_eq!(strategy.state(0), Some(CircuitState::HalfOpen));
+
+        // A failed probe re-opens the breaker
+        nodes[0].fail.fetch_add(1, Ordering::Relaxed);
+        picker.pick(&req).unwrap();
+        picker.pick(&req).unwrap();
+        assert_eq!(strategy.state(0), Some(CircuitState::Open));
+
+        // A successful probe closes it again
+        std::thread::sleep(Duration::from_millis(60));
+        while picker.pick(&req).unwrap().endpoint.id != 0 {}
+        nodes[0].success.fetch_add(1, Ordering::Relaxed);
+        picker.pick(&req).unwrap();
+        picker.pick(&req).unwrap();
+        assert_eq!(strategy.state(0), Some(CircuitState::Closed));
+    }
+
+    #[test]
+    fn test_equal_round_robin_rotation() {
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        for count in [3usize, 5, 100] {
+            let nodes = create_test_nodes(count, 1);
+            let picker = EqualRoundRobin
+                .build_picker(Arc::new(nodes.clone()))
+                .unwrap();
+
+            // Several full round trips visit every node in order, ignoring weights
+            for round in 0..3 {
+                for expected in 0..count {
+                    let node = picker.pick(&req).unwrap();
+                    assert_eq!(
+                        node.endpoint.id, expected as u64,
+                        "count {count}, round {round}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_robin_is_equal_round_robin() {
+        let nodes = create_weighted_test_nodes();
+        let rr = RoundRobin.build_picker(Arc::new(nodes.clone())).unwrap();
+        let err = EqualRoundRobin
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        for _ in 0..6 {
+            assert_eq!(
+                rr.pick(&req).unwrap().endpoint.id,
+                err.pick(&req).unwrap().endpoint.id
+            );
+        }
+    }
+
+    #[test]
+    fn test_consistent_hash_custom_hasher() {
+        // Request keys hash to themselves, every virtual node of node `id` lands on id * 1000
+        let strategy = ConsistentHash::default().with_hasher(|bytes: &[u8]| {
+            if let Ok(raw) = <[u8; 8]>::try_from(bytes) {
+                return u64::from_le_bytes(raw);
+            }
+            let key = std::str::from_utf8(bytes).unwrap();
+            let id: u64 = key
+                .strip_prefix("id:")
+                .and_then(|rest| rest.split('|').next())
+                .unwrap()
+                .parse()
+                .unwrap();
+            id * 1000
+        });
+        let picker = strategy
+            .build_picker(Arc::new(create_test_nodes(3, 1)))
+            .unwrap();
+
+        // Ring points are 0, 1000 and 2000; keys go to the next point clockwise
+        for (key, expected) in [(0, 0), (1, 1), (1000, 1), (1500, 2), (2000, 2), (2001, 0)] {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            assert_eq!(
+                picker.pick(&req).unwrap().endpoint.id,
+                expected,
+                "key {key}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_weighted_round_robin_weight_overflow() {
+        let mut nodes = create_test_nodes(2, 1);
+        nodes[0] = Arc::new(
+            NodeBuilder::default()
+                .endpoint(nodes[0].endpoint.clone())
+                .weight(u32::MAX)
+                .build(),
+        );
+        let result = WeightedRoundRobin.build_picker(Arc::new(nodes.clone()));
+        assert!(matches!(
+            result,
+            Err(LoadBalanceError::WeightOverflow {
+                node_id: 0,
+                weight: u32::MAX
+            })
+        ));
+
+        // i32::MAX is still accepted
+        nodes[0] = Arc::new(
+            NodeBuilder::default()
+                .endpoint(nodes[0].endpoint.clone())
+                .weight(i32::MAX as u32)
+                .build(),
+        );
+        assert!(WeightedRoundRobin.build_picker(Arc::new(nodes)).is_ok());
+    }
+
+    #[test]
+    fn test_base_balancer_picker_for_subset() {
+        let nodes = create_test_nodes(4, 1);
+        let balancer = BaseBalancer::new(RoundRobin);
+        balancer.update_nodes(nodes[..2].to_vec());
+
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let subset = balancer.picker_for_subset(nodes[2..].to_vec()).unwrap();
+        for _ in 0..10 {
+            let id = subset.pick(&req).unwrap().endpoint.id;
+            assert!(id == 2 || id == 3);
+        }
+
+        // The stored node list is untouched
+        let picker = balancer.picker().unwrap();
+        for _ in 0..10 {
+            assert!(picker.pick(&req).unwrap().endpoint.id < 2);
+        }
+
+        // Works even when the balancer has no nodes at all
+        let empty = BaseBalancer::new(RoundRobin);
+        let subset = empty.picker_for_subset(nodes[3..].to_vec()).unwrap();
+        assert_eq!(subset.pick(&req).unwrap().endpoint.id, 3);
+        assert!(matches!(
+            empty.picker().unwrap().pick(&req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_weighted_random_alias_distribution() {
+        let weights = [1u32, 0, 2, 3, 10, 4];
+        let nodes: Vec<Arc<Node>> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                Arc::new(
+                    NodeBuilder::default()
+                        .endpoint(Endpoint {
+                            id: i as u64,
+                            ..Default::default()
+                        })
+                        .weight(w)
+                        .build(),
+                )
+            })
+            .collect();
+        let picker = WeightedRandom.build_picker(Arc::new(nodes)).unwrap();
+
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let samples = 10_000;
+        let mut counts = [0usize; 6];
+        for _ in 0..samples {
+            counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+        }
+
+        let total: u32 = weights.iter().sum();
+        for (i, &w) in weights.iter().enumerate() {
+            let expected = w as f64 / total as f64;
+            let actual = counts[i] as f64 / samples as f64;
+            assert!(
+                (actual - expected).abs() < 0.02,
+                "node {i}: expected {expected:.3}, got {actual:.3}"
+            );
+        }
+        // Zero-weight nodes are never sampled while others have weight
+        assert_eq!(counts[1], 0);
+    }
+
+    #[test]
+    fn test_weighted_random_all_zero_weights() {
+        let nodes = create_test_nodes(4, 0);
+        nodes[1..]
+            .iter()
+            .for_each(|n| assert_eq!(n.weight(), n.endpoint.id as u32));
+        let nodes: Vec<Arc<Node>> = (0..4)
+            .map(|i| {
+                Arc::new(
+                    NodeBuilder::default()
+                        .endpoint(Endpoint {
+                            id: i,
+                            ..Default::default()
+                        })
+                        .build(),
+                )
+            })
+            .collect();
+        let picker = WeightedRandom.build_picker(Arc::new(nodes)).unwrap();
+
+        // All weights 0 falls back to uniform selection
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+        let mut counts = [0usize; 4];
+        for _ in 0..4000 {
+            counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c > 800 && c < 1200));
+    }
+
+    #[test]
+    fn test_base_balancer_update_nodes_pooled() {
+        let endpoints: Vec<Endpoint> = create_test_nodes(4, 1)
+            .iter()
+            .map(|n| n.endpoint.clone())
+            .collect();
+        let pool = NodePool::new(8);
+        let balancer = BaseBalancer::with_config(
+            RoundRobin,
+            BalanceConfig {
+                default_weight: 7,
+                ..Default::default()
+            },
+        );
+
+        balancer.update_nodes_pooled(&pool, endpoints.clone());
+        let picker = balancer.picker().unwrap();
+        let kept = picker.pick(&RequestMetadata::default()).unwrap();
+        assert_eq!(kept.endpoint.id, 0);
+        assert_eq!(kept.weight(), 7);
+        kept.success.fetch_add(5, Ordering::Relaxed);
+        drop(picker);
+
+        // Unchanged nodes survive with their stats, removed ones go back to the pool
+        balancer.update_nodes_pooled(&pool, endpoints[..2].to_vec());
+        assert_eq!(pool.len(), 2);
+        let picker = balancer.picker().unwrap();
+        let node = picker.pick(&RequestMetadata::default()).unwrap();
+        assert!(Arc::ptr_eq(&node, &kept));
+        assert_eq!(node.success.load(Ordering::Relaxed), 5);
+        drop((picker, node));
+
+        // Re-adding nodes reuses the released allocations
+        balancer.update_nodes_pooled(&pool, endpoints.clone());
+        assert!(pool.is_empty());
+        let picker = balancer.picker().unwrap();
+        let mut ids: Vec<u64> = (0..4)
+            .map(|_| {
+                picker
+                    .pick(&RequestMetadata::default())
+                    .unwrap()
+                    .endpoint
+                    .id
+            })
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+    }
+
+    fn count_picks(picker: &Arc<dyn Picker>, rounds: usize) -> HashMap<u64, usize> {
+        let mut counts = HashMap::new();
+        for _ in 0..rounds {
+            let node = picker.pick(&RequestMetadata::default()).unwrap();
+            *counts.entry(node.endpoint.id).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_peak_ewma_avoids_spiking_node() {
+        let nodes = create_test_nodes(3, 1);
+        let strategy = PeakEwma::new(Duration::from_millis(5), Duration::from_millis(10));
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+
+        // Equal estimates: every node gets traffic
+        let counts = count_picks(&picker, 300);
+        assert!((0..3).all(|id| counts.get(&id).copied().unwrap_or(0) > 0));
+
+        // A spike is reflected at once and the node is no longer chosen
+        picker.on_response(&nodes[0], Duration::from_millis(200));
+        assert_eq!(nodes[0].last_rtt_ns.load(Ordering::Relaxed), 200_000_000);
+        let counts = count_picks(&picker, 300);
+        assert_eq!(counts.get(&0), None);
+
+        // The estimate survives a rebuild, so the node stays avoided
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        assert_eq!(strategy.estimate_ns(0), Some(200_000_000.0));
+        assert_eq!(count_picks(&picker, 300).get(&0), None);
+
+        // Fast responses decay the estimate back toward the observed RTT
+        std::thread::sleep(Duration::from_millis(50));
+        picker.on_response(&nodes[0], Duration::from_millis(1));
+        assert!(strategy.estimate_ns(0).unwrap() < 10_000_000.0);
+        assert!(count_picks(&picker, 300).get(&0).copied().unwrap_or(0) > 0);
+    }
+
+    #[test]
+    fn test_peak_ewma_penalizes_in_flight() {
+        let nodes = create_test_nodes(2, 1);
+        let picker = PeakEwma::default()
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        // Same RTT estimate, the node with outstanding requests costs more
+        nodes[1].in_flight.store(3, Ordering::Relaxed);
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts.get(&0), Some(&100));
+    }
+
+    // Standard deviation of `counts[i] / expected[i]`, 0 means a perfect match
+    fn share_std_dev(counts: &[usize], expected: &[f64]) -> f64 {
+        let total: usize = counts.iter().sum();
+        let ratios: Vec<f64> = counts
+            .iter()
+            .zip(expected)
+            .map(|(&c, &e)| c as f64 / total as f64 / e)
+            .collect();
+        let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+        (ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / ratios.len() as f64).sqrt()
+    }
+
+    fn hash_distribution(strategy: &ConsistentHash, weights: &[u32]) -> Vec<usize> {
+        let nodes: Vec<Arc<Node>> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| Arc::new(Node::builder().id(i as u64).weight(w).build()))
+            .collect();
+        let picker = strategy.build_picker(Arc::new(nodes)).unwrap();
+        let mut counts = vec![0usize; weights.len()];
+        for key in 0..20_000u64 {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_consistent_hash_virtual_node_policy() {
+        let weights = [1, 1, 1, 5];
+        let total: u32 = weights.iter().sum();
+        let uniform = [0.25; 4];
+        let by_weight: Vec<f64> = weights.iter().map(|&w| w as f64 / total as f64).collect();
+
+        let fixed = hash_distribution(
+            &ConsistentHash {
+                virtual_factor: 100,
+                policy: VirtualNodePolicy::Fixed,
+                ..Default::default()
+            },
+            &weights,
+        );
+        let proportional = hash_distribution(
+            &ConsistentHash {
+                virtual_factor: 100,
+                ..Default::default()
+            },
+            &weights,
+        );
+
+        // Fixed ignores weights and keeps the ring balanced
+        assert!(share_std_dev(&fixed, &uniform) < 0.15);
+        assert!(share_std_dev(&fixed, &by_weight) > share_std_dev(&fixed, &uniform));
+
+        // Proportional follows the weights instead
+        assert!(share_std_dev(&proportional, &by_weight) < 0.15);
+        assert!(share_std_dev(&proportional, &uniform) > share_std_dev(&proportional, &by_weight));
+    }
+
+    #[test]
+    fn test_consistent_hash_proportional_keeps_ratio_under_cap() {
+        // 10 * 400 virtual nodes exceed the per-node cap; all nodes shrink together
+        let weights = [100, 400];
+        let counts = hash_distribution(
+            &ConsistentHash {
+                virtual_factor: 400,
+                ..Default::default()
+            },
+            &weights,
+        );
+        assert!(share_std_dev(&counts, &[0.2, 0.8]) < 0.15);
+    }
+
+    #[test]
+    fn test_response_time_weighted_extreme_rtt() {
+        let nodes: Vec<Arc<Node>> = [u64::MAX, 5_000_000_000, u64::MAX / 2]
+            .iter()
+            .enumerate()
+            .map(|(i, &rtt)| {
+                Arc::new(
+                    Node::builder()
+                        .id(i as u64)
+                        .weight(1)
+                        .initial_rtt_ns(rtt)
+                        .build(),
+                )
+            })
+            .collect();
+        let picker = ResponseTimeWeighted
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        // Slow RTTs stay ordered instead of collapsing to the same score
+        let req = RequestMetadata::default();
+        for _ in 0..10 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+        }
+
+        // All-extreme values still give a deterministic node
+        for node in &nodes {
+            node.last_rtt_ns.store(u64::MAX, Ordering::Relaxed);
+            node.in_flight.store(usize::MAX, Ordering::Relaxed);
+        }
+        for _ in 0..10 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+        }
+    }
+
+    #[test]
+    fn test_least_connection_weighted() {
+        // Weights 1:2:3
+        let nodes = create_test_nodes(3, 1);
+        let picker = LeastConnectionWeighted
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        // Weight 3 wins with 3x the connections of weight 1 (2/1 vs 5/2 vs 6/3)
+        nodes[0].in_flight.store(2, Ordering::Relaxed);
+        nodes[1].in_flight.store(5, Ordering::Relaxed);
+        nodes[2].in_flight.store(6, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+
+        // Plain LeastConnection would pick the weight 1 node
+        let plain = LeastConnection
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        assert_eq!(plain.pick(&req).unwrap().endpoint.id, 0);
+
+        // Strictly lower relative load wins
+        nodes[0].in_flight.store(1, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+
+        let empty = LeastConnectionWeighted
+            .build_picker(Arc::new(Vec::new()))
+            .unwrap();
+        assert!(matches!(
+            empty.pick(&req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_timeout_aware_picker_records_on_drop() {
+        let nodes = create_test_nodes(1, 1);
+        let picker =
+            TimeoutAwarePicker::new(RoundRobin.build_picker(Arc::new(nodes.clone())).unwrap());
+        assert_eq!(nodes[0].last_rtt_ns.load(Ordering::Relaxed), 0);
+
+        {
+            let handle = picker.pick_timed(&RequestMetadata::default()).unwrap();
+            assert_eq!(handle.node.endpoint.id, 0);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // An unset RTT takes the first sample as is
+        assert!(nodes[0].last_rtt_ns.load(Ordering::Relaxed) >= 5_000_000);
+    }
+
+    #[test]
+    fn test_timeout_aware_picker_ewma() {
+        let nodes = create_test_nodes(1, 1);
+        let previous = 1_000_000_000u64;
+        nodes[0].last_rtt_ns.store(previous, Ordering::Relaxed);
+        let picker =
+            TimeoutAwarePicker::new(RoundRobin.build_picker(Arc::new(nodes.clone())).unwrap())
+                .with_alpha(0.25);
+
+        let sample = picker
+            .pick_timed(&RequestMetadata::default())
+            .unwrap()
+            .finish()
+            .as_nanos() as f64;
+        let expected = 0.25 * sample + 0.75 * previous as f64;
+        let actual = nodes[0].last_rtt_ns.load(Ordering::Relaxed) as f64;
+        assert!((actual - expected).abs() <= 1.0);
+
+        // Only the raw sample reaches the histogram
+        #[cfg(feature = "rtt-histogram")]
+        assert_eq!(nodes[0].rtt_histogram.len(), 1);
+    }
+
+    #[test]
+    fn test_timeout_aware_picker_feeds_peak_ewma() {
+        let nodes = create_test_nodes(1, 1);
+        let strategy = PeakEwma::new(Duration::from_secs(10), Duration::from_secs(1));
+        let picker = TimeoutAwarePicker::new(strategy.build_picker(Arc::new(nodes)).unwrap());
+
+        picker
+            .pick_timed(&RequestMetadata::default())
+            .unwrap()
+            .finish();
+        // The fast sample pulled the 1s default estimate down
+        assert!(strategy.estimate_ns(0).unwrap() < 1_000_000_000.0);
+    }
+
+    #[test]
+    fn test_zone_aware_prefers_local_zone() {
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "b"), ("zone", "a"), ("", "")]);
+        let picker = ZoneAware::new(RoundRobin, "a")
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+
+        let req = RequestMetadata::default();
+        let ids: Vec<u64> = (0..4)
+            .map(|_| picker.pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 2, 0, 2]);
+    }
+
+    #[test]
+    fn test_zone_aware_cross_zone_fallback() {
+        let req = RequestMetadata::default();
+
+        // No local node at all
+        let nodes = tagged_nodes(&[("zone", "b"), ("", "")]);
+        let picker = ZoneAware::new(RoundRobin, "a")
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+        assert!(picker.pick(&req).unwrap().endpoint.id < 2);
+
+        // Local nodes exist but are all unhealthy
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "b")]);
+        let strategy = ZoneAware::new(
+            CircuitBreaker::with_config(
+                RoundRobin,
+                CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    cooldown: Duration::from_secs(60),
+                },
+            ),
+            "a",
+        );
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+
+        nodes[0].fail.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+
+        // Everything unhealthy, in both partitions' breakers across a rebuild
+        nodes[1].fail.fetch_add(1, Ordering::Relaxed);
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+        let picker = strategy.build_picker(Arc::new(nodes)).unwrap();
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_zone_aware_spills_past_unhealthy_local_nodes() {
+        let req = RequestMetadata::default();
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "b")]);
+        let picker = ZoneAware::new(RoundRobin, "a")
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+
+        // A failing local node no longer takes the traffic without a breaker
+        nodes[0].fail.store(20, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+
+        // With every node unhealthy the remote zone is picked best effort
+        nodes[1].fail.store(20, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+    }
+
+    #[test]
+    fn test_zone_aware_returns_other_local_errors() {
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "b")]);
+        let picker = ZoneAware::new(CapacityLimited::new(RoundRobin, 1), "a")
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        // The local node is at its cap: that is not a reason to leave the zone
+        nodes[0].in_flight.store(1, Ordering::Relaxed);
+        assert!(matches!(
+            picker.pick(&RequestMetadata::default()),
+            Err(LoadBalanceError::Overloaded)
+        ));
+    }
+
+    #[test]
+    fn test_multi_tenant_isolation() {
+        let balancer = MultiTenantBalancer::new(RoundRobin);
+        let req = RequestMetadata::default();
+
+        let tenant_a = create_test_nodes(3, 100);
+        let tenant_b: Vec<Arc<Node>> = create_test_nodes(5, 100).into_iter().skip(3).collect();
+        balancer.register_tenant("a", tenant_a).unwrap();
+        balancer.register_tenant("b", tenant_b).unwrap();
+
+        // Each tenant only ever sees its own nodes, and rotates over all of them
+        let ids_a: Vec<u64> = (0..6)
+            .map(|_| balancer.pick_for_tenant("a", &req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids_a, vec![0, 1, 2, 0, 1, 2]);
+        for _ in 0..10 {
+            let id = balancer.pick_for_tenant("b", &req).unwrap().endpoint.id;
+            assert!(id == 3 || id == 4);
+        }
+
+        // Re-registering replaces only that tenant's nodes
+        balancer
+            .register_tenant("b", create_test_nodes(1, 100))
+            .unwrap();
+        assert_eq!(balancer.pick_for_tenant("b", &req).unwrap().endpoint.id, 0);
+        assert_eq!(balancer.tenant_nodes("a").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_multi_tenant_isolates_strategy_state() {
+        let ewma = PeakEwma::default();
+        let balancer = MultiTenantBalancer::new(ewma.clone());
+        let node = |id| Arc::new(Node::builder().id(id).weight(1).build());
+
+        let node_a = node(1);
+        balancer.register_tenant("a", vec![node_a.clone()]).unwrap();
+        balancer
+            .tenant_picker("a")
+            .unwrap()
+            .on_response(&node_a, Duration::from_millis(40));
+
+        let estimate = |tenant| balancer.tenant_strategy(tenant).unwrap().estimate_ns(1);
+
+        // Registering "b" without node 1 does not prune the estimate of "a"
+        balancer.register_tenant("b", vec![node(2)]).unwrap();
+        assert_eq!(estimate("a"), Some(40_000_000.0));
+        assert_eq!(estimate("b"), None);
+
+        // With node 1, "b" starts at the default RTT
+        let node_b = node(1);
+        balancer
+            .register_tenant("b", vec![node(2), node_b.clone()])
+            .unwrap();
+        assert_eq!(estimate("a"), Some(40_000_000.0));
+        assert_eq!(estimate("b"), Some(30_000_000.0));
+
+        // Responses of "b" for the same node id stay with "b"
+        balancer
+            .tenant_picker("b")
+            .unwrap()
+            .on_response(&node_b, Duration::from_millis(50));
+        assert_eq!(estimate("a"), Some(40_000_000.0));
+        assert_eq!(estimate("b"), Some(50_000_000.0));
+
+        // The strategy passed in keeps no tenant's state
+        assert_eq!(ewma.estimate_ns(1), None);
+        assert!(matches!(
+            balancer.tenant_picker("missing"),
+            Err(LoadBalanceError::UnknownTenant(_))
+        ));
+    }
+
+    #[test]
+    fn test_detach_state_reaches_wrapped_strategies() {
+        let ewma = PeakEwma::default();
+        let mut strategy = ZoneAware::new(CircuitBreaker::new(ewma.clone()), "a");
+        strategy.detach_state();
+
+        let nodes = Arc::new(tagged_nodes(&[("zone", "a")]));
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+        picker.on_response(&nodes[0], Duration::from_millis(40));
+        assert_eq!(ewma.estimate_ns(0), None);
+    }
+
+    #[test]
+    fn test_multi_tenant_unknown_tenant() {
+        let balancer = MultiTenantBalancer::new(RoundRobin);
+        let req = RequestMetadata::default();
+
+        assert!(matches!(
+            balancer.pick_for_tenant("missing", &req),
+            Err(LoadBalanceError::UnknownTenant(id)) if id == "missing"
+        ));
+
+        balancer
+            .register_tenant("a", create_test_nodes(2, 100))
+            .unwrap();
+        assert!(balancer.contains_tenant("a"));
+        assert!(balancer.remove_tenant("a"));
+        assert!(!balancer.remove_tenant("a"));
+        assert!(matches!(
+            balancer.pick_for_tenant("a", &req),
+            Err(LoadBalanceError::UnknownTenant(_))
+        ));
+
+        // Registered without nodes is known but empty
+        balancer.register_tenant("empty", Vec::new()).unwrap();
+        assert!(matches!(
+            balancer.pick_for_tenant("empty", &req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_exclude_ids_honored_by_every_strategy() {
+        let strategies: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("rr", Box::new(RoundRobin)),
+            ("wrr", Box::new(WeightedRoundRobin)),
+            ("p2c", Box::new(PowerOfTwoChoices)),
+            ("wr", Box::new(WeightedRandom)),
+            ("lc", Box::new(LeastConnection)),
+            ("lcw", Box::new(LeastConnectionWeighted)),
+            ("rtw", Box::new(ResponseTimeWeighted)),
+            ("peak_ewma", Box::new(PeakEwma::default())),
+            ("ch", Box::new(ConsistentHash::default())),
+            ("circuit_breaker", Box::new(CircuitBreaker::new(RoundRobin))),
+        ];
+
+        for (name, strategy) in strategies {
+            let nodes = create_test_nodes(4, 100);
+            let picker = strategy.build_picker(Arc::new(nodes)).unwrap();
+
+            for key in 0..200u64 {
+                let req = RequestMetadata {
+                    hash_key: Some(key),
+                    ..Default::default()
+                }
+                .exclude(1)
+                .exclude(3);
+                let id = picker.pick(&req).unwrap().endpoint.id;
+                assert!(id == 0 || id == 2, "{name} returned excluded node {id}");
+            }
+
+            // Excluding every node leaves nothing to pick
+            let req = RequestMetadata {
+                hash_key: Some(7),
+                ..Default::default()
+            }
+            .exclude(0)
+            .exclude(1)
+            .exclude(2)
+            .exclude(3);
+            assert!(
+                matches!(picker.pick(&req), Err(LoadBalanceError::NoAvailableNodes)),
+                "{name} picked with every node excluded"
+            );
+        }
+    }
+
+    #[test]
+    fn test_exclude_ids_consistent_hash_walks_ring() {
+        let nodes = create_test_nodes(5, 100);
+        let picker = ConsistentHash::default()
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+
+        for key in 0..100u64 {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            let owner = picker.pick(&req).unwrap().endpoint.id;
+
+            // The fallback is deterministic and never the excluded owner
+            let retry = req.clone().exclude(owner);
+            let fallback = picker.pick(&retry).unwrap().endpoint.id;
+            assert_ne!(fallback, owner);
+            assert_eq!(picker.pick(&retry).unwrap().endpoint.id, fallback);
+
+            // Excluding some other node does not move the key
+            let other = req.clone().exclude((owner + 1) % 5);
+            assert_eq!(picker.pick(&other).unwrap().endpoint.id, owner);
+        }
+    }
+
+    #[test]
+    fn test_exclude_ids_round_robin_skips_in_rotation() {
+        let nodes = create_test_nodes(4, 100);
+        let picker = RoundRobin.build_picker(Arc::new(nodes)).unwrap();
+        let req = RequestMetadata::default().exclude(1);
+
+        let ids: Vec<u64> = (0..6)
+            .map(|_| picker.pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 2, 3, 0, 2, 3]);
+
+        let ids: Vec<u64> = picker
+            .pick_n(&req, 4)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 2, 3]);
+
+        // P2C fan-out drops excluded nodes as well
+        let nodes = create_test_nodes(4, 100);
+        let picker = PowerOfTwoChoices.build_picker(Arc::new(nodes)).unwrap();
+        for _ in 0..20 {
+            let picked = picker.pick_n(&req, 4).unwrap();
+            assert_eq!(picked.len(), 3);
+            assert!(picked.iter().all(|n| n.endpoint.id != 1));
+        }
+    }
+
+    #[test]
+    fn test_strategy_compose_routes_by_hash_key() {
+        let nodes = create_test_nodes(4, 100);
+        let picker = StrategyCompose::new(ConsistentHash::default(), RoundRobin)
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+
+        // Keyed requests stick to one node per key
+        for key in 0..50u64 {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            let first = picker.pick(&req).unwrap().endpoint.id;
+            for _ in 0..5 {
+                assert_eq!(picker.pick(&req).unwrap().endpoint.id, first);
+            }
+        }
+
+        // Keyless requests rotate, unaffected by the keyed picks above
+        let req = RequestMetadata::default();
+        let ids: Vec<u64> = (0..8)
+            .map(|_| picker.pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 3, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_pick_retry_distinct_nodes() {
+        let strategies: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("rr", Box::new(RoundRobin)),
+            ("p2c", Box::new(PowerOfTwoChoices)),
+            ("wr", Box::new(WeightedRandom)),
+            ("lc", Box::new(LeastConnection)),
+            ("ch", Box::new(ConsistentHash::default())),
+        ];
+
+        for (name, strategy) in strategies {
+            let picker = strategy
+                .build_picker(Arc::new(create_test_nodes(5, 100)))
+                .unwrap();
+
+            for key in 0..20u64 {
+                let mut req = RequestMetadata {
+                    hash_key: Some(key),
+                    ..Default::default()
+                }
+                .exclude(4);
+
+                // 4 attempts over the 4 nodes that are not excluded
+                let mut ids: Vec<u64> = (0..4)
+                    .map(|attempt| picker.pick_retry(&mut req, attempt).unwrap().endpoint.id)
+                    .collect();
+                ids.sort_unstable();
+                assert_eq!(ids, vec![0, 1, 2, 3], "{name} repeated a node");
+            }
+        }
+    }
+
+    #[test]
+    fn test_pick_retry_exhaustion() {
+        let picker = ConsistentHash::default()
+            .build_picker(Arc::new(create_test_nodes(2, 100)))
+            .unwrap();
+
+        // Wrap (default): the third attempt starts over with the first node
+        let mut req = RequestMetadata {
+            hash_key: Some(9),
+            ..Default::default()
+        };
+        let first = picker.pick_retry(&mut req, 0).unwrap().endpoint.id;
+        let second = picker.pick_retry(&mut req, 1).unwrap().endpoint.id;
+        assert_ne!(first, second);
+        assert_eq!(picker.pick_retry(&mut req, 2).unwrap().endpoint.id, first);
+
+        // A new attempt 0 forgets the previous request's history
+        assert_eq!(picker.pick_retry(&mut req, 0).unwrap().endpoint.id, first);
+
+        // Error: no node is reused
+        let mut req = RequestMetadata {
+            hash_key: Some(9),
+            retry_exhaustion: RetryExhaustion::Error,
+            ..Default::default()
+        };
+        picker.pick_retry(&mut req, 0).unwrap();
+        picker.pick_retry(&mut req, 1).unwrap();
+        assert!(matches!(
+            picker.pick_retry(&mut req, 2),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_with_weights_overrides_node_weights() {
+        // Nodes carry weights 100 and 101, the override skews traffic 1:3
+        let nodes = Arc::new(create_test_nodes(2, 100));
+
+        let picker = WeightedRoundRobin
+            .with_weights(vec![1, 3])
+            .build_picker(nodes.clone())
+            .unwrap();
+        let counts = count_picks(&picker, 400);
+        assert_eq!(counts[&0], 100);
+        assert_eq!(counts[&1], 300);
+
+        let picker = WeightedRandom
+            .with_weights(vec![1, 3])
+            .build_picker(nodes.clone())
+            .unwrap();
+        let counts = count_picks(&picker, 20_000);
+        let share = counts[&1] as f64 / 20_000.0;
+        assert!((share - 0.75).abs() < 0.03, "share {share}");
+
+        // Zero override removes a node from weighted random entirely
+        let picker = WeightedRandom
+            .with_weights(vec![0, 5])
+            .build_picker(nodes.clone())
+            .unwrap();
+        assert!(!count_picks(&picker, 500).contains_key(&0));
+
+        let picker = ConsistentHash {
+            virtual_factor: 100,
+            ..Default::default()
+        }
+        .with_weights(vec![1, 4])
+        .build_picker(nodes.clone())
+        .unwrap();
+        let mut heavy = 0;
+        for key in 0..10_000u64 {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            if picker.pick(&req).unwrap().endpoint.id == 1 {
+                heavy += 1;
+            }
+        }
+        assert!(heavy > 7_000, "heavy node got {heavy} keys");
+
+        // The nodes themselves are untouched and still returned as is
+        assert_eq!(nodes[0].weight(), 100);
+        assert_eq!(nodes[1].weight(), 101);
+        let req = RequestMetadata {
+            hash_key: Some(1),
+            ..Default::default()
+        };
+        let picked = picker.pick(&req).unwrap();
+        assert!(nodes.iter().any(|n| Arc::ptr_eq(n, &picked)));
+    }
+
+    #[test]
+    fn test_with_weights_length_mismatch() {
+        let nodes = Arc::new(create_test_nodes(3, 100));
+        let strategy: StaticWeightSnapshot<WeightedRoundRobin> =
+            WeightedRoundRobin.with_weights(vec![1, 2]);
+        assert_eq!(strategy.weights(), &[1, 2]);
+        assert!(matches!(
+            strategy.build_picker(nodes),
+            Err(LoadBalanceError::WeightCountMismatch {
+                nodes: 3,
+                weights: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_pick_batch_distinct_round_robin() {
+        let picker = RoundRobin
+            .build_picker(Arc::new(create_test_nodes(5, 100)))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        picker.pick(&req).unwrap();
+        picker.pick(&req).unwrap();
+        picker.pick(&req).unwrap();
+
+        // The batch continues from the cursor, wraps around and advances it by n
+        let ids: Vec<u64> = picker
+            .pick_batch_distinct(&req, 4)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![3, 4, 0, 1]);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+
+        // Too large a batch fails and leaves the cursor alone
+        assert!(matches!(
+            picker.pick_batch_distinct(&req, 6),
+            Err(LoadBalanceError::InsufficientNodes {
+                requested: 6,
+                available: 5
+            })
+        ));
+        assert!(matches!(
+            picker.pick_batch_distinct(&req.clone().exclude(0), 5),
+            Err(LoadBalanceError::InsufficientNodes {
+                requested: 5,
+                available: 4
+            })
+        ));
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 3);
+        assert!(picker.pick_batch_distinct(&req, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pick_batch_distinct_least_connection() {
+        let nodes = create_test_nodes(5, 100);
+        for (node, load) in nodes.iter().zip([7, 1, 4, 0, 1]) {
+            node.in_flight.store(load, Ordering::Relaxed);
+        }
+        let picker = LeastConnection.build_picker(Arc::new(nodes)).unwrap();
+
+        let ids: Vec<u64> = picker
+            .pick_batch_distinct(&RequestMetadata::default(), 3)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![3, 1, 4]);
+    }
+
+    #[test]
+    fn test_pick_batch_distinct_every_strategy() {
+        let strategies: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("rr", Box::new(RoundRobin)),
+            ("wrr", Box::new(WeightedRoundRobin)),
+            ("p2c", Box::new(PowerOfTwoChoices)),
+            ("wr", Box::new(WeightedRandom)),
+            ("lc", Box::new(LeastConnection)),
+            ("lcw", Box::new(LeastConnectionWeighted)),
+            ("rtw", Box::new(ResponseTimeWeighted)),
+            ("peak_ewma", Box::new(PeakEwma::default())),
+            ("ch", Box::new(ConsistentHash::default())),
+        ];
+
+        for (name, strategy) in strategies {
+            let picker = strategy
+                .build_picker(Arc::new(create_test_nodes(6, 100)))
+                .unwrap();
+            let req = RequestMetadata {
+                hash_key: Some(11),
+                ..Default::default()
+            };
+
+            for _ in 0..10 {
+                let mut ids: Vec<u64> = picker
+                    .pick_batch_distinct(&req, 6)
+                    .unwrap()
+                    .iter()
+                    .map(|n| n.endpoint.id)
+                    .collect();
+                ids.sort_unstable();
+                assert_eq!(ids, vec![0, 1, 2, 3, 4, 5], "{name} repeated a node");
+            }
+            assert!(
+                matches!(
+                    picker.pick_batch_distinct(&req, 7),
+                    Err(LoadBalanceError::InsufficientNodes {
+                        requested: 7,
+                        available: 6
+                    })
+                ),
+                "{name} accepted an oversized batch"
+            );
+        }
+    }
+
+    // Ignores `exclude_ids`, always returning the first of its nodes
+    struct FirstNodePicker {
+        nodes: Vec<Arc<Node>>,
+    }
+
+    impl Picker for FirstNodePicker {
+        fn pick(&self, _req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+            self.nodes
+                .first()
+                .cloned()
+                .ok_or(LoadBalanceError::NoAvailableNodes)
+        }
+    }
+
+    #[test]
+    fn test_pick_batch_distinct_default_drops_repeats() {
+        let picker = FirstNodePicker {
+            nodes: create_test_nodes(3, 1),
+        };
+        let req = RequestMetadata::default();
+
+        assert_eq!(
+            node_ids(&picker.pick_batch_distinct(&req, 1).unwrap()),
+            vec![0]
+        );
+        assert!(matches!(
+            picker.pick_batch_distinct(&req, 2),
+            Err(LoadBalanceError::InsufficientNodes {
+                requested: 2,
+                available: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_consistent_hash_rejects_zero_virtual_factor() {
+        for policy in [
+            VirtualNodePolicy::Proportional,
+            VirtualNodePolicy::Fixed,
+            VirtualNodePolicy::Normalized,
+        ] {
+            let strategy = ConsistentHash {
+                virtual_factor: 0,
+                policy,
+                ..Default::default()
+            };
+            assert!(matches!(
+                strategy.build_picker(Arc::new(create_test_nodes(3, 100))),
+                Err(LoadBalanceError::ZeroVirtualFactor)
+            ));
+        }
+
+        // The smallest valid factor still builds a working ring
+        let picker = ConsistentHash {
+            virtual_factor: 1,
+            ..Default::default()
+        }
+        .build_picker(Arc::new(create_test_nodes(3, 100)))
+        .unwrap();
+        let req = RequestMetadata {
+            hash_key: Some(5),
+            ..Default::default()
+        };
+        assert!(picker.pick(&req).unwrap().endpoint.id < 3);
+    }
+
+    #[test]
+    fn test_dynamic_weight_strategy_snapshots_provider() {
+        use std::sync::atomic::AtomicU32;
+
+        // Node 0's weight comes from an external signal, node 1 stays at 1
+        let signal = Arc::new(AtomicU32::new(1));
+        let provider_signal = signal.clone();
+        let strategy = DynamicWeightStrategy::new(WeightedRoundRobin, move |node: &Node| {
+            if node.endpoint.id == 0 {
+                provider_signal.load(Ordering::Relaxed) as f64
+            } else {
+                1.0
+            }
+        });
+        let balancer = BaseBalancer::new(strategy);
+        balancer.update_nodes(create_test_nodes(2, 100));
+
+        let picker = balancer.picker().unwrap();
+        let counts = count_picks(&picker, 200);
+        assert_eq!(counts[&0], 100);
+        assert_eq!(counts[&1], 100);
+
+        // The running picker keeps its snapshot, the next one sees the new weight
+        signal.store(3, Ordering::Relaxed);
+        let counts = count_picks(&picker, 200);
+        assert_eq!(counts[&0], 100);
+
+        let picker = balancer.picker().unwrap();
+        let counts = count_picks(&picker, 400);
+        assert_eq!(counts[&0], 300);
+        assert_eq!(counts[&1], 100);
+
+        // Picked nodes are the balancer's own, not copies
+        let node = picker.pick(&RequestMetadata::default()).unwrap();
+        node.in_flight.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(
+            balancer
+                .picker()
+                .unwrap()
+                .pick_n(&RequestMetadata::default(), 2)
+                .unwrap()
+                .iter()
+                .map(|n| n.in_flight.load(Ordering::Relaxed))
+                .sum::<usize>(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_least_connection_weighted_prefers_lower_error_rate() {
+        let nodes = create_test_nodes(3, 100);
+        // Equal weights would otherwise tie on load 0 and pick the weight-101 node
+        let nodes: Vec<Arc<Node>> = nodes
+            .iter()
+            .map(|n| Arc::new(n.clone_with_metadata(n.endpoint.clone(), 100)))
+            .collect();
+        nodes[0].fail.store(5, Ordering::Relaxed);
+        nodes[0].success.store(5, Ordering::Relaxed);
+        nodes[1].fail.store(1, Ordering::Relaxed);
+        nodes[1].success.store(9, Ordering::Relaxed);
+        nodes[2].fail.store(3, Ordering::Relaxed);
+        nodes[2].success.store(7, Ordering::Relaxed);
+
+        let picker = LeastConnectionWeighted
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let req = RequestMetadata::default();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+
+        // Load still comes first
+        nodes[1].in_flight.store(1, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+    }
+
+    #[test]
+    fn test_base_balancer_reset_all_stats() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let nodes = create_test_nodes(3, 100);
+        for node in &nodes {
+            node.in_flight.store(2, Ordering::Relaxed);
+            node.success.store(5, Ordering::Relaxed);
+            node.fail.store(1, Ordering::Relaxed);
+            node.last_rtt_ns.store(1_000, Ordering::Relaxed);
+        }
+        balancer.update_nodes(nodes.clone());
+        let picker = balancer.picker().unwrap();
+
+        balancer.reset_all_stats();
+
+        // Reset in place: existing pickers see the zeroed counters too
+        for node in picker.pick_n(&RequestMetadata::default(), 3).unwrap() {
+            assert_eq!(node.in_flight.load(Ordering::Relaxed), 0);
+            assert_eq!(node.success.load(Ordering::Relaxed), 0);
+            assert_eq!(node.fail.load(Ordering::Relaxed), 0);
+            assert_eq!(node.last_rtt_ns.load(Ordering::Relaxed), 0);
+        }
+    }
+
+    #[test]
+    fn test_base_balancer_update_weights() {
+        let balancer = BaseBalancer::new(WeightedRoundRobin);
+        let nodes = create_test_nodes(2, 100);
+        nodes[0].success.store(42, Ordering::Relaxed);
+        nodes[1].last_rtt_ns.store(1_000, Ordering::Relaxed);
+        balancer.update_nodes(nodes.clone());
+        let generation = balancer.generation();
+
+        let old_picker = balancer.picker().unwrap();
+        balancer.update_weights(HashMap::from([(0, 1), (1, 3), (99, 5)]));
+        assert!(balancer.generation() > generation);
+
+        // The next picker follows the new weights
+        let counts = count_picks(&balancer.picker().unwrap(), 400);
+        assert_eq!(counts[&0], 100);
+        assert_eq!(counts[&1], 300);
+
+        // The old one keeps its snapshot
+        let counts = count_picks(&old_picker, 201);
+        assert_eq!(counts[&0], 100);
+        assert_eq!(counts[&1], 101);
+
+        // Same nodes, stats untouched
+        assert_eq!(nodes[0].weight(), 1);
+        assert_eq!(nodes[1].weight(), 3);
+        assert_eq!(nodes[0].success.load(Ordering::Relaxed), 42);
+        assert_eq!(nodes[1].last_rtt_ns.load(Ordering::Relaxed), 1_000);
+    }
+
+    #[test]
+    fn test_base_balancer_update_weights_default_weight() {
+        let config = BalanceConfig {
+            default_weight: 10,
+            ..Default::default()
+        };
+        let balancer = BaseBalancer::with_config(WeightedRoundRobin, config);
+        let nodes = create_test_nodes(2, 5);
+        balancer.update_nodes(nodes.clone());
+
+        // Zero follows the same rule as in `update_nodes`
+        balancer.update_weights(HashMap::from([(0, 0), (1, 30)]));
+        assert_eq!(nodes[0].weight(), 10);
+        assert_eq!(nodes[1].weight(), 30);
+
+        // Without a config zero is stored as given
+        let plain = BaseBalancer::new(WeightedRoundRobin);
+        plain.update_nodes(nodes.clone());
+        plain.update_weights(HashMap::from([(0, 0)]));
+        assert_eq!(nodes[0].weight(), 0);
+    }
+
+    #[test]
+    fn test_consistent_hash_exact_key_cache() {
+        let nodes = Arc::new(create_test_nodes(7, 10));
+        let ring = ConsistentHash::default()
+            .build_picker(nodes.clone())
+            .unwrap();
+        let cached = ConsistentHash {
+            cache_exact_keys: true,
+            ..Default::default()
+        }
+        .build_picker(nodes)
+        .unwrap();
+
+        // More keys than the cache holds, each looked up repeatedly
+        let keys = ConsistentHash::EXACT_KEY_CACHE_CAPACITY as u64 + 500;
+        for round in 0..3 {
+            for key in 0..keys {
+                let req = RequestMetadata {
+                    hash_key: Some(key),
+                    ..Default::default()
+                };
+                let expected = ring.pick(&req).unwrap().endpoint.id;
+                assert_eq!(
+                    cached.pick(&req).unwrap().endpoint.id,
+                    expected,
+                    "key {key} in round {round}"
+                );
+
+                // Cached positions still walk past excluded nodes like the ring does
+                let retry = req.exclude(expected);
+                assert_eq!(
+                    cached.pick(&retry).unwrap().endpoint.id,
+                    ring.pick(&retry).unwrap().endpoint.id
+                );
+            }
+        }
+    }
+
+    fn diff_ids(diff: &NodeDiff) -> (Vec<u64>, Vec<u64>, Vec<u64>) {
+        let ids = |nodes: &[Arc<Node>]| nodes.iter().map(|n| n.endpoint.id).collect();
+        (ids(&diff.added), ids(&diff.removed), ids(&diff.unchanged))
+    }
+
+    #[test]
+    fn test_base_balancer_diff_nodes() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let nodes = create_test_nodes(6, 100);
+        balancer.update_nodes(nodes[..3].to_vec());
+
+        // No change
+        let diff = balancer.diff_nodes(&nodes[..3]);
+        assert!(diff.is_empty());
+        assert_eq!(diff_ids(&diff), (vec![], vec![], vec![0, 1, 2]));
+
+        // Added only
+        let diff = balancer.diff_nodes(&nodes[..5]);
+        assert_eq!(diff_ids(&diff), (vec![3, 4], vec![], vec![0, 1, 2]));
+
+        // Removed only
+        let diff = balancer.diff_nodes(&nodes[1..2]);
+        assert_eq!(diff_ids(&diff), (vec![], vec![0, 2], vec![1]));
+
+        // Mixed; unchanged entries are the balancer's nodes, not the new ones
+        let replacement = Arc::new(Node::builder().id(2).weight(1).build());
+        let diff = balancer.diff_nodes(&[nodes[5].clone(), replacement, nodes[4].clone()]);
+        assert_eq!(diff_ids(&diff), (vec![5, 4], vec![0, 1], vec![2]));
+        assert!(Arc::ptr_eq(&diff.unchanged[0], &nodes[2]));
+
+        // Diffing never touches the balancer
+        let ids: Vec<u64> = balancer
+            .picker()
+            .unwrap()
+            .pick_n(&RequestMetadata::default(), 10)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_sticky_failover() {
+        let nodes = create_test_nodes(5, 100);
+        let ring = ConsistentHash::default()
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let picker = StickyFailover::new(ConsistentHash::default())
+            .with_thresholds(0.5, 10)
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let req = RequestMetadata {
+            hash_key: Some(77),
+            ..Default::default()
+        };
+
+        // Healthy: same node as the plain ring, every time
+        let sticky = ring.pick(&req).unwrap().endpoint.id;
+        for _ in 0..10 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, sticky);
+        }
+
+        // A few failures below `min_requests` do not count yet
+        let target = &nodes[sticky as usize];
+        target.fail.store(9, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, sticky);
+
+        // Unhealthy: the next node clockwise, deterministically
+        target.fail.store(20, Ordering::Relaxed);
+        let next = ring.pick(&req.clone().exclude(sticky)).unwrap().endpoint.id;
+        for _ in 0..10 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, next);
+        }
+
+        // The next node failing too moves one further
+        nodes[next as usize].fail.store(20, Ordering::Relaxed);
+        let after = ring
+            .pick(&req.clone().exclude(sticky).exclude(next))
+            .unwrap()
+            .endpoint
+            .id;
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, after);
+
+        // Recovery re-sticks to the ring node
+        target.success.store(100, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, sticky);
+
+        // With every node unhealthy the ring node is still returned
+        for node in &nodes {
+            node.success.store(0, Ordering::Relaxed);
+            node.fail.store(20, Ordering::Relaxed);
+        }
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, sticky);
+    }
+
+    #[test]
+    fn test_rate_aware_picker() {
+        let limited = |id, rps| Arc::new(Node::builder().id(id).weight(1).rate_limit(rps).build());
+        let unlimited = Arc::new(Node::builder().id(1).weight(1).build());
+        let nodes = vec![limited(0, 5), unlimited];
+        let picker = RateAwarePicker::new(RoundRobin.build_picker(Arc::new(nodes)).unwrap());
+        let req = RequestMetadata::default();
+
+        // Node 0 admits its burst of 5, after that every pick goes to node 1
+        let counts = count_picks(&(Arc::new(picker) as Arc<dyn Picker>), 100);
+        assert_eq!(counts[&0], 5);
+        assert_eq!(counts[&1], 95);
+
+        // Consistent hash sticks to one node, the retry excludes it
+        let nodes = vec![limited(0, 1), limited(1, 1)];
+        let picker = RateAwarePicker::new(
+            ConsistentHash::default()
+                .build_picker(Arc::new(nodes))
+                .unwrap(),
+        );
+        let keyed = RequestMetadata {
+            hash_key: Some(3),
+            ..Default::default()
+        };
+        let first = picker.pick(&keyed).unwrap().endpoint.id;
+        assert_ne!(picker.pick(&keyed).unwrap().endpoint.id, first);
+        assert!(matches!(
+            picker.pick(&keyed),
+            Err(LoadBalanceError::Overloaded)
+        ));
+
+        // Without retries a limited node fails right away
+        let nodes = vec![limited(0, 1), limited(1, 1)];
+        let picker = RateAwarePicker::new(RoundRobin.build_picker(Arc::new(nodes)).unwrap())
+            .with_max_retries(0);
+        assert!(picker.pick(&req).is_ok());
+        assert!(picker.pick(&req).is_ok());
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::Overloaded)
+        ));
+    }
+
+    #[test]
+    fn test_weighted_round_robin_smooth_sequence() {
+        let weighted = |id, weight| Arc::new(Node::builder().id(id).weight(weight).build());
+        let picker = WeightedRoundRobin
+            .build_picker(Arc::new(vec![
+                weighted(0, 5),
+                weighted(1, 1),
+                weighted(2, 1),
+            ]))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        // The smooth WRR sequence for 5:1:1 is a a b a c a a, repeated every cycle
+        let picks: Vec<u64> = (0..14)
+            .map(|_| picker.pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(picks, [0, 0, 1, 0, 2, 0, 0, 0, 0, 1, 0, 2, 0, 0]);
+
+        // A 1:1000 disparity picks the light node exactly once per cycle
+        let picker = WeightedRoundRobin
+            .build_picker(Arc::new(vec![weighted(0, 1), weighted(1, 1000)]))
+            .unwrap();
+        for _ in 0..3 {
+            let light = (0..1001)
+                .filter(|_| picker.pick(&req).unwrap().endpoint.id == 0)
+                .count();
+            assert_eq!(light, 1);
+        }
+    }
+
+    #[test]
+    fn test_deadline_aware() {
+        let nodes = vec![
+            Arc::new(Node::builder().id(0).initial_rtt_ns(100_000_000).build()),
+            Arc::new(Node::builder().id(1).initial_rtt_ns(1_000_000).build()),
+        ];
+        let picker = DeadlineAware::new(RoundRobin, Duration::from_millis(50))
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+        let with_deadline = |deadline| RequestMetadata {
+            deadline,
+            ..Default::default()
+        };
+        let picks = |req: &RequestMetadata| -> Vec<u64> {
+            (0..4)
+                .map(|_| picker.pick(req).unwrap().endpoint.id)
+                .collect()
+        };
+
+        // Tight or missed deadlines always go to the fast node
+        let now = Instant::now();
+        assert_eq!(
+            picks(&with_deadline(Some(now + Duration::from_millis(5)))),
+            [1; 4]
+        );
+        assert_eq!(picks(&with_deadline(Some(now))), [1; 4]);
+
+        // A loose deadline or none at all rotates through both
+        let loose = picks(&with_deadline(Some(now + Duration::from_secs(10))));
+        assert!(loose.contains(&0) && loose.contains(&1));
+        let none = picks(&with_deadline(None));
+        assert!(none.contains(&0) && none.contains(&1));
+    }
+
+    #[test]
+    fn test_weighted_reservoir_distribution() {
+        // Weights 1, 2, 3
+        let nodes = create_test_nodes(3, 1);
+        let picker = WeightedReservoir
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let counts = count_picks(&picker, 6000);
+        for (id, expected) in [(0u64, 1000.0), (1, 2000.0), (2, 3000.0)] {
+            let count = counts.get(&id).copied().unwrap_or(0) as f64;
+            assert!(
+                (count - expected).abs() < expected * 0.15,
+                "node {id} picked {count} times, expected ~{expected}"
+            );
+        }
+
+        // Weight changes apply without a rebuild
+        nodes[0].set_weight(0);
+        nodes[1].set_weight(0);
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts.get(&2), Some(&100));
+
+        // Only zero weights left: uniform among them
+        let req = RequestMetadata::default().exclude(2);
+        let mut seen = [0; 2];
+        for _ in 0..200 {
+            seen[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+        }
+        assert!(seen.iter().all(|&c| c > 50));
+
+        let req = req.exclude(0).exclude(1);
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_fractional_wrr_distribution() {
+        let fractional = |id, weight| {
+            Arc::new(
+                Node::builder()
+                    .id(id)
+                    .weight(100)
+                    .fractional_weight(weight)
+                    .build(),
+            )
+        };
+        let nodes = vec![fractional(0, 0.5), fractional(1, 1.0), fractional(2, 1.5)];
+        let picker = FractionalWRR::default()
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+
+        // Exact 1:2:3 over every full cycle of six picks
+        for _ in 0..100 {
+            let mut counts = [0; 3];
+            for _ in 0..6 {
+                counts[picker
+                    .pick(&RequestMetadata::default())
+                    .unwrap()
+                    .endpoint
+                    .id as usize] += 1;
+            }
+            assert_eq!(counts, [1, 2, 3]);
+        }
+
+        // Without a fractional weight the integer weight is used
+        let nodes = create_test_nodes(2, 1);
+        assert_eq!(nodes[1].fractional_weight(), 2.0);
+        let counts = count_picks(
+            &FractionalWRR::default()
+                .build_picker(Arc::new(nodes))
+                .unwrap(),
+            300,
+        );
+        assert_eq!(counts[&0], 100);
+        assert_eq!(counts[&1], 200);
+    }
+
+    fn node_ids(nodes: &[Arc<Node>]) -> Vec<u64> {
+        nodes.iter().map(|n| n.endpoint.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_changes() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let mut rx = balancer.subscribe_changes();
+        let nodes = create_test_nodes(4, 1);
+
+        balancer.update_nodes(nodes[..3].to_vec());
+        match rx.recv().await.unwrap() {
+            NodeChange::Added(added) => assert_eq!(node_ids(&added), [0, 1, 2]),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        balancer.update_nodes(nodes[1..].to_vec());
+        match rx.recv().await.unwrap() {
+            NodeChange::Added(added) => assert_eq!(node_ids(&added), [3]),
+            other => panic!("unexpected {other:?}"),
+        }
+        match rx.recv().await.unwrap() {
+            NodeChange::Removed(removed) => assert_eq!(node_ids(&removed), [0]),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        // The same ids again change nothing
+        balancer.update_nodes(nodes[1..].to_vec());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_changes_lagged() {
+        use tokio::sync::broadcast::error::RecvError;
+
+        let balancer = BaseBalancer::new(RoundRobin).with_change_buffer(2);
+        let mut rx = balancer.subscribe_changes();
+        let nodes = create_test_nodes(4, 1);
+
+        // Four events for a buffer of two; the writer does not wait for the receiver
+        for node in &nodes[..4] {
+            balancer.update_nodes(vec![node.clone()]);
+        }
+        assert!(matches!(rx.recv().await, Err(RecvError::Lagged(5))));
+        // The receiver picks up the newest events
+        match rx.recv().await.unwrap() {
+            NodeChange::Added(added) => assert_eq!(node_ids(&added), [3]),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(matches!(rx.recv().await.unwrap(), NodeChange::Removed(_)));
+    }
+
+    #[test]
+    fn test_strategy_names() {
+        let strategies: Vec<(Box<dyn BalanceStrategy>, &str)> = vec![
+            (Box::new(RoundRobin), "round_robin"),
+            (Box::new(WeightedRoundRobin), "weighted_round_robin"),
+            (
+                Box::new(RoundRobinWithWeightedJump),
+                "round_robin_with_weighted_jump",
+            ),
+            (
+                Box::new(PowerOfTwoChoices::seeded(7)),
+                "seeded_power_of_two_choices",
+            ),
+            (Box::new(GradientDescent::default()), "gradient_descent"),
+            (Box::new(LeastLoad::default()), "least_load"),
+            (Box::new(PowerOfKChoices::new(3)), "power_of_k_choices"),
+            (
+                Box::new(ConcurrencyLimited::new(RoundRobin)),
+                "concurrency_limited(round_robin)",
+            ),
+            (
+                Box::new(FractionalWRR::default()),
+                "fractional_weighted_round_robin",
+            ),
+            (Box::new(PowerOfTwoChoices), "power_of_two_choices"),
+            (Box::new(WeightedRandom), "weighted_random"),
+            (Box::new(WeightedReservoir), "weighted_reservoir"),
+            (Box::new(LeastConnection), "least_connection"),
+            (
+                Box::new(LeastConnectionWeighted),
+                "least_connection_weighted",
+            ),
+            (Box::new(ResponseTimeWeighted), "response_time_weighted"),
+            (Box::new(PeakEwma::default()), "peak_ewma"),
+            (Box::new(ConsistentHash::default()), "consistent_hash"),
+            (
+                Box::new(StickyFailover::new(ConsistentHash::default())),
+                "sticky_failover(consistent_hash)",
+            ),
+            (
+                Box::new(WeightedRandom.with_weights(vec![1])),
+                "static_weights(weighted_random)",
+            ),
+            (
+                Box::new(DynamicWeightStrategy::new(
+                    WeightedRoundRobin,
+                    |_: &Node| 1.0,
+                )),
+                "dynamic_weights(weighted_round_robin)",
+            ),
+            (
+                Box::new(DeadlineAware::new(RoundRobin, Duration::from_millis(5))),
+                "deadline_aware(round_robin)",
+            ),
+            (
+                Box::new(Shadow::new(RoundRobin, WeightedRandom)),
+                "shadow(round_robin, weighted_random)",
+            ),
+            (
+                Box::new(CircuitBreaker::new(LeastConnection)),
+                "circuit_breaker(least_connection)",
+            ),
+            (
+                Box::new(ZoneAware::new(RoundRobin, "a")),
+                "zone_aware(round_robin)",
+            ),
+            (
+                Box::new(TopologySpread::new(RoundRobin, "zone", 1)),
+                "topology_spread(round_robin)",
+            ),
+            (Box::new(Sticky::new(RoundRobin)), "sticky(round_robin)"),
+            (
+                Box::new(WeightDecay::new(
+                    WeightedRoundRobin,
+                    Duration::from_secs(1),
+                    Duration::from_secs(10),
+                )),
+                "weight_decay(weighted_round_robin)",
+            ),
+            (
+                Box::new(HotStandby::new(RoundRobin, "active", "standby")),
+                "hot_standby(round_robin)",
+            ),
+            (
+                Box::new(Tiered::by_tag(RoundRobin, "tier", "primary", "backup")),
+                "tiered(round_robin)",
+            ),
+            (
+                Box::new(WeightedRandom.with_config(WeightedRandomConfig::default())),
+                "slow_aware_weighted_random",
+            ),
+            (
+                Box::new(CapacityLimited::new(RoundRobin, 8)),
+                "capacity_limited(round_robin)",
+            ),
+            (
+                Box::new(FaultTolerant::new(LeastConnection)),
+                "fault_tolerant(least_connection)",
+            ),
+            (
+                Box::new(StrategyCompose::new(ConsistentHash::default(), RoundRobin)),
+                "compose(consistent_hash, round_robin)",
+            ),
+            (
+                Box::new(CircuitBreaker::new(ZoneAware::new(
+                    PeakEwma::default(),
+                    "a",
+                ))),
+                "circuit_breaker(zone_aware(peak_ewma))",
+            ),
+        ];
+
+        for (strategy, expected) in strategies {
+            assert_eq!(strategy.name(), expected);
+        }
+    }
+
+    // Every node but 3 fails most requests, while the unhealthy ones look best otherwise
+    fn assert_fault_tolerant<S: BalanceStrategy>(strategy: S) {
+        let name = strategy.name().into_owned();
+        let nodes = create_test_nodes(5, 1);
+        for (i, node) in nodes.iter().enumerate() {
+            if i == 3 {
+                node.in_flight.store(10, Ordering::Relaxed);
+                node.last_rtt_ns.store(500_000_000, Ordering::Relaxed);
+            } else {
+                node.fail.store(20, Ordering::Relaxed);
+            }
+        }
+        let picker = FaultTolerant::new(strategy)
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        for key in 0..50 {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 3, "{name}");
+        }
+
+        // All unhealthy: best effort still returns a node
+        nodes[3].fail.store(20, Ordering::Relaxed);
+        let req = RequestMetadata {
+            hash_key: Some(1),
+            ..Default::default()
+        };
+        assert!(picker.pick(&req).is_ok(), "{name}");
+    }
+
+    #[test]
+    fn test_fault_tolerant_one_healthy_among_unhealthy() {
+        assert_fault_tolerant(RoundRobin);
+        assert_fault_tolerant(WeightedRoundRobin);
+        assert_fault_tolerant(PowerOfTwoChoices);
+        assert_fault_tolerant(WeightedRandom);
+        assert_fault_tolerant(LeastConnection);
+        assert_fault_tolerant(LeastConnectionWeighted);
+        assert_fault_tolerant(ResponseTimeWeighted);
+        assert_fault_tolerant(PeakEwma::default());
+        assert_fault_tolerant(ConsistentHash::default());
+    }
+
+    #[test]
+    fn test_scan_pickers_one_healthy_among_unhealthy() {
+        let strategies: Vec<Box<dyn BalanceStrategy>> = vec![
+            Box::new(LeastConnection),
+            Box::new(LeastConnectionWeighted),
+            Box::new(LeastLoad::default()),
+            Box::new(ResponseTimeWeighted),
+        ];
+        for strategy in strategies {
+            let name = strategy.name().into_owned();
+            // Node 3 is the only healthy node, and the worst by load and RTT
+            let nodes = create_test_nodes(5, 1);
+            for (i, node) in nodes.iter().enumerate() {
+                if i == 3 {
+                    node.in_flight.store(10, Ordering::Relaxed);
+                    node.last_rtt_ns.store(500_000_000, Ordering::Relaxed);
+                } else {
+                    node.fail.store(20, Ordering::Relaxed);
+                }
+            }
+            let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+            let req = RequestMetadata::default();
+
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 3, "{name}");
+            let batch = picker.pick_batch_distinct(&req, 2).unwrap();
+            assert_eq!(batch[0].endpoint.id, 3, "{name}");
+
+            // All unhealthy: best effort by the strategy's own ranking
+            nodes[3].fail.store(20, Ordering::Relaxed);
+            assert_ne!(picker.pick(&req).unwrap().endpoint.id, 3, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_health_filter() {
+        let health = HealthFilter::default();
+        let nodes = create_test_nodes(3, 1);
+
+        // Too few requests to judge, then over the error threshold
+        nodes[0].fail.store(9, Ordering::Relaxed);
+        assert!(health.is_healthy(&nodes[0]));
+        nodes[0].fail.store(10, Ordering::Relaxed);
+        assert!(!health.is_healthy(&nodes[0]));
+
+        // At the in-flight cap
+        let capped = Node::builder().id(9).max_in_flight(2).build();
+        capped.in_flight.store(2, Ordering::Relaxed);
+        assert!(!health.is_healthy(&capped));
+
+        let req = RequestMetadata::default();
+        let filtered = health.exclude_unhealthy(&nodes, &req).unwrap();
+        assert_eq!(filtered.exclude_ids.as_slice(), [0]);
+
+        // Nothing healthy left after the caller's own exclusions
+        let req = RequestMetadata::default().exclude(1).exclude(2);
+        assert!(health.exclude_unhealthy(&nodes, &req).is_none());
+    }
+
+    #[test]
+    fn test_random_subset() {
+        let nodes = Arc::new(create_test_nodes(1000, 1));
+        let strategy = RandomSubset::new(5, RoundRobin);
+        let subset_ids = |picker: &Arc<dyn Picker>| {
+            let mut ids: Vec<u64> = count_picks(picker, 50).into_keys().collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        // Round robin over the subset visits exactly k nodes of the pool
+        let first = strategy.build_picker(nodes.clone()).unwrap();
+        let first_ids = subset_ids(&first);
+        assert_eq!(first_ids.len(), 5);
+        assert!(first_ids.iter().all(|&id| id < 1000));
+
+        // Every build samples afresh
+        let second = strategy.build_picker(nodes.clone()).unwrap();
+        assert_ne!(subset_ids(&second), first_ids);
+
+        // Small pools are used whole
+        let small = RandomSubset::new(5, RoundRobin)
+            .build_picker(Arc::new(create_test_nodes(3, 1)))
+            .unwrap();
+        assert_eq!(subset_ids(&small), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_consistent_hash_seed() {
+        let nodes = Arc::new(create_test_nodes(5, 100));
+        let placement = |seed: u64| -> Vec<u64> {
+            let picker = ConsistentHash::default()
+                .with_seed(seed)
+                .build_picker(nodes.clone())
+                .unwrap();
+            (0..1000)
+                .map(|k| {
+                    let req = RequestMetadata {
+                        hash_key: Some(k),
+                        ..Default::default()
+                    };
+                    picker.pick(&req).unwrap().endpoint.id
+                })
+                .collect()
+        };
+
+        // Independently built rings with the same seed agree on every key
+        assert_eq!(placement(7), placement(7));
+        assert_eq!(placement(0), placement(0));
+
+        let moved = placement(7)
+            .iter()
+            .zip(placement(8))
+            .filter(|(a, b)| **a != *b)
+            .count();
+        assert!(moved > 100, "only {moved} of 1000 keys moved");
+    }
+
+    #[test]
+    fn test_weighted_random_checked_build() {
+        let nodes = Arc::new(create_test_nodes(3, 0));
+        nodes[0].set_weight(0);
+        nodes[1].set_weight(0);
+        nodes[2].set_weight(0);
+
+        // The lenient path degrades to uniform picks, the checked one reports it
+        assert!(WeightedRandom.build_picker(nodes.clone()).is_ok());
+        assert!(matches!(
+            WeightedRandom.build_picker_checked(nodes.clone()),
+            Err(LoadBalanceError::InvalidWeights)
+        ));
+
+        nodes[1].set_weight(5);
+        let picker = WeightedRandom.build_picker_checked(nodes).unwrap();
+        let req = RequestMetadata::default();
+        for _ in 0..50 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+        }
+    }
+
+    #[test]
+    fn test_weighted_round_robin_skewed_weights() {
+        let nodes = create_test_nodes(2, 1);
+        nodes[1].set_weight(100);
+        let picker = WeightedRoundRobin.build_picker(Arc::new(nodes)).unwrap();
+        let req = RequestMetadata::default();
+
+        // Every window of 101 picks holds the light node exactly once
+        for _ in 0..5 {
+            let ids: Vec<u64> = (0..101)
+                .map(|_| picker.pick(&req).unwrap().endpoint.id)
+                .collect();
+            assert_eq!(ids.iter().filter(|&&id| id == 0).count(), 1);
+            assert_eq!(ids.iter().filter(|&&id| id == 1).count(), 100);
+        }
+    }
+
+    // Hold every picked request open and return the in-flight total of zone "a", "b"
+    fn spread_loads(
+        picker: &Arc<dyn Picker>,
+        nodes: &[Arc<Node>],
+        rounds: usize,
+    ) -> Vec<(usize, usize)> {
+        let req = RequestMetadata::default();
+        (0..rounds)
+            .map(|_| {
+                picker
+                    .pick(&req)
+                    .unwrap()
+                    .in_flight
+                    .fetch_add(1, Ordering::Relaxed);
+                let load = |zone: &str| -> usize {
+                    nodes
+                        .iter()
+                        .filter(|n| n.tags["zone"] == zone)
+                        .map(|n| n.in_flight.load(Ordering::Relaxed))
+                        .sum()
+                };
+                (load("a"), load("b"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_topology_spread_max_skew() {
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "a"), ("zone", "a"), ("zone", "b")]);
+        let picker = TopologySpread::new(LeastConnection, "zone", 2)
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        // Per-node balance alone would give zone "a" three times the load of "b"
+        for (a, b) in spread_loads(&picker, &nodes, 40) {
+            assert!(a.abs_diff(b) <= 2, "zone loads {a} and {b}");
+        }
+    }
+
+    #[test]
+    fn test_topology_spread_fewer_nodes_fewer_requests() {
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "b"), ("zone", "a"), ("zone", "a")]);
+        let picker = TopologySpread::new(LeastConnection, "zone", 100)
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let loads = spread_loads(&picker, &nodes, 40);
+        assert_eq!(loads.last(), Some(&(30, 10)));
+        // Within a zone the inner strategy spreads the load
+        assert!(nodes
+            .iter()
+            .all(|n| n.in_flight.load(Ordering::Relaxed) == 10));
+
+        // No zone within the skew: the least loaded one is used
+        let nodes = tagged_nodes(&[("zone", "a"), ("zone", "b")]);
+        nodes[0].in_flight.store(5, Ordering::Relaxed);
+        let picker = TopologySpread::new(RoundRobin, "zone", 0)
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+        let req = RequestMetadata::default();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+    }
+
+    #[test]
+    fn test_sticky_sessions() {
+        let strategy = Sticky::new(RoundRobin);
+        let nodes = create_test_nodes(4, 10);
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        let session = |key: u64| RequestMetadata {
+            hash_key: Some(key),
+            ..Default::default()
+        };
+
+        let first = picker.pick(&session(7)).unwrap().endpoint.id;
+        let other = picker.pick(&session(8)).unwrap().endpoint.id;
+        assert_ne!(first, other);
+        for _ in 0..100 {
+            assert_eq!(picker.pick(&session(7)).unwrap().endpoint.id, first);
+            // Keyless requests keep rotating
+            picker.pick(&RequestMetadata::default()).unwrap();
+        }
+        assert_eq!(strategy.sessions(), 2);
+
+        // The table outlives the picker; a removed node releases its sessions
+        let rebuilt = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+        assert_eq!(rebuilt.pick(&session(7)).unwrap().endpoint.id, first);
+        let remaining: Vec<_> = nodes
+            .iter()
+            .filter(|n| n.endpoint.id != first)
+            .cloned()
+            .collect();
+        let shrunk = strategy.build_picker(Arc::new(remaining)).unwrap();
+        let moved = shrunk.pick(&session(7)).unwrap().endpoint.id;
+        assert_ne!(moved, first);
+        for _ in 0..100 {
+            assert_eq!(shrunk.pick(&session(7)).unwrap().endpoint.id, moved);
+        }
+
+        // Unhealthy and excluded nodes are skipped too
+        nodes[moved as usize].fail.store(100, Ordering::Relaxed);
+        assert_ne!(shrunk.pick(&session(7)).unwrap().endpoint.id, moved);
+        let current = shrunk.pick(&session(7)).unwrap().endpoint.id;
+        let excluded = session(7).exclude(current);
+        assert_ne!(shrunk.pick(&excluded).unwrap().endpoint.id, current);
+    }
+
+    #[test]
+    fn test_sticky_sessions_expire() {
+        let picker = Sticky::new(RoundRobin)
+            .with_ttl(Duration::ZERO)
+            .build_picker(Arc::new(create_test_nodes(2, 10)))
+            .unwrap();
+        let req = RequestMetadata {
+            hash_key: Some(1),
+            ..Default::default()
+        };
+        let ids: Vec<u64> = (0..4)
+            .map(|_| picker.pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_capacity_limited_skips_saturated_nodes() {
+        let nodes = create_test_nodes(3, 10);
+        nodes[1].in_flight.store(2, Ordering::Relaxed);
+        let config = BalanceConfig {
+            max_in_flight_per_node: Some(2),
+            ..Default::default()
+        };
+        let picker = CapacityLimited::from_config(RoundRobin, &config)
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let req = RequestMetadata::default();
+        for _ in 0..20 {
+            assert_ne!(picker.pick(&req).unwrap().endpoint.id, 1);
+        }
+
+        // Saturated everywhere: refuse instead of piling on
+        nodes[0].in_flight.store(2, Ordering::Relaxed);
+        nodes[2].in_flight.store(3, Ordering::Relaxed);
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::Overloaded)
+        ));
+        nodes[2].in_flight.store(1, Ordering::Relaxed);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+        assert!(matches!(
+            picker.pick(&req.clone().exclude(2)),
+            Err(LoadBalanceError::Overloaded)
+        ));
+
+        // No cap configured: saturated nodes are still picked
+        let picker = CapacityLimited::from_config(RoundRobin, &BalanceConfig::default())
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+        let mut ids: Vec<u64> = (0..3)
+            .map(|_| picker.pick(&req).unwrap().endpoint.id)
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    struct SlowPicker {
+        inner: Arc<dyn Picker>,
+        delay: Duration,
+    }
+
+    impl Picker for SlowPicker {
+        fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+            std::thread::sleep(self.delay);
+            self.inner.pick(req)
+        }
+    }
+
+    #[test]
+    fn test_pick_with_deadline() {
+        let picker = RoundRobin
+            .build_picker(Arc::new(create_test_nodes(2, 10)))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        let later = Instant::now() + Duration::from_secs(60);
+        assert!(picker.pick_with_deadline(&req, later).is_ok());
+        let past = Instant::now() - Duration::from_millis(1);
+        assert!(matches!(
+            picker.pick_with_deadline(&req, past),
+            Err(LoadBalanceError::Timeout)
+        ));
+        // Pick errors win over the deadline
+        let all = req.clone().exclude(0).exclude(1);
+        assert!(matches!(
+            picker.pick_with_deadline(&all, past),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+
+        let slow = SlowPicker {
+            inner: picker,
+            delay: Duration::from_millis(20),
+        };
+        let deadline = Instant::now() + Duration::from_millis(5);
+        assert!(matches!(
+            slow.pick_with_deadline(&req, deadline),
+            Err(LoadBalanceError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_prewarm_publishes_picker() {
+        let balancer = BaseBalancer::new(ConsistentHash::default());
+        balancer.update_nodes(create_test_nodes(3, 10));
+        balancer.prewarm().unwrap();
+        let req = RequestMetadata {
+            hash_key: Some(5),
+            ..Default::default()
+        };
+        let first = balancer.published_picker().unwrap();
+        assert!(Arc::ptr_eq(&first, &balancer.published_picker().unwrap()));
+
+        // The old picker keeps serving until the next prewarm
+        let replacement: Vec<_> = (10..13)
+            .map(|id| Arc::new(Node::builder().id(id).weight(10).build()))
+            .collect();
+        balancer.update_nodes(replacement);
+        let served = balancer.published_picker().unwrap();
+        assert!(served.pick(&req).unwrap().endpoint.id < 3);
+        balancer.prewarm().unwrap();
+        let served = balancer.published_picker().unwrap();
+        assert!(served.pick(&req).unwrap().endpoint.id >= 10);
+    }
+
+    #[test]
+    fn test_prewarm_concurrent_swap() {
+        let balancer = Arc::new(BaseBalancer::new(ConsistentHash {
+            virtual_factor: 160,
+            ..Default::default()
+        }));
+        balancer.update_nodes(create_test_nodes(8, 10));
+        let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|t| {
+                let balancer = balancer.clone();
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    let mut picks = 0u64;
+                    while !stop.load(Ordering::Relaxed) || picks < 1000 {
+                        let req = RequestMetadata {
+                            hash_key: Some(picks * 4 + t),
+                            ..Default::default()
+                        };
+                        balancer.published_picker().unwrap().pick(&req).unwrap();
+                        picks += 1;
+                    }
+                })
+            })
+            .collect();
+
+        for round in 0..50 {
+            let count = 4 + round % 5;
+            balancer.update_nodes(create_test_nodes(count, 10));
+            balancer.prewarm().unwrap();
+        }
+        stop.store(true, Ordering::Relaxed);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_weighted_random_demotes_slow_nodes() {
+        let nodes = create_test_nodes(4, 10);
+        for node in &nodes {
+            node.set_weight(10);
+            node.last_rtt_ns.store(1_000_000, Ordering::Relaxed);
+        }
+        // 10x the RTT of its peers
+        nodes[3].last_rtt_ns.store(10_000_000, Ordering::Relaxed);
+
+        let config = WeightedRandomConfig {
+            slow_threshold_ns: 5_000_000,
+            ..Default::default()
+        };
+        let strategy = WeightedRandom.with_config(config);
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+
+        let rounds = 31_000;
+        let counts = count_picks(&picker, rounds);
+        let ratio = counts.get(&3).copied().unwrap_or(0) as f64 / counts[&0] as f64;
+        assert!((0.07..0.13).contains(&ratio), "slow node ratio {ratio}");
+
+        // The linear path used with exclusions applies the same penalty
+        let req = RequestMetadata::default().exclude(1);
+        let mut slow = 0;
+        let mut peer = 0;
+        for _ in 0..rounds {
+            match picker.pick(&req).unwrap().endpoint.id {
+                0 => peer += 1,
+                3 => slow += 1,
+                _ => {}
+            }
+        }
+        let ratio = slow as f64 / peer as f64;
+        assert!((0.07..0.13).contains(&ratio), "slow node ratio {ratio}");
+
+        // Recovered nodes get their full weight back on the next build
+        nodes[3].last_rtt_ns.store(1_000_000, Ordering::Relaxed);
+        let counts = count_picks(&strategy.build_picker(Arc::new(nodes)).unwrap(), 8_000);
+        assert!(counts[&3] > 1_500);
+    }
+
+    #[test]
+    fn test_disabled_node_skipped_by_every_strategy() {
+        let strategies: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("rr", Box::new(RoundRobin)),
+            ("wrr", Box::new(WeightedRoundRobin)),
+            ("fractional_wrr", Box::new(FractionalWRR::default())),
+            ("p2c", Box::new(PowerOfTwoChoices)),
+            ("wr", Box::new(WeightedRandom)),
+            (
+                "slow_aware_wr",
+                Box::new(WeightedRandom.with_config(WeightedRandomConfig::default())),
+            ),
+            ("reservoir", Box::new(WeightedReservoir)),
+            ("lc", Box::new(LeastConnection)),
+            ("lcw", Box::new(LeastConnectionWeighted)),
+            ("rtw", Box::new(ResponseTimeWeighted)),
+            ("peak_ewma", Box::new(PeakEwma::default())),
+            ("ch", Box::new(ConsistentHash::default())),
+            (
+                "sticky_failover",
+                Box::new(StickyFailover::new(ConsistentHash::default())),
+            ),
+            ("circuit_breaker", Box::new(CircuitBreaker::new(RoundRobin))),
+        ];
+
+        for (name, strategy) in strategies {
+            let nodes = create_test_nodes(3, 10);
+            let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+            let keyed = |key: u64| RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+
+            nodes[1].set_enabled(false);
+            for key in 0..300 {
+                let picked = picker.pick(&keyed(key)).unwrap();
+                assert_ne!(picked.endpoint.id, 1, "{name} picked a disabled node");
+            }
+
+            // Load the others so least-loaded strategies prefer node 1 again
+            nodes[1].set_enabled(true);
+            nodes[0].in_flight.store(5, Ordering::Relaxed);
+            nodes[2].in_flight.store(5, Ordering::Relaxed);
+            assert!(
+                (0..300).any(|key| picker.pick(&keyed(key)).unwrap().endpoint.id == 1),
+                "{name} did not pick the re-enabled node"
+            );
+
+            for node in &nodes {
+                node.set_enabled(false);
+            }
+            assert!(
+                matches!(
+                    picker.pick(&keyed(1)),
+                    Err(LoadBalanceError::NoAvailableNodes)
+                ),
+                "{name} did not fail with every node disabled"
+            );
+        }
+    }
+
+    #[test]
+    fn test_consistent_hash_normalized_follows_fractional_weights() {
+        // Ratios 1 : 1.5 : 2.5, which integer weights could only express as 2 : 3 : 5
+        let weights = [0.4, 0.6, 1.0];
+        let nodes: Vec<Arc<Node>> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                Arc::new(
+                    Node::builder()
+                        .id(i as u64)
+                        .weight(1)
+                        .fractional_weight(w)
+                        .build(),
+                )
+            })
+            .collect();
+        let picker = ConsistentHash {
+            virtual_factor: 100,
+            policy: VirtualNodePolicy::Normalized,
+            ..Default::default()
+        }
+        .build_picker(Arc::new(nodes))
+        .unwrap();
+
+        let keys = 60_000;
+        let mut counts = [0usize; 3];
+        for key in 0..keys {
+            let req = RequestMetadata {
+                hash_key: Some(key),
+                ..Default::default()
+            };
+            counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+        }
+
+        let total: f64 = weights.iter().sum();
+        for (i, &w) in weights.iter().enumerate() {
+            let expected = keys as f64 * w / total;
+            let ratio = counts[i] as f64 / expected;
+            assert!((0.8..=1.2).contains(&ratio), "node {i}: {counts:?}");
+        }
+    }
+
+    // Nodes 0 and 1 active, 2 and 3 standby
+    #[test]
+    fn test_hot_standby_prefers_healthy_actives() {
+        let nodes = tagged_nodes(&[
+            ("active", ""),
+            ("active", ""),
+            ("standby", ""),
+            ("standby", ""),
+        ]);
+        let picker = HotStandby::new(RoundRobin, "active", "standby")
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&0] + counts[&1], 100);
+
+        // One unhealthy active still keeps the standbys idle
+        nodes[0].fail.store(20, Ordering::Relaxed);
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&1], 100);
+    }
+
+    #[test]
+    fn test_hot_standby_falls_back_when_actives_fail() {
+        let nodes = tagged_nodes(&[
+            ("active", ""),
+            ("active", ""),
+            ("standby", ""),
+            ("standby", ""),
+        ]);
+        let picker = HotStandby::new(RoundRobin, "active", "standby")
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        nodes[0].fail.store(20, Ordering::Relaxed);
+        nodes[1].set_enabled(false);
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts[&2] + counts[&3], 100);
+
+        // Back to the actives as soon as one recovers
+        nodes[1].set_enabled(true);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+
+        // Excluding every active, e.g. on retry, also reaches the standbys
+        let retry = req.clone().exclude(0).exclude(1);
+        assert!(picker.pick(&retry).unwrap().endpoint.id >= 2);
+
+        let nothing_left = retry.exclude(2).exclude(3);
+        assert!(matches!(
+            picker.pick(&nothing_left),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    // Pretend `node` last reported an RTT `ago`
+    fn report_rtt_ago(node: &Node, ago: Duration) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        node.last_rtt_ns.store(1_000_000, Ordering::Relaxed);
+        node.last_rtt_updated_at
+            .store((now - ago).as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn test_weight_decay_schedule() {
+        let decay = WeightDecay::new(
+            WeightedRoundRobin,
+            Duration::from_secs(5),
+            Duration::from_secs(30),
+        )
+        .with_decay_per_second(0.5);
+
+        assert_eq!(decay.decayed_weight(800, Duration::ZERO), 800);
+        assert_eq!(decay.decayed_weight(800, Duration::from_secs(5)), 800);
+        assert_eq!(decay.decayed_weight(800, Duration::from_secs(6)), 400);
+        assert_eq!(decay.decayed_weight(800, Duration::from_millis(7500)), 141);
+        assert_eq!(decay.decayed_weight(800, Duration::from_secs(8)), 100);
+        assert_eq!(decay.decayed_weight(800, Duration::from_secs(29)), 0);
+        assert_eq!(decay.decayed_weight(800, Duration::from_secs(30)), 0);
+
+        // Out of range multipliers are clamped
+        let no_decay =
+            WeightDecay::new(RoundRobin, Duration::from_secs(5), Duration::from_secs(30))
+                .with_decay_per_second(2.0);
+        assert_eq!(no_decay.decayed_weight(800, Duration::from_secs(20)), 800);
+    }
+
+    #[test]
+    fn test_weight_decay_reduces_stale_nodes() {
+        let nodes: Vec<Arc<Node>> = (0..3)
+            .map(|i| Arc::new(Node::builder().id(i).weight(100).build()))
+            .collect();
+        // Node 0 reported recently, node 1 is 2s past the threshold, node 2 never reported
+        nodes[0].record_rtt(1_000_000);
+        report_rtt_ago(&nodes[1], Duration::from_secs(3));
+        let strategy = WeightDecay::new(
+            WeightedRoundRobin,
+            Duration::from_secs(1),
+            Duration::from_secs(10),
+        )
+        .with_decay_per_second(0.5);
+
+        let counts = count_picks(
+            &strategy.build_picker(Arc::new(nodes.clone())).unwrap(),
+            900,
+        );
+        assert_eq!(counts[&0], 400);
+        assert_eq!(counts[&1], 100);
+        assert_eq!(counts[&2], 400);
+
+        // A fresh report restores the full weight
+        nodes[1].record_rtt(1_000_000);
+        let counts = count_picks(
+            &strategy.build_picker(Arc::new(nodes.clone())).unwrap(),
+            900,
+        );
+        assert_eq!(counts[&1], 300);
+    }
+
+    #[test]
+    fn test_weight_decay_skips_expired_nodes() {
+        let nodes = create_test_nodes(2, 10);
+        report_rtt_ago(&nodes[0], Duration::from_secs(20));
+        let strategy = WeightDecay::new(
+            LeastConnection,
+            Duration::from_secs(1),
+            Duration::from_secs(10),
+        );
+        let picker = strategy.build_picker(Arc::new(nodes.clone())).unwrap();
+
+        // LeastConnection ignores weights and would pick node 0 on ties
+        let counts = count_picks(&picker, 50);
+        assert_eq!(counts.get(&0), None);
+
+        // With nothing fresher left, the expired node is still picked
+        let req = RequestMetadata::default().exclude(1);
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+    }
+
+    #[test]
+    fn test_pick_with_stats_snapshots_picked_node() {
+        let nodes = create_test_nodes(3, 10);
+        for (i, node) in nodes.iter().enumerate() {
+            node.in_flight.store(i + 1, Ordering::Relaxed);
+            node.success.store(10 * i as u64, Ordering::Relaxed);
+            node.fail.store(i as u64, Ordering::Relaxed);
+            node.last_rtt_ns
+                .store(1_000 * (i as u64 + 1), Ordering::Relaxed);
+        }
+        let picker = LeastConnection
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let (node, stats) = picker.pick_with_stats(&RequestMetadata::default()).unwrap();
+        assert_eq!(node.endpoint.id, 0);
+        assert_eq!(
+            stats,
+            NodeStatsSnapshot {
+                in_flight: 1,
+                success: 0,
+                fail: 0,
+                last_rtt_ns: 1_000,
+            }
+        );
+
+        // Later updates do not leak into the returned snapshot
+        node.in_flight.fetch_add(5, Ordering::Relaxed);
+        assert_eq!(stats.in_flight, 1);
+        assert_eq!(node.stats_snapshot().in_flight, 6);
+    }
+
+    #[test]
+    fn test_consistent_hash_auto_virtual_factor_bounds_imbalance() {
+        assert_eq!(ConsistentHash::virtual_factor_for(10, 0.2), 231);
+        // Tighter targets need quadratically more virtual nodes, up to the cap
+        assert_eq!(ConsistentHash::virtual_factor_for(10, 0.1), 922);
+        assert_eq!(ConsistentHash::virtual_factor_for(10, 0.01), 1024);
+        assert_eq!(ConsistentHash::virtual_factor_for(10, 0.0), 1024);
+        assert_eq!(ConsistentHash::virtual_factor_for(10, f64::NAN), 1024);
+        assert_eq!(ConsistentHash::virtual_factor_for(10, 10.0), 1);
+
+        for (count, target) in [(5, 0.3), (10, 0.2), (20, 0.15)] {
+            let nodes: Vec<Arc<Node>> = (0..count as u64)
+                .map(|i| Arc::new(Node::builder().id(i).weight(1).build()))
+                .collect();
+            let picker = ConsistentHash::auto_virtual_factor(target)
+                .build_picker(Arc::new(nodes))
+                .unwrap();
+            let keys = 100_000u64;
+            let mut counts = vec![0usize; count];
+            for key in 0..keys {
+                let req = RequestMetadata {
+                    hash_key: Some(key),
+                    ..Default::default()
+                };
+                counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+            }
+
+            let mean = keys as f64 / count as f64;
+            let imbalance = *counts.iter().max().unwrap() as f64 / mean - 1.0;
+            assert!(
+                imbalance <= target,
+                "{count} nodes: imbalance {imbalance:.3} above {target}"
+            );
+        }
+    }
+
+    // Nodes 0..primaries on tier "primary", the rest on tier "backup"
+    #[test]
+    fn test_tiered_prefers_primary_pool() {
+        let nodes = tagged_nodes(&[
+            ("tier", "primary"),
+            ("tier", "primary"),
+            ("tier", "backup"),
+            ("tier", "backup"),
+        ]);
+        let picker = Tiered::by_tag(RoundRobin, "tier", "primary", "backup")
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts[&0], 50);
+        assert_eq!(counts[&1], 50);
+
+        // A single healthy primary keeps all traffic in the primary pool
+        nodes[0].fail.store(20, Ordering::Relaxed);
+        let counts = count_picks(&picker, 100);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&1], 100);
+    }
+
+    #[test]
+    fn test_tiered_activates_backup_pool() {
+        let nodes = tagged_nodes(&[
+            ("tier", "primary"),
+            ("tier", "primary"),
+            ("tier", "backup"),
+            ("tier", "backup"),
+        ]);
+        let picker = Tiered::by_tag(LeastConnection, "tier", "primary", "backup")
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+
+        for node in &nodes[..2] {
+            node.fail.store(20, Ordering::Relaxed);
+        }
+        nodes[2].in_flight.store(3, Ordering::Relaxed);
+        let req = RequestMetadata::default();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 3);
+
+        // Unhealthy backups are still used rather than failing the request
+        for node in &nodes[2..] {
+            node.fail.store(20, Ordering::Relaxed);
+        }
+        assert!(picker.pick(&req).unwrap().endpoint.id >= 2);
+
+        // Predicates need not be tags: untagged node 4 is the only backup here
+        let mut nodes = nodes;
+        nodes.push(Arc::new(Node::builder().id(4).weight(1).build()));
+        let picker = Tiered::new(
+            RoundRobin,
+            |n: &Node| n.tags.get("tier").map(String::as_str) == Some("primary"),
+            |n: &Node| n.tags.is_empty(),
+        )
+        .build_picker(Arc::new(nodes))
+        .unwrap();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 4);
+    }
+
+    #[test]
+    fn test_base_balancer_try_pick() {
+        let balancer = BaseBalancer::new(ConsistentHash::default());
+        let keyed = RequestMetadata {
+            hash_key: Some(7),
+            ..Default::default()
+        };
+        assert!(balancer.try_pick(&keyed).is_none());
+
+        balancer.update_nodes(create_test_nodes(3, 10));
+        let expected = balancer.picker().unwrap().pick(&keyed).unwrap();
+        assert_eq!(
+            balancer.try_pick(&keyed).unwrap().unwrap().endpoint.id,
+            expected.endpoint.id
+        );
+
+        // Pick errors are delegated, not turned into `None`
+        assert!(matches!(
+            balancer.try_pick(&RequestMetadata::default()),
+            Some(Err(LoadBalanceError::MissingHashKey))
+        ));
+
+        balancer.update_nodes(Vec::new());
+        assert!(balancer.try_pick(&keyed).is_none());
+    }
+
+    #[test]
+    fn test_base_balancer_try_pick_reuses_picker() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        balancer.update_nodes(create_test_nodes(3, 10));
+        let req = RequestMetadata::default();
+        let ids: Vec<u64> = (0..6)
+            .map(|_| balancer.try_pick(&req).unwrap().unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 0, 1, 2]);
+
+        // A node update is picked up by the next call
+        balancer.update_nodes(create_test_nodes(1, 10));
+        assert_eq!(balancer.try_pick(&req).unwrap().unwrap().endpoint.id, 0);
+        assert_eq!(balancer.published_picker().unwrap().nodes().len(), 1);
+    }
+
+    #[test]
+    fn test_weighted_jump_keeps_wrr_shares_without_load() {
+        let nodes: Vec<Arc<Node>> = [2, 2, 2, 1]
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| Arc::new(Node::builder().id(i as u64).weight(w).build()))
+            .collect();
+        let picker = RoundRobinWithWeightedJump
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+
+        // Ties are broken at random among the equal nodes, but every cycle of 7 picks
+        // still holds each node's share
+        for _ in 0..20 {
+            let counts = count_picks(&picker, 7);
+            assert_eq!(counts[&0], 2);
+            assert_eq!(counts[&1], 2);
+            assert_eq!(counts[&2], 2);
+            assert_eq!(counts[&3], 1);
+        }
+    }
+
+    #[test]
+    fn test_weighted_jump_prefers_lighter_tied_node() {
+        let nodes = create_test_nodes(2, 0);
+        for node in &nodes {
+            node.set_weight(1);
+        }
+        nodes[0].in_flight.store(5, Ordering::Relaxed);
+        let req = RequestMetadata::default();
+
+        // Plain WRR starts with the earliest of the tied nodes
+        let wrr = WeightedRoundRobin
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        assert_eq!(wrr.pick(&req).unwrap().endpoint.id, 0);
+
+        let jump = RoundRobinWithWeightedJump
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let picked: Vec<u64> = (0..4)
+            .map(|_| jump.pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(picked, [1, 0, 1, 0]);
+
+        // Excluded nodes are not tie candidates
+        let jump = RoundRobinWithWeightedJump
+            .build_picker(Arc::new(nodes))
+            .unwrap();
+        assert_eq!(jump.pick(&req.clone().exclude(1)).unwrap().endpoint.id, 0);
+    }
+
+    #[test]
+    fn test_round_robin_uniform_under_contention() {
+        let nodes = create_test_nodes(10, 1);
+        let picker = RoundRobin.build_picker(Arc::new(nodes)).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let picker = picker.clone();
+                std::thread::spawn(move || {
+                    let req = RequestMetadata::default();
+                    let mut counts = [0usize; 10];
+                    for _ in 0..10_000 {
+                        counts[picker.pick(&req).unwrap().endpoint.id as usize] += 1;
+                    }
+                    counts
+                })
+            })
+            .collect();
+
+        let mut totals = [0usize; 10];
+        for handle in handles {
+            for (total, count) in totals.iter_mut().zip(handle.join().unwrap()) {
+                *total += count;
+            }
+        }
+        // Every pick claims its own cursor slot, so the split is exact
+        assert_eq!(totals, [8_000; 10]);
+    }
+
+    #[test]
+    fn test_update_nodes_iter() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let nodes = create_test_nodes(4, 10);
+        balancer.update_nodes_iter(nodes.iter().filter(|n| n.endpoint.id % 2 == 0).cloned());
+
+        // Diffing against nothing lists the current nodes as removed
+        assert_eq!(node_ids(&balancer.diff_nodes(&[]).removed), [0, 2]);
+        assert_eq!(balancer.generation(), 1);
+    }
+
+    #[test]
+    fn test_seeded_p2c_is_reproducible() {
+        let sequence = |strategy: &SeededPowerOfTwoChoices| -> Vec<u64> {
+            let nodes = create_test_nodes(8, 1);
+            for (i, node) in nodes.iter().enumerate() {
+                node.in_flight.store(i % 3, Ordering::Relaxed);
+            }
+            let picker = strategy.build_picker(Arc::new(nodes)).unwrap();
+            let req = RequestMetadata::default();
+            (0..200)
+                .map(|_| picker.pick(&req).unwrap().endpoint.id)
+                .collect()
+        };
+
+        let strategy = PowerOfTwoChoices::seeded(42);
+        let first = sequence(&strategy);
+        assert_eq!(first, sequence(&strategy));
+        assert_eq!(first, sequence(&PowerOfTwoChoices::seeded(42)));
+        assert_ne!(first, sequence(&PowerOfTwoChoices::seeded(43)));
+    }
+
+    #[test]
+    fn test_gradient_descent_shifts_traffic_to_fast_node() {
+        let strategy = GradientDescent::new(0.1, Duration::ZERO);
+        let nodes = create_test_nodes(2, 1);
+        let picker = strategy.build_picker(Arc::new(nodes)).unwrap();
+        let req = RequestMetadata::default();
+        let rtt = |node: &Node| match node.endpoint.id {
+            0 => Duration::from_millis(1),
+            _ => Duration::from_millis(10),
+        };
+
+        // Node 0 answers 10x faster; every response takes a step with a zero interval
+        for _ in 0..2_000 {
+            let node = picker.pick(&req).unwrap();
+            picker.on_response(&node, rtt(&node));
+        }
+        assert!(strategy.weight(1).unwrap() < strategy.weight(0).unwrap());
+
+        let fast = (0..1_000)
+            .filter(|_| picker.pick(&req).unwrap().endpoint.id == 0)
+            .count();
+        assert!(fast >= 800, "fast node got {fast} of 1000 picks");
+
+        // Weights are kept across rebuilds and excluded nodes are never picked
+        let rebuilt = strategy
+            .build_picker(Arc::new(create_test_nodes(2, 1)))
+            .unwrap();
+        let req = RequestMetadata::default().exclude(0);
+        for _ in 0..100 {
+            assert_eq!(rebuilt.pick(&req).unwrap().endpoint.id, 1);
+        }
+        assert!(strategy.weight(1).unwrap() < 1.0);
+    }
+
+    #[test]
+    fn test_pick_index_agrees_with_pick() {
+        // Deterministic strategies: a twin picker built from the same list sees the same
+        // sequence through `pick_index`
+        let deterministic: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("rr", Box::new(RoundRobin)),
+            ("wrr", Box::new(WeightedRoundRobin)),
+            ("ch", Box::new(ConsistentHash::default())),
+            ("lc", Box::new(LeastConnection)),
+            ("circuit_breaker", Box::new(CircuitBreaker::new(RoundRobin))),
+            ("zone_aware", Box::new(ZoneAware::new(RoundRobin, "a"))),
+        ];
+        for (name, strategy) in deterministic {
+            let nodes = Arc::new(create_test_nodes(5, 10));
+            let by_node = strategy.build_picker(nodes.clone()).unwrap();
+            let by_index = strategy.build_picker(nodes.clone()).unwrap();
+
+            for key in 0..100u64 {
+                let req = RequestMetadata {
+                    hash_key: Some(key),
+                    ..Default::default()
+                }
+                .exclude(key % 5);
+                let expected = by_node.pick(&req).unwrap();
+                let i = by_index.pick_index(&req).unwrap();
+                assert!(
+                    Arc::ptr_eq(&nodes[i], &expected),
+                    "{name} disagrees at {key}"
+                );
+            }
+        }
+
+        // Randomized strategies: the index points at an allowed node of the input list
+        let randomized: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("p2c", Box::new(PowerOfTwoChoices)),
+            ("wr", Box::new(WeightedRandom)),
+            ("peak_ewma", Box::new(PeakEwma::default())),
+            ("gradient_descent", Box::new(GradientDescent::default())),
+            ("sticky", Box::new(Sticky::new(WeightedRandom))),
+        ];
+        for (name, strategy) in randomized {
+            let nodes = Arc::new(create_test_nodes(5, 10));
+            let picker = strategy.build_picker(nodes.clone()).unwrap();
+            assert_eq!(node_ids(picker.nodes()), node_ids(&nodes), "{name}");
+
+            let req = RequestMetadata::default().exclude(2);
+            for _ in 0..100 {
+                let i = picker.pick_index(&req).unwrap();
+                assert!(i < nodes.len() && i != 2, "{name} returned index {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_weighted_random_degrades_to_uniform_when_weights_zeroed() {
+        let nodes: Vec<Arc<Node>> = [1000, 1, 1, 1]
+            .into_iter()
+            .enumerate()
+            .map(|(i, w)| Arc::new(Node::builder().id(i as u64).weight(w).build()))
+            .collect();
+        let picker = WeightedRandom
+            .build_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let share_of_first = |req: &RequestMetadata| {
+            (0..4_000)
+                .filter(|_| picker.pick(req).unwrap().endpoint.id == 0)
+                .count()
+        };
+        assert!(share_of_first(&RequestMetadata::default()) > 3_900);
+
+        // Zeroed after the build: the snapshot would keep favouring node 0
+        for node in &nodes {
+            node.set_weight(0);
+        }
+        let first = share_of_first(&RequestMetadata::default());
+        assert!((700..=1_300).contains(&first), "node 0 got {first} of 4000");
+
+        // Same on the exclusion path, among the allowed nodes
+        let first = share_of_first(&RequestMetadata::default().exclude(3));
+        assert!(
+            (1_000..=1_700).contains(&first),
+            "node 0 got {first} of 4000"
+        );
+    }
+
+    #[test]
+    fn test_caching_balancer_reuses_picker() {
+        let balancer = CachingBalancer::new(RoundRobin);
+        balancer.update_nodes(create_test_nodes(3, 1));
+
+        let first = balancer.picker().unwrap();
+        let second = balancer.picker().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        // The rotation continues across calls
+        let req = RequestMetadata::default();
+        let ids: Vec<u64> = (0..3)
+            .map(|_| balancer.picker().unwrap().pick(&req).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, [0, 1, 2]);
+
+        // Node and weight updates both invalidate the cache
+        balancer.update_nodes(create_test_nodes(2, 1));
+        let rebuilt = balancer.picker().unwrap();
+        assert!(!Arc::ptr_eq(&first, &rebuilt));
+        assert_eq!(rebuilt.nodes().len(), 2);
+        assert!(Arc::ptr_eq(&rebuilt, &balancer.picker().unwrap()));
+
+        balancer.balancer().update_weights(HashMap::from([(0, 5)]));
+        assert!(!Arc::ptr_eq(&rebuilt, &balancer.picker().unwrap()));
+    }
+
+    #[test]
+    fn test_for_each_node_matches_snapshot() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        let mut visited = 0;
+        balancer.for_each_node(|_| visited += 1);
+        assert_eq!(visited, 0);
+        assert!(balancer.nodes_snapshot().is_empty());
+
+        let nodes = create_test_nodes(4, 10);
+        nodes[2].in_flight.store(3, Ordering::Relaxed);
+        balancer.update_nodes(nodes);
+
+        let mut ids = Vec::new();
+        let mut in_flight = 0;
+        balancer.for_each_node(|node| {
+            ids.push(node.endpoint.id);
+            in_flight += node.in_flight.load(Ordering::Relaxed);
+        });
+        assert_eq!(ids, node_ids(&balancer.nodes_snapshot()));
+        assert_eq!(in_flight, 3);
+    }
+
+    #[test]
+    fn test_config_hash_tells_configurations_apart() {
+        let ch = ConsistentHash::default();
+        assert_eq!(ch.config_hash(), ConsistentHash::default().config_hash());
+        for other in [
+            ConsistentHash {
+                virtual_factor: 20,
+                ..Default::default()
+            },
+            ConsistentHash {
+                seed: 1,
+                ..Default::default()
+            },
+            ConsistentHash {
+                policy: VirtualNodePolicy::Fixed,
+                ..Default::default()
+            },
+            ConsistentHash::auto_virtual_factor(0.1),
+        ] {
+            assert_ne!(ch.config_hash(), other.config_hash());
+        }
+
+        // Without parameters the name decides
+        assert_eq!(RoundRobin.config_hash(), RoundRobin.config_hash());
+        assert_ne!(RoundRobin.config_hash(), WeightedRoundRobin.config_hash());
+    }
+
+    #[test]
+    fn test_config_hash_covers_parameters_and_inner_strategies() {
+        let seeded = |seed| CircuitBreaker::new(ConsistentHash::default().with_seed(seed));
+        assert_eq!(seeded(1).config_hash(), seeded(1).config_hash());
+        assert_ne!(seeded(1).config_hash(), seeded(2).config_hash());
+
+        let pairs: Vec<(Box<dyn BalanceStrategy>, Box<dyn BalanceStrategy>)> = vec![
+            (
+                Box::new(ZoneAware::new(ConsistentHash::default().with_seed(1), "a")),
+                Box::new(ZoneAware::new(ConsistentHash::default().with_seed(2), "a")),
+            ),
+            (
+                Box::new(ZoneAware::new(RoundRobin, "a")),
+                Box::new(ZoneAware::new(RoundRobin, "b")),
+            ),
+            (
+                Box::new(RandomSubset::new(3, RoundRobin)),
+                Box::new(RandomSubset::new(4, RoundRobin)),
+            ),
+            (
+                Box::new(TopologySpread::new(RoundRobin, "zone", 1)),
+                Box::new(TopologySpread::new(RoundRobin, "zone", 2)),
+            ),
+            (
+                Box::new(PeakEwma::new(
+                    Duration::from_secs(1),
+                    Duration::from_millis(30),
+                )),
+                Box::new(PeakEwma::new(
+                    Duration::from_secs(2),
+                    Duration::from_millis(30),
+                )),
+            ),
+            (
+                Box::new(GradientDescent::new(0.1, Duration::from_secs(1))),
+                Box::new(GradientDescent::new(0.2, Duration::from_secs(1))),
+            ),
+            (
+                Box::new(StrategyCompose::new(
+                    ConsistentHash::default().with_seed(1),
+                    RoundRobin,
+                )),
+                Box::new(StrategyCompose::new(
+                    ConsistentHash::default().with_seed(2),
+                    RoundRobin,
+                )),
+            ),
+        ];
+        for (a, b) in pairs {
+            assert_ne!(a.config_hash(), b.config_hash(), "{}", a.name());
+        }
+    }
+
+    #[test]
+    fn test_least_load_counts_queue_depth() {
+        let nodes = create_test_nodes(3, 1);
+        nodes[0].in_flight.store(7, Ordering::Relaxed);
+        nodes[1].in_flight.store(5, Ordering::Relaxed);
+        nodes[2].in_flight.store(2, Ordering::Relaxed);
+        nodes[2].set_queue_depth(5);
+        let nodes = Arc::new(nodes);
+        let req = RequestMetadata::default();
+
+        // 5 + 0 * 2 beats 2 + 5 * 2
+        let picker = LeastLoad::default().build_picker(nodes.clone()).unwrap();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+        let batch = picker.pick_batch_distinct(&req, 2).unwrap();
+        assert_eq!(node_ids(&batch), [1, 0]);
+
+        // Without the multiplier only in_flight counts
+        let picker = LeastLoad::new(0).build_picker(nodes.clone()).unwrap();
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+
+        nodes[2].reset_stats();
+        assert_eq!(nodes[2].queue_depth.load(Ordering::Relaxed), 0);
+    }
+
+    // Marks the requests of some services latency sensitive before delegating
+    struct ServiceRoutedPicker {
+        inner: Arc<dyn Picker>,
+        latency_sensitive: &'static [&'static str],
+    }
+
+    impl Picker for ServiceRoutedPicker {
+        fn pick(&self, req: &RequestMetadata) -> Result<Arc<Node>, LoadBalanceError> {
+            match req.service_name.as_deref() {
+                Some(service) if self.latency_sensitive.contains(&service) => self
+                    .inner
+                    .pick(&req.clone().tag(RequestMetadata::LATENCY_SENSITIVE_TAG, "1")),
+                _ => self.inner.pick(req),
+            }
+        }
+    }
+
+    #[test]
+    fn test_response_time_weighted_per_service_scoring() {
+        // Node 0: 10ms and idle; node 1: 5ms with two requests in flight
+        let nodes = create_test_nodes(2, 1);
+        nodes[0].last_rtt_ns.store(10_000_000, Ordering::Relaxed);
+        nodes[1].last_rtt_ns.store(5_000_000, Ordering::Relaxed);
+        nodes[1].in_flight.store(2, Ordering::Relaxed);
+        let picker = ServiceRoutedPicker {
+            inner: ResponseTimeWeighted.build_picker(Arc::new(nodes)).unwrap(),
+            latency_sensitive: &["search"],
+        };
+
+        // 100 / 1 beats 200 / 3, while 100^2 / 1 loses to 200^2 / 3
+        let batch = RequestMetadata::default().with_service_name("batch");
+        assert_eq!(picker.pick(&batch).unwrap().endpoint.id, 0);
+        let search = RequestMetadata::default().with_service_name("search");
+        assert_eq!(picker.pick(&search).unwrap().endpoint.id, 1);
+        assert!(!search.is_latency_sensitive());
+    }
+
+    #[test]
+    fn test_concurrency_limited_resamples_full_nodes() {
+        let nodes: Vec<Arc<Node>> = (0..3)
+            .map(|i| Arc::new(Node::builder().id(i).max_concurrency(1).build()))
+            .collect();
+        let picker = ConcurrencyLimited::new(RoundRobin)
+            .build_limited_picker(Arc::new(nodes.clone()))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        // One slot per node: three guards, then every node is full
+        let guards: Vec<_> = (0..3).map(|_| picker.pick_acquire(&req).unwrap()).collect();
+        let mut ids: Vec<u64> = guards.iter().map(|g| g.node().endpoint.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, [0, 1, 2]);
+        assert!(matches!(
+            picker.pick_acquire(&req),
+            Err(LoadBalanceError::Overloaded)
+        ));
+        assert!(matches!(
+            picker.pick(&req),
+            Err(LoadBalanceError::Overloaded)
+        ));
+
+        // Released slots are found again by resampling past the full nodes
+        drop(guards);
+        let _busy = [
+            nodes[0].try_acquire().unwrap(),
+            nodes[1].try_acquire().unwrap(),
+        ];
+        for _ in 0..5 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+        }
+        let guard = picker.pick_acquire(&req).unwrap();
+        assert_eq!(guard.node().endpoint.id, 2);
+        assert_eq!(nodes[2].in_flight.load(Ordering::Relaxed), 1);
+        drop(guard);
+        assert_eq!(nodes[2].in_flight.load(Ordering::Relaxed), 0);
+
+        // Nothing allowed at all is still `NoAvailableNodes`
+        let all = RequestMetadata::default().exclude(0).exclude(1).exclude(2);
+        assert!(matches!(
+            picker.pick_acquire(&all),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_utilization_ranks_capped_nodes() {
+        // Node 0 is at 40% of 10 slots, node 1 at 50% of 2 slots
+        let capped = || -> Arc<Vec<Arc<Node>>> {
+            let nodes: Vec<Arc<Node>> = [10, 2]
+                .into_iter()
+                .enumerate()
+                .map(|(i, cap)| {
+                    Arc::new(
+                        Node::builder()
+                            .id(i as u64)
+                            .weight(1)
+                            .max_in_flight(cap)
+                            .build(),
+                    )
+                })
+                .collect();
+            nodes[0].in_flight.store(4, Ordering::Relaxed);
+            nodes[1].in_flight.store(1, Ordering::Relaxed);
+            Arc::new(nodes)
+        };
+        let req = RequestMetadata::default();
+
+        for strategy in [
+            Box::new(LeastConnectionWeighted) as Box<dyn BalanceStrategy>,
+            Box::new(LeastLoad::default()),
+        ] {
+            let picker = strategy.build_picker(capped()).unwrap();
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+
+            // One uncapped node: back to raw counts
+            let mut nodes = capped().to_vec();
+            nodes.push(Arc::new(Node::builder().id(2).weight(1).build()));
+            nodes[2].in_flight.store(3, Ordering::Relaxed);
+            let picker = strategy.build_picker(Arc::new(nodes)).unwrap();
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+        }
+    }
+
+    #[test]
+    fn test_power_of_k_choices_tightens_imbalance() {
+        // Balls into bins: every pick adds one request that never completes, so the spread
+        // between the busiest and the idlest node is all the strategy's doing
+        let equal_nodes = |count: u64| -> Arc<Vec<Arc<Node>>> {
+            Arc::new(
+                (0..count)
+                    .map(|i| Arc::new(Node::builder().id(i).weight(1).build()))
+                    .collect(),
+            )
+        };
+        let imbalance = |k: usize| {
+            let mut total = 0;
+            for _ in 0..20 {
+                let nodes = equal_nodes(50);
+                let picker = PowerOfKChoices::new(k).build_picker(nodes.clone()).unwrap();
+                let req = RequestMetadata::default();
+                for _ in 0..1000 {
+                    picker
+                        .pick(&req)
+                        .unwrap()
+                        .in_flight
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+                let loads: Vec<usize> = nodes
+                    .iter()
+                    .map(|n| n.in_flight.load(Ordering::Relaxed))
+                    .collect();
+                total += loads.iter().max().unwrap() - loads.iter().min().unwrap();
+            }
+            total
+        };
+        let (two, three) = (imbalance(2), imbalance(3));
+        assert!(three < two, "k=3 spread {three} not below k=2 spread {two}");
+
+        // k above the node count reads every allowed node
+        let nodes = equal_nodes(3);
+        nodes[0].in_flight.store(2, Ordering::Relaxed);
+        nodes[1].in_flight.store(1, Ordering::Relaxed);
+        nodes[2].in_flight.store(5, Ordering::Relaxed);
+        let picker = PowerOfKChoices::new(10).build_picker(nodes).unwrap();
+        for _ in 0..20 {
+            assert_eq!(
+                picker
+                    .pick(&RequestMetadata::default())
+                    .unwrap()
+                    .endpoint
+                    .id,
+                1
+            );
+            let req = RequestMetadata::default().exclude(1);
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 0);
+        }
+    }
+
+    #[test]
+    fn test_retryable_balancer_excludes_failing_node() {
+        let nodes = create_test_nodes(3, 1);
+        let balancer =
+            RetryableBalancer::new(ConsistentHash::default(), RetryPolicy::ExcludePrevious);
+        balancer.update_nodes(nodes.clone());
+        let req = RequestMetadata {
+            hash_key: Some(42),
+            ..Default::default()
+        };
+        let owner = balancer.pick_with_retry(&req, 0).unwrap();
+
+        // The key's node fails every request: the second attempt skips it
+        owner.fail.fetch_add(20, Ordering::Relaxed);
+        let retried = balancer.pick_with_retry(&req, 1).unwrap();
+        assert_ne!(retried.endpoint.id, owner.endpoint.id);
+        assert!(req.exclude_ids.is_empty());
+
+        // Without retries the unhealthy node is still returned as best effort
+        let picked = balancer.pick_with_retry(&req, 0).unwrap();
+        assert_eq!(picked.endpoint.id, owner.endpoint.id);
+
+        // A custom policy sees the failed node
+        let balancer = RetryableBalancer::new(
+            ConsistentHash::default(),
+            RetryPolicy::Custom(Box::new(|req, failed| {
+                req.exclude_ids.push(failed.unwrap().endpoint.id);
+            })),
+        );
+        balancer.update_nodes(nodes);
+        let retried = balancer.pick_with_retry(&req, 1).unwrap();
+        assert_ne!(retried.endpoint.id, owner.endpoint.id);
+    }
+
+    #[test]
+    fn test_retryable_balancer_clears_hash_key() {
+        let balancer = RetryableBalancer::new(ConsistentHash::default(), RetryPolicy::ClearHashKey);
+        balancer.update_nodes(create_test_nodes(3, 1));
+
+        // No key at all: round-robin fallback instead of `MissingHashKey`, rotating
+        // across calls
+        let ids: Vec<u64> = (0..6)
+            .map(|_| {
+                balancer
+                    .pick_with_retry(&RequestMetadata::default(), 0)
+                    .unwrap()
+                    .endpoint
+                    .id
+            })
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 0, 1, 2]);
+
+        let req = RequestMetadata {
+            hash_key: Some(7),
+            ..Default::default()
+        }
+        .exclude(0)
+        .exclude(1)
+        .exclude(2);
+        assert!(matches!(
+            balancer.pick_with_retry(&req, 2),
+            Err(LoadBalanceError::NoAvailableNodes)
+        ));
+    }
+
+    #[test]
+    fn test_replace_node_keeps_stats_of_cloned_node() {
+        let balancer = BaseBalancer::new(RoundRobin);
+        balancer.update_nodes(create_test_nodes(3, 1));
+        let old = balancer.nodes_snapshot()[1].clone();
+        old.in_flight.store(2, Ordering::Relaxed);
+        old.success.store(5, Ordering::Relaxed);
+        old.fail.store(1, Ordering::Relaxed);
+        let generation = balancer.generation();
+
+        // Same id, new address
+        let endpoint = Endpoint {
+            id: 1,
+            address: "127.0.0.1:9091".into(),
+            zone: None,
+        };
+        let new_node = Arc::new(old.clone_with_metadata(endpoint, old.weight()));
+        balancer.replace_node(1, new_node.clone()).unwrap();
+
+        let nodes = balancer.nodes_snapshot();
+        assert_eq!(node_ids(&nodes), vec![0, 1, 2]);
+        assert!(Arc::ptr_eq(&nodes[1], &new_node));
+        assert_eq!(nodes[1].endpoint.address.to_string(), "127.0.0.1:9091");
+        assert_eq!(nodes[1].in_flight.load(Ordering::Relaxed), 2);
+        assert_eq!(nodes[1].success.load(Ordering::Relaxed), 5);
+        assert_eq!(nodes[1].fail.load(Ordering::Relaxed), 1);
+        assert!(balancer.generation() > generation);
+
+        assert!(matches!(
+            balancer.replace_node(7, new_node),
+            Err(LoadBalanceError::NodeNotFound(7))
+        ));
+        assert_eq!(balancer.nodes_snapshot().len(), 3);
+    }
+
+    #[test]
+    fn test_replace_node_rejects_duplicate_id() {
+        let balancer = BaseBalancer::with_config(RoundRobin, BalanceConfig::default());
+        balancer.update_nodes(create_test_nodes(3, 1));
+        let generation = balancer.generation();
+
+        // Node 0 cannot be swapped for a node that takes id 2
+        let duplicate = Arc::new(Node::builder().id(2).weight(0).build());
+        assert!(matches!(
+            balancer.replace_node(0, duplicate.clone()),
+            Err(LoadBalanceError::DuplicateNode(2))
+        ));
+        assert_eq!(node_ids(&balancer.nodes_snapshot()), vec![0, 1, 2]);
+        assert_eq!(balancer.generation(), generation);
+        // A rejected node is left as given
+        assert_eq!(duplicate.weight(), 0);
+
+        // A new, unused id is fine and gets the default weight
+        let renamed = Arc::new(Node::builder().id(5).weight(0).build());
+        balancer.replace_node(0, renamed.clone()).unwrap();
+        assert_eq!(node_ids(&balancer.nodes_snapshot()), vec![5, 1, 2]);
+        assert_eq!(renamed.weight(), 100);
+    }
+
+    #[test]
+    fn test_circuit_breaker_state_survives_partitioned_builds() {
+        // Node 0 is local, nodes 1 and 2 are remote
+        let nodes = Arc::new(tagged_nodes(&[("zone", "a"), ("zone", "b"), ("zone", "b")]));
+        let strategy = ZoneAware::new(
+            CircuitBreaker::with_config(
+                RoundRobin,
+                CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    cooldown: Duration::from_secs(60),
+                },
+            ),
+            "a",
+        );
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+
+        // A remote failure trips node 1 although the local partition was built last
+        let remote = RequestMetadata::default().exclude(0);
+        nodes[1].fail.fetch_add(1, Ordering::Relaxed);
+        let ids: Vec<u64> = (0..4)
+            .map(|_| picker.pick(&remote).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![2, 2, 2, 2]);
+
+        // Both open breakers survive a rebuild
+        nodes[0].fail.fetch_add(1, Ordering::Relaxed);
+        picker.pick(&RequestMetadata::default()).unwrap();
+        let picker = strategy.build_picker(nodes).unwrap();
+        let ids: Vec<u64> = (0..4)
+            .map(|_| {
+                picker
+                    .pick(&RequestMetadata::default())
+                    .unwrap()
+                    .endpoint
+                    .id
+            })
+            .collect();
+        assert_eq!(ids, vec![2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_balancer_prunes_state_of_removed_nodes() {
+        let nodes = create_test_nodes(3, 1);
+        let balancer = BaseBalancer::new(CircuitBreaker::with_config(
+            RoundRobin,
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                cooldown: Duration::from_secs(60),
+            },
+        ));
+        balancer.update_nodes(nodes.clone());
+        balancer.picker().unwrap();
+        assert_eq!(balancer.strategy().state(2), Some(CircuitState::Closed));
+
+        // Partial builds keep every entry, removing the node from the balancer drops it
+        balancer.picker_for_subset(nodes[..1].to_vec()).unwrap();
+        assert!(balancer.strategy().state(2).is_some());
+        balancer.update_nodes(nodes[..2].to_vec());
+        assert_eq!(balancer.strategy().state(0), Some(CircuitState::Closed));
+        assert_eq!(balancer.strategy().state(2), None);
+    }
+
+    #[test]
+    fn test_peak_ewma_estimates_survive_partitioned_builds() {
+        let nodes = Arc::new(tagged_nodes(&[("zone", "a"), ("zone", "b"), ("zone", "b")]));
+        // Clones share their estimates
+        let ewma = PeakEwma::default();
+        let strategy = ZoneAware::new(ewma.clone(), "a");
+
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+        picker.on_response(&nodes[0], Duration::from_millis(200));
+        picker.on_response(&nodes[1], Duration::from_millis(500));
+        strategy.build_picker(nodes).unwrap();
+
+        let ms = |id| ewma.estimate_ns(id).unwrap() / 1e6;
+        assert!((ms(0) - 200.0).abs() < 1.0);
+        assert!((ms(1) - 500.0).abs() < 1.0);
+        assert!((ms(2) - 30.0).abs() < 1.0);
+
+        ewma.prune(&[0, 2]);
+        assert_eq!(ewma.estimate_ns(1), None);
+    }
+
+    #[test]
+    fn test_gradient_descent_weights_survive_partitioned_builds() {
+        // Node 0 is remote, nodes 1 and 2 are local; clones share their weights
+        let nodes = Arc::new(tagged_nodes(&[("zone", "a"), ("zone", "b"), ("zone", "b")]));
+        let gradient = GradientDescent::new(0.1, Duration::ZERO);
+        let strategy = ZoneAware::new(gradient.clone(), "b");
+        let req = RequestMetadata::default();
+
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+        for _ in 0..2_000 {
+            let node = picker.pick(&req).unwrap();
+            let rtt = if node.endpoint.id == 1 { 1 } else { 10 };
+            picker.on_response(&node, Duration::from_millis(rtt));
+        }
+        let learned = gradient.weight(2).unwrap();
+        assert!(learned < gradient.weight(1).unwrap());
+
+        // Rebuilding the remote partition leaves the local weights alone
+        strategy.build_picker(nodes).unwrap();
+        assert_eq!(gradient.weight(2), Some(learned));
+        assert!(gradient.weight(0).is_some());
+    }
+
+    #[test]
+    fn test_topology_spread_keeps_breaker_state_of_every_zone() {
+        let nodes = Arc::new(tagged_nodes(&[
+            ("zone", "a"),
+            ("zone", "a"),
+            ("zone", "b"),
+            ("zone", "b"),
+        ]));
+        let strategy = TopologySpread::new(
+            CircuitBreaker::with_config(
+                RoundRobin,
+                CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    cooldown: Duration::from_secs(60),
+                },
+            ),
+            "zone",
+            1,
+        );
+        // Hold every pick open so both zones take turns
+        let picked = |picker: &Arc<dyn Picker>| -> Vec<u64> {
+            (0..20)
+                .map(|_| {
+                    let node = picker.pick(&RequestMetadata::default()).unwrap();
+                    node.in_flight.fetch_add(1, Ordering::Relaxed);
+                    node.endpoint.id
+                })
+                .collect()
+        };
+
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+        nodes[0].fail.fetch_add(1, Ordering::Relaxed);
+        nodes[2].fail.fetch_add(1, Ordering::Relaxed);
+        picked(&picker);
+
+        let picker = strategy.build_picker(nodes).unwrap();
+        let ids = picked(&picker);
+        assert!(ids.iter().all(|&id| id == 1 || id == 3), "{ids:?}");
+        assert!(ids.contains(&1) && ids.contains(&3));
+    }
+
+    #[test]
+    fn test_hot_standby_keeps_inner_estimates_of_both_pools() {
+        let nodes = Arc::new(tagged_nodes(&[("active", ""), ("standby", "")]));
+        // Clones share their estimates
+        let ewma = PeakEwma::default();
+        let strategy = HotStandby::new(ewma.clone(), "active", "standby");
+
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+        picker.on_response(&nodes[0], Duration::from_millis(200));
+        picker.on_response(&nodes[1], Duration::from_millis(500));
+        strategy.build_picker(nodes).unwrap();
+
+        let ms = |id| ewma.estimate_ns(id).unwrap() / 1e6;
+        assert!((ms(0) - 200.0).abs() < 1.0);
+        assert!((ms(1) - 500.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_tiered_keeps_primary_breaker_state_across_rebuilds() {
+        let nodes = Arc::new(tagged_nodes(&[
+            ("tier", "primary"),
+            ("tier", "primary"),
+            ("tier", "backup"),
+        ]));
+        let strategy = Tiered::by_tag(
+            CircuitBreaker::with_config(
+                RoundRobin,
+                CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    cooldown: Duration::from_secs(60),
+                },
+            ),
+            "tier",
+            "primary",
+            "backup",
+        );
+        let req = RequestMetadata::default();
+
+        // One failure opens node 0's breaker, well below `HealthFilter`'s sample minimum
+        let picker = strategy.build_picker(nodes.clone()).unwrap();
+        nodes[0].fail.fetch_add(1, Ordering::Relaxed);
+        for _ in 0..4 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+        }
+
+        // Building the backup pool after the primary one keeps the breaker open
+        let picker = strategy.build_picker(nodes).unwrap();
+        for _ in 0..4 {
+            assert_eq!(picker.pick(&req).unwrap().endpoint.id, 1);
+        }
+    }
+
+    #[test]
+    fn test_retryable_balancer_reuses_picker() {
+        let balancer = RetryableBalancer::new(RoundRobin, RetryPolicy::ExcludePrevious);
+        balancer.update_nodes(create_test_nodes(3, 1));
+        let req = RequestMetadata::default();
+        let ids: Vec<u64> = (0..6)
+            .map(|_| balancer.pick_with_retry(&req, 1).unwrap().endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 0, 1, 2]);
+
+        // A node update replaces the picker
+        balancer.update_nodes(create_test_nodes(1, 1));
+        assert_eq!(balancer.pick_with_retry(&req, 1).unwrap().endpoint.id, 0);
+    }
+
+    #[test]
+    fn test_partitioned_wrappers_record_each_response_once() {
+        let nodes = Arc::new(tagged_nodes(&[("zone", "a"), ("zone", "b"), ("zone", "b")]));
+        let ewma = PeakEwma::default();
+        let strategies: Vec<(&str, Box<dyn BalanceStrategy>)> = vec![
+            ("zone_aware", Box::new(ZoneAware::new(ewma.clone(), "a"))),
+            (
+                "tiered",
+                Box::new(Tiered::new(
+                    ewma.clone(),
+                    |n: &Node| n.endpoint.id == 0,
+                    |n: &Node| n.endpoint.id != 0,
+                )),
+            ),
+            (
+                "topology_spread",
+                Box::new(TopologySpread::new(ewma.clone(), "zone", 1)),
+            ),
+            (
+                "compose",
+                Box::new(StrategyCompose::new(ewma.clone(), ewma.clone())),
+            ),
+        ];
+
+        for (name, strategy) in strategies {
+            for node in nodes.iter() {
+                node.reset_stats();
+            }
+            let picker = strategy.build_picker(nodes.clone()).unwrap();
+            // Above the default RTT, so the peak estimate takes them as they are
+            picker.on_response(&nodes[0], Duration::from_millis(40));
+            picker.on_response(&nodes[2], Duration::from_millis(50));
+
+            // The inner estimates of both partitions are updated
+            assert_eq!(ewma.estimate_ns(0), Some(40_000_000.0), "{name}");
+            assert_eq!(ewma.estimate_ns(2), Some(50_000_000.0), "{name}");
+            #[cfg(feature = "rtt-histogram")]
+            {
+                assert_eq!(nodes[0].rtt_histogram.len(), 1, "{name}");
+                assert_eq!(nodes[2].rtt_histogram.len(), 1, "{name}");
+            }
+            ewma.prune(&[]);
+        }
+    }
 }
@@ -0,0 +1,95 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::Arc;
+
+use tracing_test::traced_test;
+use volo_loadbalance::{
+    node::Node,
+    strategy::{BalanceStrategy, ConsistentHash, RequestMetadata, RoundRobin, TracedStrategy},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_nodes(count: u64) -> Arc<Vec<Arc<Node>>> {
+        Arc::new(
+            (0..count)
+                .map(|i| Arc::new(Node::builder().id(i).weight(1).build()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_traced_pick_emits_endpoint_id() {
+        let picker = TracedStrategy::new(RoundRobin)
+            .build_picker(create_test_nodes(3))
+            .unwrap();
+        let req = RequestMetadata {
+            hash_key: None,
+            ..Default::default()
+        };
+
+        picker.pick(&req).unwrap();
+        let node = picker.pick(&req).unwrap();
+        assert_eq!(node.endpoint.id, 1);
+
+        assert!(logs_contain("picked node"));
+        assert!(logs_contain("endpoint_id=1"));
+        assert!(logs_contain("strategy=round_robin"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_traced_pick_records_hash_key() {
+        let picker = TracedStrategy::new(ConsistentHash::default())
+            .build_picker(create_test_nodes(3))
+            .unwrap();
+        let node = picker
+            .pick(&RequestMetadata {
+                hash_key: Some(4242),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(logs_contain(&format!("endpoint_id={}", node.endpoint.id)));
+        assert!(logs_contain("hash_key=Some(4242)"));
+        assert!(logs_contain("strategy=consistent_hash"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_traced_pick_error() {
+        let picker = TracedStrategy::new(RoundRobin)
+            .build_picker(create_test_nodes(0))
+            .unwrap();
+        assert!(picker.pick(&RequestMetadata::default()).is_err());
+
+        assert!(logs_contain("pick failed"));
+        assert!(logs_contain("no available nodes"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_traced_pick_batch_distinct_keeps_inner_override() {
+        let picker = TracedStrategy::new(RoundRobin)
+            .build_picker(create_test_nodes(3))
+            .unwrap();
+        let req = RequestMetadata::default();
+
+        let ids: Vec<u64> = picker
+            .pick_batch_distinct(&req, 2)
+            .unwrap()
+            .iter()
+            .map(|n| n.endpoint.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1]);
+        assert!(logs_contain("picked batch"));
+        assert!(logs_contain("endpoint_ids=[0, 1]"));
+
+        // RoundRobin's own batch leaves the cursor alone when it fails
+        assert!(picker.pick_batch_distinct(&req, 4).is_err());
+        assert_eq!(picker.pick(&req).unwrap().endpoint.id, 2);
+    }
+}
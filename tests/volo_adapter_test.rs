@@ -2,12 +2,13 @@
 mod volo_adapter_tests {
     use std::net::SocketAddr;
     use std::sync::Arc;
+    use std::time::Duration;
     use volo::context::Endpoint;
     use volo::discovery::{Change, Discover, Instance};
     use volo::loadbalance::LoadBalance;
     use volo::net::Address;
     use volo_loadbalance::adapter::volo_adapter::*;
-    // use volo_loadbalance::strategy::RoundRobin;
+    use volo_loadbalance::strategy::RoundRobin;
 
     // Mock service discoverer
     struct MockDiscover {
@@ -105,14 +106,478 @@ mod volo_adapter_tests {
                 &volo::discovery::StaticDiscover::new(discover.instances.clone()),
             )
             .await;
-        assert!(result.is_err());
+        let Err(volo::loadbalance::error::LoadBalanceError::Discover(err)) = result else {
+            panic!("expected a discover error");
+        };
+        assert!(matches!(
+            err.downcast_ref::<volo_loadbalance::error::LoadBalanceError>(),
+            Some(volo_loadbalance::error::LoadBalanceError::EmptyInstances { service })
+                if service == "test_service"
+        ));
     }
 
-    #[test]
-    fn test_volo_instance_iter() {
-        // This test requires more complex mocking, skipped for now
-        // In practice, VoloInstanceIter should correctly iterate instances
-        assert!(true);
+    fn test_instances() -> Vec<Arc<Instance>> {
+        vec![
+            Arc::new(Instance {
+                address: "127.0.0.1:8080".parse::<SocketAddr>().unwrap().into(),
+                weight: 10,
+                tags: Default::default(),
+            }),
+            Arc::new(Instance {
+                address: "127.0.0.1:8081".parse::<SocketAddr>().unwrap().into(),
+                weight: 20,
+                tags: Default::default(),
+            }),
+        ]
+    }
+
+    fn test_endpoint(service: &str) -> Endpoint {
+        Endpoint {
+            service_name: service.to_string().into(),
+            address: None,
+            tags: Default::default(),
+            faststr_tags: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_volo_loadbalancer_cache_ttl_eviction() {
+        let lb = VoloLoadBalancer::new_with_cache_config(
+            RoundRobin,
+            CacheConfig {
+                max_entries: 16,
+                ttl: Duration::from_millis(50),
+            },
+        );
+        let discover = volo::discovery::StaticDiscover::new(test_instances());
+
+        lb.get_picker(&test_endpoint("service_a"), &discover)
+            .await
+            .unwrap();
+        lb.get_picker(&test_endpoint("service_a"), &discover)
+            .await
+            .unwrap();
+        assert_eq!(lb.cached_pickers(), 1);
+
+        // The expired entry is dropped when the next one is inserted
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        lb.get_picker(&test_endpoint("service_b"), &discover)
+            .await
+            .unwrap();
+        assert_eq!(lb.cached_pickers(), 1);
+
+        // An expired hit is rebuilt in place rather than served
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let mut iter = lb
+            .get_picker(&test_endpoint("service_b"), &discover)
+            .await
+            .unwrap();
+        assert!(iter.next().is_some());
+        assert_eq!(lb.cached_pickers(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_volo_loadbalancer_cache_capacity_eviction() {
+        let lb = VoloLoadBalancer::new_with_cache_config(
+            RoundRobin,
+            CacheConfig {
+                max_entries: 2,
+                ttl: Duration::from_secs(60),
+            },
+        );
+        let discover = volo::discovery::StaticDiscover::new(test_instances());
+
+        for service in ["service_a", "service_b", "service_c", "service_d"] {
+            lb.get_picker(&test_endpoint(service), &discover)
+                .await
+                .unwrap();
+            assert!(lb.cached_pickers() <= 2);
+        }
+        assert_eq!(lb.cached_pickers(), 2);
+
+        // Refreshing a cached key does not evict anything
+        lb.get_picker(&test_endpoint("service_d"), &discover)
+            .await
+            .unwrap();
+        assert_eq!(lb.cached_pickers(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_volo_loadbalancer_rebalance_invalidates_cache() {
+        let lb = round_robin();
+        let discover = volo::discovery::StaticDiscover::new(test_instances());
+
+        lb.get_picker(&test_endpoint("service_a"), &discover)
+            .await
+            .unwrap();
+        lb.get_picker(&test_endpoint("service_b"), &discover)
+            .await
+            .unwrap();
+        assert_eq!(lb.cached_pickers(), 2);
+
+        lb.rebalance(Change {
+            key: (),
+            all: test_instances(),
+            added: Vec::new(),
+            updated: Vec::new(),
+            removed: Vec::new(),
+        });
+        assert_eq!(lb.cached_pickers(), 0);
+    }
+
+    fn many_instances() -> Vec<Arc<Instance>> {
+        (0..8)
+            .map(|i| {
+                Arc::new(Instance {
+                    address: format!("127.0.0.1:{}", 8080 + i)
+                        .parse::<SocketAddr>()
+                        .unwrap()
+                        .into(),
+                    weight: 10,
+                    tags: Default::default(),
+                })
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_volo_instance_iter() {
+        let lb = round_robin();
+        let discover = MockDiscover::new(test_instances());
+        let mut iter = lb
+            .get_picker(
+                &test_endpoint("iter_service"),
+                &volo::discovery::StaticDiscover::new(discover.instances.clone()),
+            )
+            .await
+            .unwrap();
+
+        let first = iter.next().unwrap();
+        let second = iter.next().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(iter.next().unwrap(), first);
+    }
+
+    #[tokio::test]
+    async fn test_volo_consistent_hash_without_key() {
+        let lb = consistent_hash();
+        let discover = MockDiscover::new(many_instances());
+        let mut iter = lb
+            .get_picker(
+                &test_endpoint("ch_service"),
+                &volo::discovery::StaticDiscover::new(discover.instances.clone()),
+            )
+            .await
+            .unwrap();
+
+        // Without a hash key the consistent hash picker yields nothing
+        assert!(iter.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_volo_consistent_hash_with_hash_key() {
+        let lb = consistent_hash();
+        let discover = MockDiscover::new(many_instances());
+        let static_discover = volo::discovery::StaticDiscover::new(discover.instances.clone());
+
+        let mut iter = lb
+            .get_picker(&test_endpoint("ch_service"), &static_discover)
+            .await
+            .unwrap()
+            .with_hash_key(42);
+        let first = iter.next().unwrap();
+        for _ in 0..10 {
+            assert_eq!(iter.next().unwrap(), first);
+        }
+
+        // Same key through a fresh (cached) picker lands on the same instance
+        let mut again = lb
+            .get_picker(&test_endpoint("ch_service"), &static_discover)
+            .await
+            .unwrap()
+            .with_hash_key(42);
+        assert_eq!(again.next().unwrap(), first);
+    }
+
+    #[tokio::test]
+    async fn test_volo_consistent_hash_with_request_hash_tag() {
+        let lb = consistent_hash();
+        let discover = MockDiscover::new(many_instances());
+        let static_discover = volo::discovery::StaticDiscover::new(discover.instances.clone());
+
+        let mut endpoint = test_endpoint("ch_service");
+        endpoint.insert(volo::loadbalance::RequestHash(42));
+        let mut tagged = lb.get_picker(&endpoint, &static_discover).await.unwrap();
+        let mut explicit = lb
+            .get_picker(&test_endpoint("ch_service"), &static_discover)
+            .await
+            .unwrap()
+            .with_hash_key(42);
+
+        let first = tagged.next().unwrap();
+        assert_eq!(explicit.next().unwrap(), first);
+        for _ in 0..10 {
+            assert_eq!(tagged.next().unwrap(), first);
+        }
+    }
+
+    struct WatchDiscover {
+        instances: Vec<Arc<Instance>>,
+        changes: async_broadcast::InactiveReceiver<Change<String>>,
+    }
+
+    impl Discover for WatchDiscover {
+        type Key = String;
+        type Error = Box<dyn std::error::Error + Send + Sync>;
+
+        fn key(&self, _endpoint: &Endpoint) -> Self::Key {
+            "test_key".to_string()
+        }
+
+        async fn discover(&self, _endpoint: &Endpoint) -> Result<Vec<Arc<Instance>>, Self::Error> {
+            Ok(self.instances.clone())
+        }
+
+        fn watch(
+            &self,
+            _keys: Option<&[Self::Key]>,
+        ) -> Option<async_broadcast::Receiver<Change<Self::Key>>> {
+            Some(self.changes.activate_cloned())
+        }
+    }
+
+    fn picked_addresses(lb: &AutoRefreshBalancer<RoundRobin>) -> Vec<String> {
+        let picker = lb.balancer().picker().unwrap();
+        let req = Default::default();
+        let mut addrs: Vec<String> = (0..6)
+            .filter_map(|_| picker.pick(&req).ok())
+            .map(|n| n.endpoint.address.to_string())
+            .collect();
+        addrs.sort();
+        addrs.dedup();
+        addrs
+    }
+
+    #[tokio::test]
+    async fn test_auto_refresh_balancer_follows_changes() {
+        let (tx, rx) = async_broadcast::broadcast(4);
+        let discover = WatchDiscover {
+            instances: test_instances(),
+            changes: rx.deactivate(),
+        };
+        let lb = AutoRefreshBalancer::new(RoundRobin);
+        lb.spawn(&discover, &test_endpoint("watched"))
+            .await
+            .unwrap();
+        assert_eq!(
+            picked_addresses(&lb),
+            vec!["127.0.0.1:8080", "127.0.0.1:8081"]
+        );
+
+        // Stats of a node that survives the change are kept
+        let picker = lb.balancer().picker().unwrap();
+        let kept = picker.pick(&Default::default()).unwrap();
+        kept.in_flight
+            .store(7, std::sync::atomic::Ordering::Relaxed);
+
+        let mut all = test_instances();
+        let kept_address = Address::try_from(&kept.endpoint.address).unwrap();
+        all.retain(|i| i.address == kept_address);
+        all.push(Arc::new(Instance {
+            address: "127.0.0.1:8082".parse::<SocketAddr>().unwrap().into(),
+            weight: 10,
+            tags: Default::default(),
+        }));
+        let generation = lb.balancer().generation();
+        tx.broadcast(Change {
+            key: "test_key".to_string(),
+            all,
+            added: Vec::new(),
+            updated: Vec::new(),
+            removed: Vec::new(),
+        })
+        .await
+        .unwrap();
+        for _ in 0..100 {
+            if lb.balancer().generation() != generation {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let mut expected = vec![
+            kept.endpoint.address.to_string(),
+            "127.0.0.1:8082".to_string(),
+        ];
+        expected.sort();
+        assert_eq!(picked_addresses(&lb), expected);
+        let picker = lb.balancer().picker().unwrap();
+        let req = Default::default();
+        let survivor = (0..2)
+            .map(|_| picker.pick(&req).unwrap())
+            .find(|n| n.endpoint.id == kept.endpoint.id)
+            .unwrap();
+        assert_eq!(
+            survivor
+                .in_flight
+                .load(std::sync::atomic::Ordering::Relaxed),
+            7
+        );
+
+        // Changes after shutdown are ignored
+        lb.shutdown();
+        tokio::task::yield_now().await;
+        let generation = lb.balancer().generation();
+        let _ = tx.try_broadcast(Change {
+            key: "test_key".to_string(),
+            all: Vec::new(),
+            added: Vec::new(),
+            updated: Vec::new(),
+            removed: Vec::new(),
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(lb.balancer().generation(), generation);
+    }
+
+    #[tokio::test]
+    async fn test_auto_refresh_balancer_defaults_zero_weights() {
+        let zero_weight = |port| {
+            Arc::new(Instance {
+                address: SocketAddr::from(([127, 0, 0, 1], port)).into(),
+                weight: 0,
+                tags: Default::default(),
+            })
+        };
+        let (tx, rx) = async_broadcast::broadcast(4);
+        let discover = WatchDiscover {
+            instances: vec![zero_weight(8080)],
+            changes: rx.deactivate(),
+        };
+        let lb = AutoRefreshBalancer::new(RoundRobin);
+        lb.spawn(&discover, &test_endpoint("watched"))
+            .await
+            .unwrap();
+        let weights = |lb: &AutoRefreshBalancer<RoundRobin>| -> Vec<u32> {
+            lb.balancer()
+                .nodes_snapshot()
+                .iter()
+                .map(|n| n.weight())
+                .collect()
+        };
+        assert_eq!(weights(&lb), [100]);
+
+        // Watched changes get the configured default too
+        let lb = AutoRefreshBalancer::with_config(
+            RoundRobin,
+            volo_loadbalance::config::BalanceConfig {
+                default_weight: 30,
+                ..Default::default()
+            },
+        );
+        lb.spawn(&discover, &test_endpoint("watched"))
+            .await
+            .unwrap();
+        let generation = lb.balancer().generation();
+        tx.broadcast(Change {
+            key: "test_key".to_string(),
+            all: vec![zero_weight(8080), zero_weight(8081)],
+            added: Vec::new(),
+            updated: Vec::new(),
+            removed: Vec::new(),
+        })
+        .await
+        .unwrap();
+        for _ in 0..100 {
+            if lb.balancer().generation() != generation {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(weights(&lb), [30, 30]);
+    }
+
+    struct DynamicDiscover {
+        instances: parking_lot::Mutex<Vec<Arc<Instance>>>,
+        changes: async_broadcast::InactiveReceiver<Change<String>>,
+    }
+
+    impl Discover for DynamicDiscover {
+        type Key = String;
+        type Error = Box<dyn std::error::Error + Send + Sync>;
+
+        fn key(&self, _endpoint: &Endpoint) -> Self::Key {
+            "test_key".to_string()
+        }
+
+        async fn discover(&self, _endpoint: &Endpoint) -> Result<Vec<Arc<Instance>>, Self::Error> {
+            Ok(self.instances.lock().clone())
+        }
+
+        fn watch(
+            &self,
+            _keys: Option<&[Self::Key]>,
+        ) -> Option<async_broadcast::Receiver<Change<Self::Key>>> {
+            Some(self.changes.activate_cloned())
+        }
+    }
+
+    fn instance(port: u16) -> Arc<Instance> {
+        Arc::new(Instance {
+            address: SocketAddr::from(([127, 0, 0, 1], port)).into(),
+            weight: 10,
+            tags: Default::default(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_volo_loadbalancer_dynamic_discover() {
+        let (tx, rx) = async_broadcast::broadcast(4);
+        let discover = DynamicDiscover {
+            instances: parking_lot::Mutex::new(vec![instance(8080), instance(8081)]),
+            changes: rx.deactivate(),
+        };
+        let lb = VoloLoadBalancer::new_dynamic(RoundRobin, &discover);
+        let endpoint = test_endpoint("dynamic_service");
+
+        let ports = |iter: VoloInstanceIter| -> Vec<u16> {
+            let mut ports: Vec<u16> = iter
+                .take(6)
+                .map(|addr| match addr {
+                    Address::Ip(addr) => addr.port(),
+                    #[cfg(unix)]
+                    Address::Unix(_) => unreachable!(),
+                })
+                .collect();
+            ports.sort_unstable();
+            ports.dedup();
+            ports
+        };
+        let iter = lb.get_picker(&endpoint, &discover).await.unwrap();
+        assert_eq!(ports(iter), vec![8080, 8081]);
+        assert_eq!(lb.cached_pickers(), 1);
+
+        // One event removes 8080 and adds 8082
+        let all = vec![instance(8081), instance(8082)];
+        *discover.instances.lock() = all.clone();
+        tx.broadcast(Change {
+            key: "test_key".to_string(),
+            all,
+            added: vec![instance(8082)],
+            updated: Vec::new(),
+            removed: vec![instance(8080)],
+        })
+        .await
+        .unwrap();
+        for _ in 0..100 {
+            if lb.cached_pickers() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(lb.cached_pickers(), 0);
+
+        let iter = lb.get_picker(&endpoint, &discover).await.unwrap();
+        assert_eq!(ports(iter), vec![8081, 8082]);
     }
 
     #[test]
@@ -123,17 +588,115 @@ mod volo_adapter_tests {
         let _p2c = power_of_two_choices();
         let _wr = weighted_random();
         let _lc = least_connection();
+        let _lcw = least_connection_weighted();
         let _rtw = response_time_weighted();
         let _ch = consistent_hash();
+    }
 
-        assert!(true);
+    #[test]
+    fn test_update_nodes_from_instances() {
+        use std::sync::atomic::Ordering;
+        use volo_loadbalance::strategy::BaseBalancer;
+
+        let config = volo_loadbalance::config::BalanceConfig {
+            default_weight: 50,
+            ..Default::default()
+        };
+        let balancer = BaseBalancer::with_config(RoundRobin, config);
+        let mut instances = vec![instance(8080), instance(8081)];
+        instances.push(Arc::new(Instance {
+            address: SocketAddr::from(([127, 0, 0, 1], 8082)).into(),
+            weight: 0,
+            tags: Default::default(),
+        }));
+        balancer.update_nodes_from_instances(&instances);
+
+        // Diffing against nothing lists the current nodes as removed
+        let nodes = balancer.diff_nodes(&[]).removed;
+        let weights: Vec<u32> = nodes.iter().map(|n| n.weight()).collect();
+        assert_eq!(weights, [10, 10, 50]);
+        assert_eq!(nodes[0].endpoint.address.to_string(), "127.0.0.1:8080");
+        nodes[0].success.store(7, Ordering::Relaxed);
+
+        // Same ids on the next update: the nodes, and their stats, are kept
+        balancer.update_nodes_from_instances(&instances[..2]);
+        let updated = balancer.diff_nodes(&[]).removed;
+        assert_eq!(updated.len(), 2);
+        assert!(Arc::ptr_eq(&updated[0], &nodes[0]));
+        assert_eq!(updated[0].success.load(Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    fn test_cache_key_includes_strategy_config() {
+        let endpoint = Endpoint {
+            service_name: "test_service".to_string().into(),
+            address: None,
+            tags: Default::default(),
+            faststr_tags: Default::default(),
+        };
+        let with_factor = |virtual_factor| {
+            VoloLoadBalancer::new(volo_loadbalance::strategy::ConsistentHash {
+                virtual_factor,
+                ..Default::default()
+            })
+        };
+
+        let key = with_factor(10).cache_key(&endpoint, &());
+        assert_eq!(key, with_factor(10).cache_key(&endpoint, &()));
+        assert_ne!(key, with_factor(20).cache_key(&endpoint, &()));
+        assert!(key.starts_with("test_service:"));
+    }
+
+    // Round robin that keeps the node list of the last picker it built
+    #[derive(Clone, Default)]
+    struct RecordingStrategy {
+        nodes: Arc<parking_lot::Mutex<Vec<Arc<volo_loadbalance::node::Node>>>>,
+    }
+
+    impl volo_loadbalance::strategy::BalanceStrategy for RecordingStrategy {
+        fn build_picker(
+            &self,
+            nodes: Arc<Vec<Arc<volo_loadbalance::node::Node>>>,
+        ) -> Result<
+            Arc<dyn volo_loadbalance::strategy::Picker>,
+            volo_loadbalance::error::LoadBalanceError,
+        > {
+            *self.nodes.lock() = nodes.to_vec();
+            RoundRobin.build_picker(nodes)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_zero_weight_instances_get_default_weight() {
+        let mut instances = test_instances();
+        Arc::make_mut(&mut instances[0]).weight = 0;
+        let discover = volo::discovery::StaticDiscover::new(instances);
+        let weights = |strategy: &RecordingStrategy| -> Vec<u32> {
+            strategy.nodes.lock().iter().map(|n| n.weight()).collect()
+        };
+
+        let strategy = RecordingStrategy::default();
+        let lb = VoloLoadBalancer::new(strategy.clone());
+        lb.get_picker(&test_endpoint("weights"), &discover)
+            .await
+            .unwrap();
+        assert_eq!(weights(&strategy), [100, 20]);
+
+        let strategy = RecordingStrategy::default();
+        let config = volo_loadbalance::config::BalanceConfig {
+            default_weight: 7,
+            ..Default::default()
+        };
+        let lb = VoloLoadBalancer::with_config(strategy.clone(), config);
+        lb.get_picker(&test_endpoint("weights"), &discover)
+            .await
+            .unwrap();
+        assert_eq!(weights(&strategy), [7, 20]);
     }
 }
 
 #[cfg(not(feature = "volo-adapter"))]
 mod volo_adapter_tests {
     #[test]
-    fn test_volo_adapter_disabled() {
-        assert!(true);
-    }
+    fn test_volo_adapter_disabled() {}
 }